@@ -7,6 +7,7 @@ fn main() {
     println!("cargo:rerun-if-changed=../user/c/src");
     println!("cargo:rerun-if-changed=../user/rust/src");
     println!("cargo:rerun-if-changed=.makeargs");
+    println!("cargo:rerun-if-env-changed=INIT");
 
     let arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap();
     let platform = if cfg!(feature = "platform-pc") {
@@ -48,6 +48,46 @@ impl TrapFrame {
         tf
     }
 
+    pub fn is_user(&self) -> bool {
+        // SPSR_EL1.M[3:0] == EL0t
+        self.spsr & 0b1111 == 0
+    }
+
+    /// Whether IRQs were enabled before the trap.
+    pub fn irqs_enabled(&self) -> bool {
+        // SPSR_EL1.I is not set
+        self.spsr & (1 << 7) == 0
+    }
+
+    pub const fn user_sp(&self) -> usize {
+        self.usp as _
+    }
+
+    pub const fn ret_value(&self) -> usize {
+        self.r[0] as _
+    }
+
+    /// Sets up to call the signal handler `entry(sig)` on the user stack `sp`, the
+    /// handler returns to `restorer`.
+    pub fn set_signal_handler(&mut self, entry: usize, sig: usize, sp: usize, restorer: usize) {
+        self.usp = sp as _;
+        self.r[30] = restorer as _; // lr
+        self.r[0] = sig as _;
+        self.elr = entry as _;
+    }
+
+    /// Restores user registers saved in a signal frame, only the condition flags of
+    /// `SPSR_EL1` are restored. Always succeeds, as returning to a kernel address faults
+    /// in user mode.
+    pub fn restore_user_regs(&mut self, saved: &Self) -> bool {
+        const NZCV: u64 = 0xf << 28;
+        self.r = saved.r;
+        self.usp = saved.usp;
+        self.elr = saved.elr;
+        self.spsr = (saved.spsr & NZCV) | (self.spsr & !NZCV);
+        true
+    }
+
     pub unsafe fn exec(&self, kstack_top: VirtAddr) -> ! {
         info!(
             "user task start: entry={:#x}, ustack={:#x}, kstack={:#x}",
@@ -129,6 +169,26 @@ impl TaskContext {
         };
     }
 
+    /// Sets the user thread pointer of a new user task to `tls`, or to the one of the
+    /// current task if it is not given. It is `TPIDR_EL0` on aarch64.
+    pub fn init_user_tls(&mut self, _tf: &mut TrapFrame, tls: Option<usize>) {
+        self.tpidr_el0 = match tls {
+            Some(tls) => tls as u64,
+            None => {
+                let tpidr_el0;
+                unsafe { asm!("mrs {}, tpidr_el0", out(reg) tpidr_el0) };
+                tpidr_el0
+            }
+        };
+    }
+
+    /// Sets the user thread pointer of the current task to `tls`.
+    pub fn set_user_tls(&mut self, _tf: &mut TrapFrame, tls: usize) {
+        // saved from the register on the next context switch
+        self.tpidr_el0 = tls as u64;
+        unsafe { asm!("msr tpidr_el0, {}", in(reg) self.tpidr_el0) };
+    }
+
     pub fn switch_to(&mut self, next_ctx: &Self) {
         unsafe {
             instructions::set_user_page_table_root(next_ctx.ttbr0_el1 as usize);
@@ -4,10 +4,15 @@ use cortex_a::registers::{ESR_EL1, FAR_EL1, VBAR_EL1};
 use tock_registers::interfaces::{Readable, Writeable};
 
 use super::TrapFrame;
-use crate::{syscall::syscall, task};
+use crate::mm::MemFlags;
+use crate::syscall::syscall;
+use crate::task::{self, signal};
 
 global_asm!(include_str!("trap.S"));
 
+/// Write not Read bit in the ISS of data aborts.
+const ISS_DABT_WNR: u64 = 1 << 6;
+
 pub fn init() {
     extern "C" {
         fn exception_vector_base();
@@ -54,34 +59,29 @@ fn handle_sync_exception(tf: &mut TrapFrame) {
         esr.read(ESR_EL1::ISS),
         tf
     );
+    task::account_trap_enter(tf.is_user());
     match esr.read_as_enum(ESR_EL1::EC) {
-        Some(ESR_EL1::EC::Value::Unknown) => {
-            warn!("Unknown exception @ {:#x}, kernel killed it.", tf.elr);
-            task::current().exit(-1);
+        Some(ESR_EL1::EC::Value::Unknown) if tf.is_user() => {
+            warn!("Unknown exception @ {:#x}, send SIGILL.", tf.elr);
+            signal::force_signal(signal::SIGILL);
         }
         Some(ESR_EL1::EC::Value::SVC64) => {
-            tf.r[0] = syscall(tf, tf.r[8] as _, tf.r[0] as _, tf.r[1] as _, tf.r[2] as _) as u64
+            let args = [tf.r[0], tf.r[1], tf.r[2], tf.r[3], tf.r[4], tf.r[5]];
+            tf.r[0] = syscall(tf, tf.r[8] as _, args.map(|r| r as _)) as u64
         }
         Some(ESR_EL1::EC::Value::DataAbortLowerEL)
-        | Some(ESR_EL1::EC::Value::InstrAbortLowerEL) => {
+        | Some(ESR_EL1::EC::Value::DataAbortCurrentEL) => {
             let iss = esr.read(ESR_EL1::ISS);
-            warn!(
-                "Page Fault @ {:#x}, FAR={:#x}, ISS={:#x}, kernel killed it.",
-                tf.elr,
-                FAR_EL1.get(),
-                iss
-            );
-            task::current().exit(-1);
+            let access = if iss & ISS_DABT_WNR != 0 {
+                MemFlags::WRITE
+            } else {
+                MemFlags::READ
+            };
+            handle_page_fault(tf, access, iss);
         }
-        Some(ESR_EL1::EC::Value::DataAbortCurrentEL)
+        Some(ESR_EL1::EC::Value::InstrAbortLowerEL)
         | Some(ESR_EL1::EC::Value::InstrAbortCurrentEL) => {
-            let iss = esr.read(ESR_EL1::ISS);
-            panic!(
-                "Kernel Page Fault @ {:#x}, FAR={:#x}, ISS={:#x}, kernel killed it.",
-                tf.elr,
-                FAR_EL1.get(),
-                iss
-            );
+            handle_page_fault(tf, MemFlags::EXECUTE, esr.read(ESR_EL1::ISS));
         }
         _ => {
             panic!(
@@ -93,9 +93,37 @@ fn handle_sync_exception(tf: &mut TrapFrame) {
             );
         }
     }
+    if tf.is_user() {
+        signal::handle_signals(tf);
+    }
+    task::account_trap_exit();
+}
+
+fn handle_page_fault(tf: &TrapFrame, access: MemFlags, iss: u64) {
+    let vaddr = FAR_EL1.get() as usize;
+    if task::handle_page_fault(vaddr, access, tf.irqs_enabled()) {
+        return;
+    }
+    if tf.is_user() {
+        warn!(
+            "Page Fault @ {:#x}, FAR={:#x}, ISS={:#x}, send SIGSEGV.",
+            tf.elr, vaddr, iss
+        );
+        signal::force_signal(signal::SIGSEGV);
+    } else {
+        panic!(
+            "Kernel Page Fault @ {:#x}, FAR={:#x}, ISS={:#x}",
+            tf.elr, vaddr, iss
+        );
+    }
 }
 
 #[no_mangle]
-fn handle_irq_exception(_tf: &mut TrapFrame) {
+fn handle_irq_exception(tf: &mut TrapFrame) {
+    task::account_trap_enter(tf.is_user());
     task::handle_irq(0);
+    if tf.is_user() {
+        signal::handle_signals(tf);
+    }
+    task::account_trap_exit();
 }
@@ -79,6 +79,42 @@ impl TrapFrame {
         tf
     }
 
+    pub fn is_user(&self) -> bool {
+        const SPP: usize = 1 << 8;
+        self.sstatus & SPP == 0
+    }
+
+    /// Whether IRQs were enabled before the trap.
+    pub fn irqs_enabled(&self) -> bool {
+        const SPIE: usize = 1 << 5;
+        self.sstatus & SPIE != 0
+    }
+
+    pub const fn user_sp(&self) -> usize {
+        self.regs.sp
+    }
+
+    pub const fn ret_value(&self) -> usize {
+        self.regs.a0
+    }
+
+    /// Sets up to call the signal handler `entry(sig)` on the user stack `sp`, the
+    /// handler returns to `restorer`.
+    pub fn set_signal_handler(&mut self, entry: usize, sig: usize, sp: usize, restorer: usize) {
+        self.regs.sp = sp;
+        self.regs.ra = restorer;
+        self.regs.a0 = sig;
+        self.sepc = entry;
+    }
+
+    /// Restores user registers saved in a signal frame, `sstatus` is not changed. Always
+    /// succeeds, as returning to a kernel address faults in user mode.
+    pub fn restore_user_regs(&mut self, saved: &Self) -> bool {
+        self.regs = saved.regs;
+        self.sepc = saved.sepc;
+        true
+    }
+
     pub unsafe fn exec(&self, kstack_top: VirtAddr) -> ! {
         info!(
             "user task start: entry={:#x}, ustack={:#x}, kstack={:#x}",
@@ -152,6 +188,20 @@ impl TaskContext {
         self.satp = page_table_root.as_usize();
     }
 
+    /// Sets the user thread pointer of a new user task to `tls`, or to the one of the
+    /// current task if it is not given. It is `tp` on riscv, which is restored from the
+    /// trap frame, as the kernel uses `tp` for per-CPU data.
+    pub fn init_user_tls(&mut self, tf: &mut TrapFrame, tls: Option<usize>) {
+        if let Some(tls) = tls {
+            tf.regs.tp = tls;
+        }
+    }
+
+    /// Sets the user thread pointer of the current task to `tls`.
+    pub fn set_user_tls(&mut self, tf: &mut TrapFrame, tls: usize) {
+        tf.regs.tp = tls;
+    }
+
     pub fn switch_to(&mut self, next_ctx: &Self) {
         unsafe {
             instructions::set_user_page_table_root(next_ctx.satp);
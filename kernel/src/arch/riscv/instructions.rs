@@ -59,3 +59,11 @@ pub fn flush_icache_all() {
 pub fn wait_for_ints() {
     unsafe { riscv::asm::wfi() }
 }
+
+/// Waits for an IRQ and enables IRQs, `wfi` returns on pending IRQs even if they are
+/// disabled, so no IRQ is missed.
+#[inline]
+pub fn enable_irqs_and_wait() {
+    wait_for_ints();
+    enable_irqs();
+}
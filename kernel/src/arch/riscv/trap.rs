@@ -2,7 +2,9 @@ use riscv::register::scause::{self, Exception as E, Trap};
 use riscv::register::{mtvec::TrapMode, stval, stvec};
 
 use super::TrapFrame;
-use crate::{syscall::syscall, task};
+use crate::mm::MemFlags;
+use crate::syscall::syscall;
+use crate::task::{self, signal};
 
 include_asm_marcos!();
 
@@ -22,30 +24,34 @@ pub fn init() {
 fn riscv_trap_handler(tf: &mut TrapFrame, from_user: bool) {
     let scause = scause::read();
     trace!("trap {:?} @ {:#x}: {:#x?}", scause.cause(), tf.sepc, tf);
+    task::account_trap_enter(from_user);
     match scause.cause() {
         Trap::Exception(E::UserEnvCall) => {
             tf.sepc += 4;
-            tf.regs.a0 = syscall(tf, tf.regs.a7, tf.regs.a0, tf.regs.a1, tf.regs.a2) as _;
+            let args = [
+                tf.regs.a0, tf.regs.a1, tf.regs.a2, tf.regs.a3, tf.regs.a4, tf.regs.a5,
+            ];
+            tf.regs.a0 = syscall(tf, tf.regs.a7, args) as _;
         }
-        Trap::Exception(E::LoadPageFault)
-        | Trap::Exception(E::StorePageFault)
-        | Trap::Exception(E::InstructionPageFault) => {
-            if from_user {
-                warn!(
-                    "Page Fault @ {:#x}, stval={:#x}, scause={}, kernel killed it.",
-                    tf.sepc,
-                    stval::read(),
-                    scause.code(),
-                );
-                task::current().exit(-1);
-            } else {
-                panic!(
-                    "Kernel Page Fault @ {:#x}, stval={:#x}, scause={}",
-                    tf.sepc,
-                    stval::read(),
-                    scause.code(),
-                );
-            }
+        Trap::Exception(E::LoadPageFault) => handle_page_fault(tf, MemFlags::READ),
+        Trap::Exception(E::StorePageFault) => handle_page_fault(tf, MemFlags::WRITE),
+        Trap::Exception(E::InstructionPageFault) => handle_page_fault(tf, MemFlags::EXECUTE),
+        Trap::Exception(E::IllegalInstruction) if from_user => {
+            warn!("Illegal Instruction @ {:#x}, send SIGILL.", tf.sepc);
+            signal::force_signal(signal::SIGILL);
+        }
+        Trap::Exception(E::LoadFault)
+        | Trap::Exception(E::StoreFault)
+        | Trap::Exception(E::InstructionFault)
+            if from_user =>
+        {
+            warn!(
+                "{:?} @ {:#x}, stval={:#x}, send SIGSEGV.",
+                scause.cause(),
+                tf.sepc,
+                stval::read(),
+            );
+            signal::force_signal(signal::SIGSEGV);
         }
         Trap::Interrupt(_) => task::handle_irq(scause.bits()),
         _ => {
@@ -57,4 +63,27 @@ fn riscv_trap_handler(tf: &mut TrapFrame, from_user: bool) {
             );
         }
     }
+    if from_user {
+        signal::handle_signals(tf);
+    }
+    task::account_trap_exit();
+}
+
+fn handle_page_fault(tf: &TrapFrame, access: MemFlags) {
+    let vaddr = stval::read();
+    if task::handle_page_fault(vaddr, access, tf.irqs_enabled()) {
+        return;
+    }
+    if tf.is_user() {
+        warn!(
+            "Page Fault @ {:#x}, stval={:#x}, access={:?}, send SIGSEGV.",
+            tf.sepc, vaddr, access,
+        );
+        signal::force_signal(signal::SIGSEGV);
+    } else {
+        panic!(
+            "Kernel Page Fault @ {:#x}, stval={:#x}, access={:?}",
+            tf.sepc, vaddr, access,
+        );
+    }
 }
@@ -1,10 +1,11 @@
 use core::arch::asm;
 
-use x86_64::registers::rflags::RFlags;
+use x86_64::registers::{model_specific::FsBase, rflags::RFlags};
 
 use super::gdt::{UCODE64_SELECTOR, UDATA_SELECTOR};
 use crate::arch::instructions;
-use crate::mm::{PhysAddr, VirtAddr};
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
+use crate::mm::{PhysAddr, UserOutPtr, VirtAddr};
 use crate::percpu::PerCpu;
 
 #[repr(C)]
@@ -76,6 +77,60 @@ impl TrapFrame {
         self.cs & 0b11 == 3
     }
 
+    /// Whether IRQs were enabled before the trap.
+    pub fn irqs_enabled(&self) -> bool {
+        RFlags::from_bits_truncate(self.rflags).contains(RFlags::INTERRUPT_FLAG)
+    }
+
+    pub const fn user_sp(&self) -> usize {
+        self.user_rsp as _
+    }
+
+    pub const fn ret_value(&self) -> usize {
+        self.rax as _
+    }
+
+    /// Sets up to call the signal handler `entry(sig)` on the user stack `sp`, the
+    /// handler returns to `restorer`.
+    pub fn set_signal_handler(&mut self, entry: usize, sig: usize, sp: usize, restorer: usize) {
+        // push the return address
+        let sp = sp - core::mem::size_of::<usize>();
+        UserOutPtr::<usize>::from(sp).write(restorer);
+        self.user_rsp = sp as _;
+        self.rip = entry as _;
+        self.rdi = sig as _;
+        self.rflags &= !RFlags::DIRECTION_FLAG.bits();
+    }
+
+    /// Restores user registers saved in a signal frame, the segment selectors and
+    /// privileged flags are not changed. Returns `false` if the saved `rip` is not a user
+    /// address.
+    ///
+    /// `sysret` clobbers `rcx` and `r11`, so signal handlers must return with `int 0x80`.
+    pub fn restore_user_regs(&mut self, saved: &Self) -> bool {
+        // returning to a non-canonical `rip` faults in the kernel
+        if !(USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&(saved.rip as _)) {
+            return false;
+        }
+        let user_flags = (RFlags::CARRY_FLAG
+            | RFlags::PARITY_FLAG
+            | RFlags::AUXILIARY_CARRY_FLAG
+            | RFlags::ZERO_FLAG
+            | RFlags::SIGN_FLAG
+            | RFlags::DIRECTION_FLAG
+            | RFlags::OVERFLOW_FLAG)
+            .bits();
+        *self = Self {
+            vector: self.vector,
+            error_code: self.error_code,
+            cs: UCODE64_SELECTOR.0 as _,
+            rflags: (saved.rflags & user_flags) | (self.rflags & !user_flags),
+            user_ss: UDATA_SELECTOR.0 as _,
+            ..*saved
+        };
+        true
+    }
+
     pub unsafe fn exec(&self, kstack_top: VirtAddr) -> ! {
         info!(
             "user task start: entry={:#x}, ustack={:#x}, kstack={:#x}",
@@ -161,13 +216,29 @@ impl TaskContext {
         self.cr3 = page_table_root.as_usize() as u64;
     }
 
+    /// Sets the user thread pointer of a new user task to `tls`, or to the one of the
+    /// current task if it is not given. It is the FS base on x86_64.
+    pub fn init_user_tls(&mut self, _tf: &mut TrapFrame, tls: Option<usize>) {
+        self.fs_base = match tls {
+            Some(tls) => tls as u64,
+            None => FsBase::read().as_u64(),
+        };
+    }
+
+    /// Sets the user thread pointer of the current task to `tls`.
+    pub fn set_user_tls(&mut self, _tf: &mut TrapFrame, tls: usize) {
+        self.fs_base = tls as u64;
+        FsBase::write(x86_64::VirtAddr::new(self.fs_base));
+    }
+
     pub fn switch_to(&mut self, next_ctx: &Self) {
         unsafe {
             PerCpu::current_arch_data()
                 .as_mut()
                 .set_kernel_stack_top(next_ctx.kstack_top);
             instructions::set_user_page_table_root(next_ctx.cr3 as usize);
-            // TODO: swtich fs_base
+            // only changed by `init_user_tls()` and `set_user_tls()`, so it is not saved
+            FsBase::write(x86_64::VirtAddr::new(next_ctx.fs_base));
             context_switch(&mut self.rsp, &next_ctx.rsp)
         }
     }
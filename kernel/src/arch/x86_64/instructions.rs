@@ -58,3 +58,10 @@ pub fn wait_for_ints() {
         x86_64::instructions::hlt();
     }
 }
+
+/// Enables IRQs and halts until the next one atomically, an IRQ can not be taken
+/// between them as `sti` takes effect after the next instruction.
+#[inline]
+pub fn enable_irqs_and_wait() {
+    x86_64::instructions::interrupts::enable_and_hlt();
+}
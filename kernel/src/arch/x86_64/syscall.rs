@@ -7,7 +7,7 @@ use x86_64::registers::rflags::RFlags;
 use super::context::TrapFrame;
 use super::gdt::{KCODE64_SELECTOR, KDATA_SELECTOR, UCODE64_SELECTOR, UDATA_SELECTOR};
 use super::percpu::{PERCPU_KERNEL_RSP_OFFSET, PERCPU_USER_RSP_OFFSET};
-use crate::syscall::syscall;
+use crate::{syscall::syscall, task};
 
 global_asm!(
     include_str!("syscall.S"),
@@ -17,7 +17,11 @@ global_asm!(
 
 #[no_mangle]
 fn x86_syscall_handler(tf: &mut TrapFrame) {
-    tf.rax = syscall(tf, tf.rax as _, tf.rdi as _, tf.rsi as _, tf.rdx as _) as u64;
+    task::account_trap_enter(true);
+    let args = [tf.rdi, tf.rsi, tf.rdx, tf.r10, tf.r8, tf.r9];
+    tf.rax = syscall(tf, tf.rax as _, args.map(|r| r as _)) as u64;
+    task::signal::handle_signals(tf);
+    task::account_trap_exit();
 }
 
 pub fn init_percpu() {
@@ -3,46 +3,45 @@ use core::arch::global_asm;
 use x86::{controlregs::cr2, irq::*};
 
 use super::context::TrapFrame;
-use crate::{syscall::syscall, task};
+use crate::mm::MemFlags;
+use crate::syscall::syscall;
+use crate::task::{self, signal};
 
 global_asm!(include_str!("trap.S"));
 
 const SYSCALL_VECTOR: u8 = 0x80;
 
+/// Bits of the page fault error code.
+const PF_WRITE: u64 = 1 << 1;
+const PF_INSTRUCTION: u64 = 1 << 4;
+
 const IRQ_VECTOR_START: u8 = 0x20;
 const IRQ_VECTOR_END: u8 = 0xff;
 
 #[no_mangle]
 fn x86_trap_handler(tf: &mut TrapFrame) {
     trace!("trap {} @ {:#x}: {:#x?}", tf.vector, tf.rip, tf);
+    task::account_trap_enter(tf.is_user());
     match tf.vector as u8 {
-        PAGE_FAULT_VECTOR => {
-            if tf.is_user() {
-                warn!(
-                    "Page Fault @ {:#x}, fault_vaddr={:#x}, error_code={:#x}, kernel killed it.",
-                    tf.rip,
-                    unsafe { cr2() },
-                    tf.error_code,
-                );
-                task::current().exit(-1);
-            } else {
-                panic!(
-                    "Kernel Page Fault @ {:#x}, fault_vaddr={:#x}, error_code={:#x}",
-                    tf.rip,
-                    unsafe { cr2() },
-                    tf.error_code,
-                );
-            }
-        }
-        GENERAL_PROTECTION_FAULT_VECTOR => {
+        PAGE_FAULT_VECTOR => handle_page_fault(tf),
+        GENERAL_PROTECTION_FAULT_VECTOR if tf.is_user() => {
             warn!(
-                "General Protection Exception @ {:#x}, error_code = {:#x}, kernel killed it.",
+                "General Protection Exception @ {:#x}, error_code = {:#x}, send SIGSEGV.",
                 tf.rip, tf.error_code,
             );
-            task::current().exit(-1);
+            signal::force_signal(signal::SIGSEGV);
+        }
+        INVALID_OPCODE_VECTOR if tf.is_user() => {
+            warn!("Invalid Opcode @ {:#x}, send SIGILL.", tf.rip);
+            signal::force_signal(signal::SIGILL);
+        }
+        DIVIDE_ERROR_VECTOR if tf.is_user() => {
+            warn!("Divide Error @ {:#x}, send SIGFPE.", tf.rip);
+            signal::force_signal(signal::SIGFPE);
         }
         SYSCALL_VECTOR => {
-            tf.rax = syscall(tf, tf.rax as _, tf.rdi as _, tf.rsi as _, tf.rdx as _) as u64
+            let args = [tf.rdi, tf.rsi, tf.rdx, tf.r10, tf.r8, tf.r9];
+            tf.rax = syscall(tf, tf.rax as _, args.map(|r| r as _)) as u64
         }
         IRQ_VECTOR_START..=IRQ_VECTOR_END => {
             task::handle_irq(tf.vector as usize);
@@ -54,4 +53,34 @@ fn x86_trap_handler(tf: &mut TrapFrame) {
             );
         }
     }
+    if tf.is_user() {
+        signal::handle_signals(tf);
+    }
+    task::account_trap_exit();
+}
+
+fn handle_page_fault(tf: &TrapFrame) {
+    let vaddr = unsafe { cr2() };
+    let access = if tf.error_code & PF_WRITE != 0 {
+        MemFlags::WRITE
+    } else if tf.error_code & PF_INSTRUCTION != 0 {
+        MemFlags::EXECUTE
+    } else {
+        MemFlags::READ
+    };
+    if task::handle_page_fault(vaddr, access, tf.irqs_enabled()) {
+        return;
+    }
+    if tf.is_user() {
+        warn!(
+            "Page Fault @ {:#x}, fault_vaddr={:#x}, error_code={:#x}, send SIGSEGV.",
+            tf.rip, vaddr, tf.error_code,
+        );
+        signal::force_signal(signal::SIGSEGV);
+    } else {
+        panic!(
+            "Kernel Page Fault @ {:#x}, fault_vaddr={:#x}, error_code={:#x}",
+            tf.rip, vaddr, tf.error_code,
+        );
+    }
 }
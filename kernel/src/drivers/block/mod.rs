@@ -8,12 +8,14 @@ use easy_fs::BlockDevice;
 use lazy_static::*;
 
 lazy_static! {
-    pub static ref BLOCK_DEVICE: Arc<dyn BlockDevice> = Arc::new(BlockDeviceImpl::new());
+    /// `None` if there is no block device.
+    pub static ref BLOCK_DEVICE: Option<Arc<dyn BlockDevice>> =
+        BlockDeviceImpl::probe().map(|dev| Arc::new(dev) as _);
 }
 
 #[allow(unused)]
 pub fn block_device_test() {
-    let block_device = BLOCK_DEVICE.clone();
+    let block_device = BLOCK_DEVICE.clone().expect("no block device");
     let mut write_buffer = [0u8; 512];
     let mut read_buffer = [0u8; 512];
     for i in 0..512 {
@@ -1,20 +1,28 @@
 use super::BlockDevice;
-use crate::sync::{UPIntrFreeCell};
-use virtio_drivers::{ VirtIOBlk, VirtIOHeader};
+use crate::config::MMIO_REGIONS;
 use crate::drivers::bus::virtio::VirtioHal;
-#[allow(unused)]
+use crate::mm::address::phys_to_virt;
+use crate::sync::UPIntrFreeCell;
+use virtio_drivers::{DeviceType, VirtIOBlk, VirtIOHeader};
+
 const VIRTIO0: usize = 0x10004000;
 pub struct VirtIOBlock(UPIntrFreeCell<VirtIOBlk<'static, VirtioHal>>);
 
-
 impl VirtIOBlock {
-    #[allow(unused)]
-    pub fn new() -> Self {
-        unsafe {
-            Self(UPIntrFreeCell::new(
-                VirtIOBlk::<VirtioHal>::new(&mut *(VIRTIO0 as *mut VirtIOHeader)).unwrap(),
-            ))
+    /// Returns `None` if the device is not mapped on this platform or not attached.
+    pub fn probe() -> Option<Self> {
+        let mapped = MMIO_REGIONS
+            .iter()
+            .any(|&(base, size)| (base..base + size).contains(&VIRTIO0));
+        if !mapped {
+            return None;
+        }
+        let header = unsafe { &mut *(phys_to_virt(VIRTIO0) as *mut VirtIOHeader) };
+        if !header.verify() || !matches!(header.device_type(), DeviceType::Block) {
+            return None;
         }
+        let blk = VirtIOBlk::<VirtioHal>::new(header).ok()?;
+        Some(Self(unsafe { UPIntrFreeCell::new(blk) }))
     }
 }
 
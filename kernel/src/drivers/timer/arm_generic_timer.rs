@@ -30,9 +30,9 @@ pub fn set_oneshot_timer(deadline_ns: u64) {
     let cnptct = CNTPCT_EL0.get();
     let cnptct_deadline = NANOS_TO_CNTPCT_RATIO.mul(deadline_ns);
     if cnptct < cnptct_deadline {
+        // TVAL is a signed 32-bit value, fire early if the deadline is too far away
         let interval = cnptct_deadline - cnptct;
-        debug_assert!(interval <= u32::MAX as u64);
-        CNTP_TVAL_EL0.set(interval);
+        CNTP_TVAL_EL0.set(interval.min(i32::MAX as u64));
     } else {
         CNTP_TVAL_EL0.set(0);
     }
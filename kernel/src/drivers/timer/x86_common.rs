@@ -14,9 +14,9 @@ pub fn set_oneshot_timer(deadline_ns: u64) {
     let now_ns = current_time_nanos();
     unsafe {
         if now_ns < deadline_ns {
+            // fire early if the deadline is too far away, the next deadline will be set then
             let apic_ticks = NANOS_TO_LAPIC_TICKS_RATIO.mul(deadline_ns - now_ns);
-            assert!(apic_ticks <= u32::MAX as u64);
-            local_apic().set_timer_initial(apic_ticks.max(1) as u32);
+            local_apic().set_timer_initial(apic_ticks.clamp(1, u32::MAX as u64) as u32);
         } else {
             local_apic().set_timer_initial(1);
         }
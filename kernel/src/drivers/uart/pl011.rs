@@ -5,12 +5,12 @@ use tock_registers::register_structs;
 use tock_registers::registers::{ReadOnly, ReadWrite, WriteOnly};
 
 use crate::mm::{PhysAddr, VirtAddr};
-use crate::sync::Mutex;
+use crate::sync::SpinNoIrqLock;
 
 const UART_BASE: PhysAddr = PhysAddr::new(0x0900_0000);
 const UART_IRQ_NUM: usize = 33;
 
-static UART: Mutex<Pl011Uart> = Mutex::new(Pl011Uart::new(UART_BASE.into_kvaddr()));
+static UART: SpinNoIrqLock<Pl011Uart> = SpinNoIrqLock::new(Pl011Uart::new(UART_BASE.into_kvaddr()));
 
 register_structs! {
     Pl011UartRegs {
@@ -2,12 +2,12 @@
 
 use x86_64::instructions::port::{Port, PortReadOnly, PortWriteOnly};
 
-use crate::sync::Mutex;
+use crate::sync::SpinNoIrqLock;
 
 const UART_CLOCK_FACTOR: usize = 16;
 const OSC_FREQ: usize = 1_843_200;
 
-static COM1: Mutex<Uart16550> = Mutex::new(Uart16550::new(0x3f8));
+static COM1: SpinNoIrqLock<Uart16550> = SpinNoIrqLock::new(Uart16550::new(0x3f8));
 
 bitflags::bitflags! {
     /// Line status flags
@@ -1,7 +1,7 @@
 
 use vga::colors::{Color16, TextModeColor};
 use vga::writers::{ScreenCharacter, Text80x25, TextWriter};
-use crate::sync::Mutex;
+use crate::sync::SpinNoIrqLock;
 
 pub fn init() {
     vga::vga::VGA.lock().set_memory_start(0xffffff80000a0000);
@@ -31,7 +31,7 @@ pub struct VGAStatus {
 }
 
 lazy_static::lazy_static! {
-    static ref VGA_STATUS: Mutex<VGAStatus> = Mutex::new(VGAStatus::default());
+    static ref VGA_STATUS: SpinNoIrqLock<VGAStatus> = SpinNoIrqLock::new(VGAStatus::default());
 }
 
 impl VGAStatus {
@@ -4,12 +4,12 @@
 //!
 //! `UPIntrFreeCell<OSInodeInner>` -> `OSInode`: for static `ROOT_INODE`,we
 //! need to wrap `OSInodeInner` into `UPIntrFreeCell`
-use super::File;
+use super::{page_cache, File};
 use crate::drivers::block::BLOCK_DEVICE;
 use crate::mm::UserBuffer;
 use crate::sync::UPIntrFreeCell;
 use alloc::sync::Arc;
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use bitflags::*;
 use easy_fs::{EasyFileSystem, Inode};
 use lazy_static::*;
@@ -41,7 +41,7 @@ impl OSInode {
         let mut buffer = [0u8; 512];
         let mut v: Vec<u8> = Vec::new();
         loop {
-            let len = inner.inode.read_at(inner.offset, &mut buffer);
+            let len = page_cache::read_at(&inner.inode, inner.offset, &mut buffer);
             if len == 0 {
                 break;
             }
@@ -53,16 +53,19 @@ impl OSInode {
 }
 
 lazy_static! {
-    pub static ref ROOT_INODE: Arc<Inode> = {
-        let efs = EasyFileSystem::open(BLOCK_DEVICE.clone());
+    /// `None` if there is no block device.
+    pub static ref ROOT_INODE: Option<Arc<Inode>> = BLOCK_DEVICE.clone().map(|dev| {
+        let efs = EasyFileSystem::open(dev);
         Arc::new(EasyFileSystem::root_inode(&efs))
-    };
+    });
 }
 /// List all files in the filesystems
 pub fn list_apps() {
     println!("/**** APPS ****");
-    for app in ROOT_INODE.ls() {
-        println!("{}", app);
+    if let Some(root) = ROOT_INODE.as_ref() {
+        for app in root.ls() {
+            println!("{}", app);
+        }
     }
     println!("**************/");
 }
@@ -76,10 +79,14 @@ bitflags! {
         const WRONLY = 1 << 0;
         ///Read & Write
         const RDWR = 1 << 1;
+        ///Fail if created but already exists, only for IPC objects
+        const EXCL = 1 << 7;
         ///Allow create
         const CREATE = 1 << 9;
         ///Clear file and return an empty one
         const TRUNC = 1 << 10;
+        ///Do not block, only for message queues
+        const NONBLOCK = 1 << 11;
     }
 }
 
@@ -87,39 +94,44 @@ impl OpenFlags {
     /// Do not check validity for simplicity
     /// Return (readable, writable)
     pub fn read_write(&self) -> (bool, bool) {
-        if self.is_empty() {
-            (true, false)
-        } else if self.contains(Self::WRONLY) {
+        if self.contains(Self::WRONLY) {
             (false, true)
-        } else {
+        } else if self.contains(Self::RDWR) {
             (true, true)
+        } else {
+            (true, false)
         }
     }
 }
 ///Open file with flags
 pub fn open_file(name: &str, flags: OpenFlags) -> Option<Arc<OSInode>> {
+    let root = ROOT_INODE.as_ref()?;
     let (readable, writable) = flags.read_write();
     if flags.contains(OpenFlags::CREATE) {
-        if let Some(inode) = ROOT_INODE.find(name) {
+        if let Some(inode) = root.find(name) {
             // clear size
             inode.clear();
+            page_cache::invalidate(&inode);
             Some(Arc::new(OSInode::new(readable, writable, inode)))
         } else {
             // create file
-            ROOT_INODE
-                .create(name)
+            root.create(name)
                 .map(|inode| Arc::new(OSInode::new(readable, writable, inode)))
         }
     } else {
-        ROOT_INODE.find(name).map(|inode| {
+        root.find(name).map(|inode| {
             if flags.contains(OpenFlags::TRUNC) {
                 inode.clear();
+                page_cache::invalidate(&inode);
             }
             Arc::new(OSInode::new(readable, writable, inode))
         })
     }
 }
 
+// User buffers may fault and lock the address space, which may sleep, so they are
+// copied through kernel buffers without holding the cell, whose IRQs are disabled.
+
 impl File for OSInode {
     fn readable(&self) -> bool {
         self.readable
@@ -128,27 +140,31 @@ impl File for OSInode {
         self.writable
     }
     fn read(&self, mut buf: UserBuffer) -> usize {
+        let mut data = vec![0; buf.len()];
         let mut inner = self.inner.exclusive_access();
-        let mut total_read_size = 0usize;
+        let len = page_cache::read_at(&inner.inode, inner.offset, &mut data);
+        inner.offset += len;
+        drop(inner);
+        let mut copied = 0;
         for slice in buf.buffers.iter_mut() {
-            let read_size = inner.inode.read_at(inner.offset, *slice);
-            if read_size == 0 {
-                break;
-            }
-            inner.offset += read_size;
-            total_read_size += read_size;
+            let n = slice.len().min(len - copied);
+            slice[..n].copy_from_slice(&data[copied..copied + n]);
+            copied += n;
         }
-        total_read_size
+        len
     }
     fn write(&self, buf: UserBuffer) -> usize {
-        let mut inner = self.inner.exclusive_access();
-        let mut total_write_size = 0usize;
+        let mut data = Vec::with_capacity(buf.len());
         for slice in buf.buffers.iter() {
-            let write_size = inner.inode.write_at(inner.offset, *slice);
-            assert_eq!(write_size, slice.len());
-            inner.offset += write_size;
-            total_write_size += write_size;
+            data.extend_from_slice(slice);
         }
-        total_write_size
+        let mut inner = self.inner.exclusive_access();
+        let len = page_cache::write_at(&inner.inode, inner.offset, &data);
+        assert_eq!(len, data.len());
+        inner.offset += len;
+        len
+    }
+    fn inode(&self) -> Option<Arc<Inode>> {
+        Some(self.inner.exclusive_access().inode.clone())
     }
 }
@@ -1,8 +1,14 @@
 //! File system in os
 mod inode;
+mod mqueue;
+pub mod page_cache;
+mod pipe;
+mod shm;
 mod stdio;
 
 use crate::mm::UserBuffer;
+use alloc::sync::Arc;
+use easy_fs::Inode;
 /// File trait
 pub trait File: Send + Sync {
     /// If readable
@@ -13,7 +19,26 @@ pub trait File: Send + Sync {
     fn read(&self, buf: UserBuffer) -> usize;
     /// Write `UserBuffer` to file
     fn write(&self, buf: UserBuffer) -> usize;
+    /// The inode of the file, if it can be mapped
+    fn inode(&self) -> Option<Arc<Inode>> {
+        None
+    }
+    /// The shared memory object of the file, if it is one
+    fn shm(&self) -> Option<Arc<SharedMemory>> {
+        None
+    }
+    /// The message queue of the file, if it is one
+    fn mqueue(&self) -> Option<&MqFile> {
+        None
+    }
+    /// Resizes the file to `len` bytes, returns `false` if it is not supported
+    fn truncate(&self, _len: usize) -> bool {
+        false
+    }
 }
 
 pub use inode::{list_apps, open_file, OSInode, OpenFlags};
-pub use stdio::{Stdin, Stdout};
+pub use mqueue::{open_mqueue, unlink_mqueue, MqFile, MQ_PRIO_MAX};
+pub use pipe::make_pipe;
+pub use shm::{open_shm, unlink_shm, SharedMemory, SHM_DIR};
+pub use stdio::{poll_console, Stdin, Stdout};
@@ -0,0 +1,209 @@
+//! POSIX message queues, opened by name as file descriptors.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use super::{File, OpenFlags};
+use crate::mm::UserBuffer;
+use crate::sync::SpinNoIrqLock;
+use crate::task::WaitQueue;
+
+/// The default capacity of a message queue.
+const DEFAULT_MAX_MSGS: usize = 10;
+/// The default maximum message size of a message queue.
+const DEFAULT_MSG_SIZE: usize = 8192;
+/// Limits of the attributes of a message queue.
+const MAX_MSGS_LIMIT: usize = 256;
+const MSG_SIZE_LIMIT: usize = 8192;
+/// Messages have priorities in `[0, MQ_PRIO_MAX)`.
+pub const MQ_PRIO_MAX: u32 = 32768;
+
+/// Message queues that are not unlinked.
+static MQUEUES: SpinNoIrqLock<BTreeMap<String, Arc<MessageQueue>>> =
+    SpinNoIrqLock::new(BTreeMap::new());
+
+struct Message {
+    prio: u32,
+    data: Vec<u8>,
+}
+
+/// A bounded queue of messages, received in the order of priorities, and in FIFO
+/// order for the same priority.
+pub struct MessageQueue {
+    max_msgs: usize,
+    msg_size: usize,
+    messages: SpinNoIrqLock<VecDeque<Message>>,
+    /// Senders waiting for the queue not to be full.
+    senders: WaitQueue,
+    /// Receivers waiting for the queue not to be empty.
+    receivers: WaitQueue,
+}
+
+impl MessageQueue {
+    fn new(max_msgs: usize, msg_size: usize) -> Self {
+        Self {
+            max_msgs,
+            msg_size,
+            messages: SpinNoIrqLock::new(VecDeque::new()),
+            senders: WaitQueue::new(),
+            receivers: WaitQueue::new(),
+        }
+    }
+
+    pub fn max_msgs(&self) -> usize {
+        self.max_msgs
+    }
+
+    pub fn msg_size(&self) -> usize {
+        self.msg_size
+    }
+
+    /// The number of messages in the queue.
+    pub fn num_msgs(&self) -> usize {
+        self.messages.lock().len()
+    }
+
+    fn try_send(&self, data: &[u8], prio: u32) -> bool {
+        let mut messages = self.messages.lock();
+        if messages.len() >= self.max_msgs {
+            return false;
+        }
+        let pos = messages
+            .iter()
+            .position(|m| m.prio < prio)
+            .unwrap_or(messages.len());
+        messages.insert(
+            pos,
+            Message {
+                prio,
+                data: data.into(),
+            },
+        );
+        true
+    }
+
+    fn try_receive(&self) -> Option<Message> {
+        self.messages.lock().pop_front()
+    }
+}
+
+/// An opened message queue.
+pub struct MqFile {
+    readable: bool,
+    writable: bool,
+    nonblock: bool,
+    mq: Arc<MessageQueue>,
+}
+
+impl MqFile {
+    pub fn queue(&self) -> &MessageQueue {
+        &self.mq
+    }
+
+    pub fn nonblock(&self) -> bool {
+        self.nonblock
+    }
+
+    /// Sends `data` with priority `prio`, blocks while the queue is full unless it is
+    /// opened with `OpenFlags::NONBLOCK`. Returns `false` if the message can not be
+    /// sent, or the wait is interrupted by a signal.
+    pub fn send(&self, data: &[u8], prio: u32) -> bool {
+        if !self.writable || data.len() > self.mq.msg_size || prio >= MQ_PRIO_MAX {
+            return false;
+        }
+        loop {
+            if self.mq.try_send(data, prio) {
+                self.mq.receivers.notify_one();
+                return true;
+            }
+            let not_full = || self.mq.num_msgs() < self.mq.max_msgs;
+            if self.nonblock || !self.mq.senders.wait_until_interruptible(not_full) {
+                return false;
+            }
+        }
+    }
+
+    /// Receives the oldest message with the highest priority into `buf`, which must be
+    /// able to hold the largest message. Blocks while the queue is empty unless it is
+    /// opened with `OpenFlags::NONBLOCK`. Returns the message size and priority.
+    pub fn receive(&self, buf: &mut [u8]) -> Option<(usize, u32)> {
+        if !self.readable || buf.len() < self.mq.msg_size {
+            return None;
+        }
+        loop {
+            if let Some(msg) = self.mq.try_receive() {
+                self.mq.senders.notify_one();
+                buf[..msg.data.len()].copy_from_slice(&msg.data);
+                return Some((msg.data.len(), msg.prio));
+            }
+            let not_empty = || self.mq.num_msgs() > 0;
+            if self.nonblock || !self.mq.receivers.wait_until_interruptible(not_empty) {
+                return None;
+            }
+        }
+    }
+}
+
+/// Opens the message queue `name`. It is created with `OpenFlags::CREATE` if it does
+/// not exist, with the capacity and maximum message size in `attr` or the defaults.
+pub fn open_mqueue(
+    name: &str,
+    flags: OpenFlags,
+    attr: Option<(usize, usize)>,
+) -> Option<Arc<MqFile>> {
+    if name.is_empty() || name.contains('/') {
+        return None;
+    }
+    let mut mqueues = MQUEUES.lock();
+    let mq = match mqueues.get(name) {
+        Some(_) if flags.contains(OpenFlags::CREATE | OpenFlags::EXCL) => return None,
+        Some(mq) => mq.clone(),
+        None if flags.contains(OpenFlags::CREATE) => {
+            let (max_msgs, msg_size) = attr.unwrap_or((DEFAULT_MAX_MSGS, DEFAULT_MSG_SIZE));
+            if !(1..=MAX_MSGS_LIMIT).contains(&max_msgs)
+                || !(1..=MSG_SIZE_LIMIT).contains(&msg_size)
+            {
+                return None;
+            }
+            let mq = Arc::new(MessageQueue::new(max_msgs, msg_size));
+            mqueues.insert(name.into(), mq.clone());
+            mq
+        }
+        None => return None,
+    };
+    let (readable, writable) = flags.read_write();
+    Some(Arc::new(MqFile {
+        readable,
+        writable,
+        nonblock: flags.contains(OpenFlags::NONBLOCK),
+        mq,
+    }))
+}
+
+/// Removes the name of the message queue `name`, which is freed when it is not
+/// opened anymore.
+pub fn unlink_mqueue(name: &str) -> bool {
+    MQUEUES.lock().remove(name).is_some()
+}
+
+// Messages are sent and received with their own system calls instead of `read` and
+// `write`.
+impl File for MqFile {
+    fn readable(&self) -> bool {
+        false
+    }
+    fn writable(&self) -> bool {
+        false
+    }
+    fn read(&self, _buf: UserBuffer) -> usize {
+        0
+    }
+    fn write(&self, _buf: UserBuffer) -> usize {
+        0
+    }
+    fn mqueue(&self) -> Option<&MqFile> {
+        Some(self)
+    }
+}
@@ -0,0 +1,114 @@
+//! Page cache of files above `Inode::read_at` and `Inode::write_at`. Pages are shared
+//! by the file mappings, and written back to the file by the writable shared ones.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::{vec, vec::Vec};
+use easy_fs::Inode;
+
+use crate::mm::address::{align_down, is_aligned};
+use crate::mm::{PhysFrame, PAGE_SIZE};
+use crate::sync::SpinNoIrqLock;
+
+/// Pages keyed by the inode ID and the page aligned offset in the file.
+static PAGE_CACHE: SpinNoIrqLock<BTreeMap<(usize, usize), Arc<PhysFrame>>> =
+    SpinNoIrqLock::new(BTreeMap::new());
+
+/// Returns the page at `offset` of `inode`, which is read from the file if it is not
+/// cached. Bytes beyond the end of the file are zero. Returns `None` if out of memory.
+pub fn get_page(inode: &Inode, offset: usize) -> Option<Arc<PhysFrame>> {
+    assert!(is_aligned(offset, PAGE_SIZE));
+    let key = (inode.id(), offset);
+    if let Some(frame) = PAGE_CACHE.lock().get(&key) {
+        return Some(frame.clone());
+    }
+    // the file is read without the lock, as it may block
+    let mut frame = PhysFrame::alloc_zero()?;
+    inode.read_at(offset, frame.as_slice_mut());
+    Some(
+        PAGE_CACHE
+            .lock()
+            .entry(key)
+            .or_insert_with(|| Arc::new(frame))
+            .clone(),
+    )
+}
+
+/// Writes `frame`, the page at `offset` of `inode`, back to the file. The file is not
+/// extended.
+pub fn write_page(inode: &Inode, offset: usize, frame: &PhysFrame) {
+    let size = inode.size();
+    if offset < size {
+        let len = PAGE_SIZE.min(size - offset);
+        inode.write_at(offset, &frame.as_slice()[..len]);
+    }
+}
+
+/// Drops the pages of `inode` that are not mapped anymore.
+pub fn release(inode: &Inode) {
+    let id = inode.id();
+    let mut cache = PAGE_CACHE.lock();
+    let unused: Vec<_> = cache
+        .range((id, 0)..=(id, usize::MAX))
+        .filter(|(_, frame)| Arc::strong_count(frame) == 1)
+        .map(|(&key, _)| key)
+        .collect();
+    for key in unused {
+        cache.remove(&key);
+    }
+}
+
+/// Drops all pages of `inode` after its content is changed in other ways. Pages still
+/// mapped are detached from the file.
+pub fn invalidate(inode: &Inode) {
+    let id = inode.id();
+    let mut cache = PAGE_CACHE.lock();
+    let pages = cache.split_off(&(id, 0));
+    cache.extend(pages.into_iter().filter(|&((page_id, _), _)| page_id != id));
+}
+
+/// Returns the cached pages of `inode` overlapping `[offset, offset + len)`.
+fn cached_pages(inode: &Inode, offset: usize, len: usize) -> Vec<(usize, Arc<PhysFrame>)> {
+    let id = inode.id();
+    PAGE_CACHE
+        .lock()
+        .range((id, align_down(offset, PAGE_SIZE))..(id, offset + len))
+        .map(|(&(_, page_offset), frame)| (page_offset, frame.clone()))
+        .collect()
+}
+
+// User buffers may fault and read mapped files, so they are copied through kernel
+// buffers without holding the file system or the cache locks.
+
+/// Reads from the cached pages of `inode` if any, otherwise from the file.
+pub fn read_at(inode: &Inode, offset: usize, buf: &mut [u8]) -> usize {
+    let mut data = vec![0; buf.len()];
+    let read_len = inode.read_at(offset, &mut data);
+    for (page_offset, frame) in cached_pages(inode, offset, read_len) {
+        let start = offset.max(page_offset);
+        let end = (offset + read_len).min(page_offset + PAGE_SIZE);
+        data[start - offset..end - offset]
+            .copy_from_slice(&frame.as_slice()[start - page_offset..end - page_offset]);
+    }
+    buf[..read_len].copy_from_slice(&data[..read_len]);
+    read_len
+}
+
+/// Writes to the file, and to the cached pages of `inode` to keep them up to date.
+pub fn write_at(inode: &Inode, offset: usize, buf: &[u8]) -> usize {
+    let data = buf.to_vec();
+    let write_len = inode.write_at(offset, &data);
+    for (page_offset, frame) in cached_pages(inode, offset, write_len) {
+        let start = offset.max(page_offset);
+        let end = (offset + write_len).min(page_offset + PAGE_SIZE);
+        // the frame is shared by mappings, write it through the physical address
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                frame.start_paddr().into_kvaddr().as_mut_ptr(),
+                PAGE_SIZE,
+            )[start - page_offset..end - page_offset]
+                .copy_from_slice(&data[start - offset..end - offset]);
+        }
+    }
+    write_len
+}
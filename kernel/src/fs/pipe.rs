@@ -0,0 +1,132 @@
+//! Anonymous pipes, created by `pipe` as a pair of file descriptors.
+
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::File;
+use crate::mm::UserBuffer;
+use crate::sync::SpinNoIrqLock;
+use crate::task::signal::{send_signal, SIGPIPE};
+use crate::task::{current, WaitQueue};
+
+/// The capacity of a pipe.
+const PIPE_SIZE: usize = 4096;
+
+/// The ring buffer shared by both ends of a pipe.
+struct PipeBuffer {
+    data: SpinNoIrqLock<VecDeque<u8>>,
+    read_closed: AtomicBool,
+    write_closed: AtomicBool,
+    /// Readers waiting for data or the write end to be closed.
+    readers: WaitQueue,
+    /// Writers waiting for space or the read end to be closed.
+    writers: WaitQueue,
+}
+
+/// One end of a pipe. Ends are shared by `dup` and `fork`, and closed when the last
+/// file descriptor referring to them is closed.
+pub struct Pipe {
+    readable: bool,
+    buffer: Arc<PipeBuffer>,
+}
+
+/// Creates a pipe, returns its read end and write end.
+pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(PipeBuffer {
+        data: SpinNoIrqLock::new(VecDeque::with_capacity(PIPE_SIZE)),
+        read_closed: AtomicBool::new(false),
+        write_closed: AtomicBool::new(false),
+        readers: WaitQueue::new(),
+        writers: WaitQueue::new(),
+    });
+    let read_end = Arc::new(Pipe {
+        readable: true,
+        buffer: buffer.clone(),
+    });
+    let write_end = Arc::new(Pipe {
+        readable: false,
+        buffer,
+    });
+    (read_end, write_end)
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        if self.readable {
+            self.buffer.read_closed.store(true, Ordering::Release);
+            self.buffer.writers.notify_all();
+        } else {
+            self.buffer.write_closed.store(true, Ordering::Release);
+            self.buffer.readers.notify_all();
+        }
+    }
+}
+
+// User buffers may fault, they are copied through kernel buffers without holding the
+// lock of the pipe.
+
+impl File for Pipe {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+    fn writable(&self) -> bool {
+        !self.readable
+    }
+    /// Blocks until some data is available, returns 0 if the write end is closed and
+    /// the pipe is empty, or `usize::MAX` (-1 to the user) if the wait is interrupted by
+    /// a signal.
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        let pipe = &self.buffer;
+        let has_data = || !pipe.data.lock().is_empty() || pipe.write_closed.load(Ordering::Acquire);
+        if buf.len() == 0 {
+            return 0;
+        }
+        if !pipe.readers.wait_until_interruptible(has_data) {
+            return usize::MAX;
+        }
+        let mut data = pipe.data.lock();
+        let len = buf.len().min(data.len());
+        let read: Vec<u8> = data.drain(..len).collect();
+        drop(data);
+        pipe.writers.notify_all();
+        let mut copied = 0;
+        for slice in buf.buffers.iter_mut() {
+            let n = slice.len().min(len - copied);
+            slice[..n].copy_from_slice(&read[copied..copied + n]);
+            copied += n;
+        }
+        len
+    }
+    /// Blocks until all data is written. If the read end is closed, sends `SIGPIPE` to
+    /// the current task, and returns `usize::MAX` (-1 to the user) if nothing is
+    /// written. Returns the written size if interrupted by a signal.
+    fn write(&self, buf: UserBuffer) -> usize {
+        let mut data = Vec::with_capacity(buf.len());
+        for slice in buf.buffers.iter() {
+            data.extend_from_slice(slice);
+        }
+        let pipe = &self.buffer;
+        let has_space =
+            || pipe.data.lock().len() < PIPE_SIZE || pipe.read_closed.load(Ordering::Acquire);
+        let mut written = 0;
+        loop {
+            if pipe.read_closed.load(Ordering::Acquire) {
+                send_signal(current().0, SIGPIPE);
+                return if written == 0 { usize::MAX } else { written };
+            }
+            let mut buffer = pipe.data.lock();
+            let n = (PIPE_SIZE - buffer.len()).min(data.len() - written);
+            buffer.extend(&data[written..written + n]);
+            drop(buffer);
+            written += n;
+            if n > 0 {
+                pipe.readers.notify_all();
+            }
+            if written == data.len() || !pipe.writers.wait_until_interruptible(has_space) {
+                return written;
+            }
+        }
+    }
+}
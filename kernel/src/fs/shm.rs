@@ -0,0 +1,192 @@
+//! Named shared memory objects, opened as `/dev/shm/<name>` files and mapped into
+//! several address spaces with `mmap`.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::{vec, vec::Vec};
+
+use super::{File, OpenFlags};
+use crate::mm::address::{align_down, align_up};
+use crate::mm::{PhysAddr, PhysFrame, UserBuffer, PAGE_SIZE};
+use crate::sync::SpinNoIrqLock;
+
+/// Where shared memory objects are opened by name.
+pub const SHM_DIR: &str = "/dev/shm/";
+
+/// Shared memory objects that are not unlinked.
+static SHM_OBJECTS: SpinNoIrqLock<BTreeMap<String, Arc<SharedMemory>>> =
+    SpinNoIrqLock::new(BTreeMap::new());
+
+/// Frames of a shared memory object. They are held by the object instead of the areas
+/// mapping it, and only freed when the object is dropped, so that frames still mapped
+/// are not freed when the object is shrunk.
+pub struct SharedMemory {
+    inner: SpinNoIrqLock<SharedMemoryInner>,
+}
+
+struct SharedMemoryInner {
+    size: usize,
+    frames: Vec<PhysFrame>,
+}
+
+impl SharedMemory {
+    fn new() -> Self {
+        Self {
+            inner: SpinNoIrqLock::new(SharedMemoryInner {
+                size: 0,
+                frames: Vec::new(),
+            }),
+        }
+    }
+
+    /// Resizes the object, bytes beyond the old size are zero. Returns `false` if out
+    /// of memory, and the object is left unchanged.
+    pub fn set_size(&self, size: usize) -> bool {
+        let mut inner = self.inner.lock();
+        let old_size = inner.size;
+        let old_frames = inner.frames.len();
+        // bytes left from before the object was shrunk
+        let zero_end = size.min(inner.frames.len() * PAGE_SIZE);
+        for page_start in (align_down(old_size, PAGE_SIZE)..zero_end).step_by(PAGE_SIZE) {
+            let start = old_size.max(page_start) - page_start;
+            inner.frames[page_start / PAGE_SIZE].as_slice_mut()[start..].fill(0);
+        }
+        while inner.frames.len() * PAGE_SIZE < align_up(size, PAGE_SIZE) {
+            match PhysFrame::alloc_zero() {
+                Some(frame) => inner.frames.push(frame),
+                None => {
+                    // the new frames are not mapped before the size is changed
+                    inner.frames.truncate(old_frames);
+                    return false;
+                }
+            }
+        }
+        inner.size = size;
+        true
+    }
+
+    /// Returns the frame of the page at `offset`, or `None` if it is beyond the size.
+    pub fn frame(&self, offset: usize) -> Option<PhysAddr> {
+        let inner = self.inner.lock();
+        if offset < inner.size {
+            Some(inner.frames[offset / PAGE_SIZE].start_paddr())
+        } else {
+            None
+        }
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let inner = self.inner.lock();
+        let end = inner.size.min(offset + buf.len());
+        let mut pos = offset;
+        while pos < end {
+            let (page, page_offset) = (pos / PAGE_SIZE, pos % PAGE_SIZE);
+            let n = (PAGE_SIZE - page_offset).min(end - pos);
+            buf[pos - offset..pos - offset + n]
+                .copy_from_slice(&inner.frames[page].as_slice()[page_offset..page_offset + n]);
+            pos += n;
+        }
+        end.saturating_sub(offset)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+        let mut inner = self.inner.lock();
+        let end = inner.size.min(offset + buf.len());
+        let mut pos = offset;
+        while pos < end {
+            let (page, page_offset) = (pos / PAGE_SIZE, pos % PAGE_SIZE);
+            let n = (PAGE_SIZE - page_offset).min(end - pos);
+            inner.frames[page].as_slice_mut()[page_offset..page_offset + n]
+                .copy_from_slice(&buf[pos - offset..pos - offset + n]);
+            pos += n;
+        }
+        end.saturating_sub(offset)
+    }
+}
+
+/// An opened shared memory object.
+pub struct ShmFile {
+    readable: bool,
+    writable: bool,
+    offset: SpinNoIrqLock<usize>,
+    shm: Arc<SharedMemory>,
+}
+
+/// Opens the shared memory object `name`, which is created empty with
+/// `OpenFlags::CREATE`, and must not exist with `OpenFlags::EXCL` too.
+pub fn open_shm(name: &str, flags: OpenFlags) -> Option<Arc<ShmFile>> {
+    if name.is_empty() || name.contains('/') {
+        return None;
+    }
+    let mut objects = SHM_OBJECTS.lock();
+    let shm = match objects.get(name) {
+        Some(_) if flags.contains(OpenFlags::CREATE | OpenFlags::EXCL) => return None,
+        Some(shm) => shm.clone(),
+        None if flags.contains(OpenFlags::CREATE) => {
+            let shm = Arc::new(SharedMemory::new());
+            objects.insert(name.into(), shm.clone());
+            shm
+        }
+        None => return None,
+    };
+    drop(objects);
+    if flags.contains(OpenFlags::TRUNC) {
+        shm.set_size(0);
+    }
+    let (readable, writable) = flags.read_write();
+    Some(Arc::new(ShmFile {
+        readable,
+        writable,
+        offset: SpinNoIrqLock::new(0),
+        shm,
+    }))
+}
+
+/// Removes the name of the shared memory object `name`, which is freed when it is not
+/// opened or mapped anymore.
+pub fn unlink_shm(name: &str) -> bool {
+    SHM_OBJECTS.lock().remove(name).is_some()
+}
+
+// User buffers may fault, they are copied through kernel buffers without holding the
+// lock of the object.
+
+impl File for ShmFile {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+    fn writable(&self) -> bool {
+        self.writable
+    }
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        let mut data = vec![0; buf.len()];
+        let mut offset = self.offset.lock();
+        let len = self.shm.read_at(*offset, &mut data);
+        *offset += len;
+        drop(offset);
+        let mut copied = 0;
+        for slice in buf.buffers.iter_mut() {
+            let n = slice.len().min(len - copied);
+            slice[..n].copy_from_slice(&data[copied..copied + n]);
+            copied += n;
+        }
+        len
+    }
+    fn write(&self, buf: UserBuffer) -> usize {
+        let mut data = Vec::with_capacity(buf.len());
+        for slice in buf.buffers.iter() {
+            data.extend_from_slice(slice);
+        }
+        let mut offset = self.offset.lock();
+        let len = self.shm.write_at(*offset, &data);
+        *offset += len;
+        len
+    }
+    fn truncate(&self, len: usize) -> bool {
+        self.writable && self.shm.set_size(len)
+    }
+    fn shm(&self) -> Option<Arc<SharedMemory>> {
+        Some(self.shm.clone())
+    }
+}
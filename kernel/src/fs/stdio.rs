@@ -2,12 +2,40 @@
 use super::File;
 use crate::drivers::uart::console_getchar;
 use crate::mm::UserBuffer;
+use crate::sync::SpinNoIrqLock;
 use crate::task::current;
+use crate::task::signal::{send_signal_to_user_tasks, SIGINT};
+use alloc::vec::Vec;
 ///Standard input
 pub struct Stdin;
 ///Standard output
 pub struct Stdout;
 
+/// `Ctrl-C`, sends `SIGINT` instead of being read.
+const CTRL_C: u8 = 0x03;
+
+/// Console input that is not read yet.
+static STDIN_BUFFER: SpinNoIrqLock<Vec<u8>> = SpinNoIrqLock::new(Vec::new());
+
+/// Moves the console input to the stdin buffer, called on timer interrupts so that
+/// `Ctrl-C` works when nobody reads. There are no process groups, all user tasks are
+/// in the foreground and receive `SIGINT`.
+pub fn poll_console() {
+    let mut interrupted = false;
+    let mut buf = STDIN_BUFFER.lock();
+    while let Some(c) = console_getchar() {
+        match c {
+            0 => {}
+            CTRL_C => interrupted = true,
+            c => buf.push(c),
+        }
+    }
+    drop(buf);
+    if interrupted && crate::task::is_init() {
+        send_signal_to_user_tasks(SIGINT);
+    }
+}
+
 impl File for Stdin {
     fn readable(&self) -> bool {
         true
@@ -19,18 +47,23 @@ impl File for Stdin {
         assert_eq!(user_buf.len(), 1);
         // busy loop
         loop {
-            if let Some(c) = console_getchar() {
-                if c == 0 {
-                    continue;
-                } else {
-                    unsafe {
-                        user_buf.buffers[0].as_mut_ptr().write_volatile(c);
-                    }
-                    break;
+            poll_console();
+            let mut buf = STDIN_BUFFER.lock();
+            if !buf.is_empty() {
+                let c = buf.remove(0);
+                // the user buffer may fault, do not hold the lock
+                drop(buf);
+                unsafe {
+                    user_buf.buffers[0].as_mut_ptr().write_volatile(c);
                 }
-            } else {
-                current().yield_now();
+                break;
+            }
+            drop(buf);
+            if current().has_pending_signals() {
+                // nothing is read, let the signal be delivered
+                return 0;
             }
+            current().yield_now();
         }
         1
     }
@@ -1,4 +1,8 @@
+use alloc::{borrow::Cow, sync::Arc};
 use core::arch::global_asm;
+use easy_fs::Inode;
+
+use crate::fs::{open_file, File, OpenFlags};
 
 global_asm!(include_str!(concat!(env!("OUT_DIR"), "/link_app.S")));
 
@@ -42,6 +46,15 @@ pub fn get_app_data_by_name(name: &str) -> Option<&'static [u8]> {
         .map(get_app_data)
 }
 
+/// Reads the executable `path` from the file system, or from the apps linked into the
+/// kernel if it is not there. The inode is returned to map the executable from.
+pub fn load_app(path: &str) -> Option<(Cow<'static, [u8]>, Option<Arc<Inode>>)> {
+    match open_file(path.trim_start_matches('/'), OpenFlags::RDONLY) {
+        Some(file) => Some((Cow::Owned(file.read_all()), file.inode())),
+        None => get_app_data_by_name(path).map(|data| (Cow::Borrowed(data), None)),
+    }
+}
+
 pub fn list_apps() {
     println!("/**** APPS ****");
     let app_count = get_app_count();
@@ -4,11 +4,11 @@ use log::{self, Level, LevelFilter, Log, Metadata, Record};
 
 use crate::drivers::vga::console_putchar;
 use crate::percpu::PerCpu;
-use crate::sync::Mutex;
+use crate::sync::SpinNoIrqLock;
 
 struct Stdout;
 
-static PRINT_LOCK: Mutex<()> = Mutex::new(());
+static PRINT_LOCK: SpinNoIrqLock<()> = SpinNoIrqLock::new(());
 
 impl Write for Stdout {
     fn write_str(&mut self, s: &str) -> fmt::Result {
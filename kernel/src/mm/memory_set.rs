@@ -1,11 +1,16 @@
 use alloc::collections::btree_map::{BTreeMap, Entry};
-use core::fmt;
+use alloc::collections::BTreeSet;
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+use core::{fmt, mem, mem::size_of};
+use easy_fs::Inode;
+use xmas_elf::ElfFile;
 
-use super::address::{align_down, is_aligned, phys_to_virt, virt_to_phys};
+use super::address::{align_down, align_up, is_aligned, phys_to_virt, virt_to_phys};
 use super::{MemFlags, PhysFrame, PAGE_SIZE};
 use crate::arch::{instructions, PageTable};
 use crate::config::{KERNEL_ASPACE_BASE, KERNEL_ASPACE_SIZE, USER_STACK_BASE, USER_STACK_SIZE};
-use crate::config::{MMIO_REGIONS, PHYS_MEMORY_END};
+use crate::config::{MMIO_REGIONS, PHYS_MEMORY_END, USER_ASPACE_BASE};
+use crate::fs::{page_cache, SharedMemory};
 use crate::mm::{PhysAddr, VirtAddr};
 use crate::sync::LazyInit;
 
@@ -25,21 +30,59 @@ extern "C" {
 
 static KERNEL_ASPACE: LazyInit<MemorySet> = LazyInit::new();
 
+/// The top of the user memory below the user stack, which leaves a guard page so that
+/// overflowing the user stack faults.
+const USER_STACK_GUARD: usize = USER_STACK_BASE - PAGE_SIZE;
+
+// Types of auxiliary vector entries.
+const AT_NULL: usize = 0;
+const AT_PHDR: usize = 3;
+const AT_PHENT: usize = 4;
+const AT_PHNUM: usize = 5;
+const AT_PAGESZ: usize = 6;
+const AT_ENTRY: usize = 9;
+
 enum Mapper {
     Offset(usize),
-    Framed(BTreeMap<VirtAddr, PhysFrame>),
+    /// Frames are reference counted, as they are shared copy-on-write after `fork`.
+    Framed(BTreeMap<VirtAddr, Arc<PhysFrame>>),
+    /// Frames of a shared memory object from the offset in it, which are held by the
+    /// object and not freed on unmap.
+    Shm(Arc<SharedMemory>, usize),
+}
+
+/// The file mapped by a framed area, from `offset` in the file at the area start.
+#[derive(Clone)]
+struct MappedFile {
+    inode: Arc<Inode>,
+    offset: usize,
+    /// Whether the file is opened writable, a shared mapping can only be made writable
+    /// if it is.
+    writable: bool,
+    /// Pages of a shared mapping written since they were written back. Clean pages are
+    /// mapped read-only, so that the first write faults and marks them dirty.
+    dirty: BTreeSet<VirtAddr>,
 }
 
 pub struct MapArea {
     pub start: VirtAddr,
     pub size: usize,
     pub flags: MemFlags,
+    /// Frames of a shared area stay shared after `fork` instead of copy-on-write.
+    shared: bool,
+    /// Frames of a file mapping come from the page cache.
+    file: Option<MappedFile>,
+    /// A thread stack, its inaccessible pages are guard pages.
+    stack: bool,
     mapper: Mapper,
 }
 
 pub struct MemorySet {
     pt: PageTable,
     areas: BTreeMap<VirtAddr, MapArea>,
+    /// The heap of `brk` is `[brk_start, brk)`, from the end of the loaded segments.
+    brk_start: usize,
+    brk: usize,
 }
 
 impl MapArea {
@@ -57,6 +100,9 @@ impl MapArea {
             start: start_vaddr,
             size,
             flags,
+            shared: false,
+            file: None,
+            stack: false,
             mapper: Mapper::Offset(offset),
         }
     }
@@ -68,50 +114,303 @@ impl MapArea {
             start: start_vaddr,
             size,
             flags,
+            shared: false,
+            file: None,
+            stack: false,
             mapper: Mapper::Framed(BTreeMap::new()),
         }
     }
 
+    /// A framed area shared with its duplicates. All frames are allocated up front, as
+    /// frames populated after `fork` would not be shared. Returns `None` if out of memory.
+    pub fn new_shared(start_vaddr: VirtAddr, size: usize, flags: MemFlags) -> Option<Self> {
+        let mut area = Self::new_framed(start_vaddr, size, flags);
+        area.shared = true;
+        for vaddr in (start_vaddr.as_usize()..area.end().as_usize()).step_by(PAGE_SIZE) {
+            area.map(VirtAddr::new(vaddr))?;
+        }
+        Some(area)
+    }
+
+    /// A framed area mapping `inode` from `offset`. Pages of a private mapping are
+    /// copied on write, and dirty pages of a shared mapping are written back to the
+    /// file, which is opened `writable` or not.
+    pub fn new_file(
+        start_vaddr: VirtAddr,
+        size: usize,
+        flags: MemFlags,
+        inode: Arc<Inode>,
+        offset: usize,
+        shared: bool,
+        writable: bool,
+    ) -> Self {
+        assert!(is_aligned(offset, PAGE_SIZE));
+        let mut area = Self::new_framed(start_vaddr, size, flags);
+        area.shared = shared;
+        area.file = Some(MappedFile {
+            inode,
+            offset,
+            writable,
+            dirty: BTreeSet::new(),
+        });
+        area
+    }
+
+    /// A private anonymous area for a thread stack, mapped with `MAP_STACK`. Pages made
+    /// inaccessible by `mprotect` are its guard pages.
+    pub fn new_stack(start_vaddr: VirtAddr, size: usize, flags: MemFlags) -> Self {
+        let mut area = Self::new_framed(start_vaddr, size, flags);
+        area.stack = true;
+        area
+    }
+
+    /// An area mapping the shared memory object `shm` from `offset`.
+    pub fn new_shm(
+        start_vaddr: VirtAddr,
+        size: usize,
+        flags: MemFlags,
+        shm: Arc<SharedMemory>,
+        offset: usize,
+    ) -> Self {
+        assert!(start_vaddr.is_aligned());
+        assert!(is_aligned(size, PAGE_SIZE));
+        assert!(is_aligned(offset, PAGE_SIZE));
+        Self {
+            start: start_vaddr,
+            size,
+            flags,
+            shared: true,
+            file: None,
+            stack: false,
+            mapper: Mapper::Shm(shm, offset),
+        }
+    }
+
+    pub fn end(&self) -> VirtAddr {
+        VirtAddr::new(self.start.as_usize() + self.size)
+    }
+
+    /// Duplicates the area with frames shared copy-on-write.
     pub fn dup(&self) -> Self {
         let mapper = match &self.mapper {
             Mapper::Offset(off) => Mapper::Offset(*off),
-            Mapper::Framed(orig_frames) => {
-                let mut new_frames = BTreeMap::new();
-                for (&vaddr, orig_frame) in orig_frames {
-                    let mut new_frame = PhysFrame::alloc().unwrap();
-                    new_frame
-                        .as_slice_mut()
-                        .copy_from_slice(orig_frame.as_slice());
-                    new_frames.insert(vaddr, new_frame);
-                }
-                Mapper::Framed(new_frames)
-            }
+            Mapper::Framed(frames) => Mapper::Framed(frames.clone()),
+            Mapper::Shm(shm, off) => Mapper::Shm(shm.clone(), *off),
         };
         Self {
             start: self.start,
             size: self.size,
             flags: self.flags,
+            shared: self.shared,
+            file: self.file.clone(),
+            stack: self.stack,
             mapper,
         }
     }
 
-    pub fn map(&mut self, vaddr: VirtAddr) -> PhysAddr {
+    /// Splits the area at `vaddr`, returns the upper part.
+    pub fn split_off(&mut self, vaddr: VirtAddr) -> Self {
         assert!(vaddr.is_aligned());
+        assert!(self.start < vaddr && vaddr < self.end());
+        let mapper = match &mut self.mapper {
+            Mapper::Offset(off) => Mapper::Offset(*off),
+            Mapper::Framed(frames) => Mapper::Framed(frames.split_off(&vaddr)),
+            Mapper::Shm(shm, off) => Mapper::Shm(
+                shm.clone(),
+                *off + (vaddr.as_usize() - self.start.as_usize()),
+            ),
+        };
+        let file = self.file.as_mut().map(|file| MappedFile {
+            inode: file.inode.clone(),
+            offset: file.offset + (vaddr.as_usize() - self.start.as_usize()),
+            writable: file.writable,
+            dirty: file.dirty.split_off(&vaddr),
+        });
+        let upper = Self {
+            start: vaddr,
+            size: self.end().as_usize() - vaddr.as_usize(),
+            flags: self.flags,
+            shared: self.shared,
+            file,
+            stack: self.stack,
+            mapper,
+        };
+        self.size = vaddr.as_usize() - self.start.as_usize();
+        upper
+    }
+
+    /// Whether `next` follows this area and can be appended to it.
+    fn can_merge(&self, next: &Self) -> bool {
+        self.end() == next.start
+            && self.flags == next.flags
+            && self.shared == next.shared
+            && self.stack == next.stack
+            && self.file.is_none()
+            && next.file.is_none()
+            && matches!(
+                (&self.mapper, &next.mapper),
+                (Mapper::Framed(_), Mapper::Framed(_))
+            )
+    }
+
+    fn append(&mut self, next: Self) {
+        assert!(self.can_merge(&next));
+        self.size += next.size;
+        if let (Mapper::Framed(frames), Mapper::Framed(mut next_frames)) =
+            (&mut self.mapper, next.mapper)
+        {
+            frames.append(&mut next_frames);
+        }
+    }
+
+    /// Returns the physical address of the page at `vaddr`, which is populated if it is
+    /// not. Returns `None` if out of memory or beyond a shared memory object.
+    pub fn map(&mut self, vaddr: VirtAddr) -> Option<PhysAddr> {
+        assert!(vaddr.is_aligned());
+        let area_offset = vaddr.as_usize() - self.start.as_usize();
         match &mut self.mapper {
-            Mapper::Offset(off) => PhysAddr::new(vaddr.as_usize() - *off),
+            Mapper::Offset(off) => Some(PhysAddr::new(vaddr.as_usize() - *off)),
             Mapper::Framed(frames) => match frames.entry(vaddr) {
-                Entry::Occupied(e) => e.get().start_paddr(),
-                Entry::Vacant(e) => e.insert(PhysFrame::alloc_zero().unwrap()).start_paddr(),
+                Entry::Occupied(e) => Some(e.get().start_paddr()),
+                Entry::Vacant(e) => {
+                    let frame = match &self.file {
+                        Some(file) => page_cache::get_page(&file.inode, file.offset + area_offset)?,
+                        None => Arc::new(PhysFrame::alloc_zero()?),
+                    };
+                    Some(e.insert(frame).start_paddr())
+                }
             },
+            Mapper::Shm(shm, off) => shm.frame(*off + area_offset),
+        }
+    }
+
+    /// Whether the area may be made writable, a shared file mapping may only if the file
+    /// is opened writable.
+    fn may_write(&self) -> bool {
+        !self.shared || self.file.as_ref().map_or(true, |file| file.writable)
+    }
+
+    /// Marks the page at `vaddr` of a shared file mapping as dirty.
+    fn mark_dirty(&mut self, vaddr: VirtAddr) {
+        if let Some(file) = self.file.as_mut().filter(|_| self.shared) {
+            file.dirty.insert(vaddr);
+        }
+    }
+
+    /// Writes the dirty pages of a shared file mapping in `[start, end)` back to the
+    /// file, returns the pages written, which are clean again.
+    pub fn write_back(&mut self, start: VirtAddr, end: VirtAddr) -> Vec<VirtAddr> {
+        let (file, frames) = match (&mut self.file, &self.mapper) {
+            (Some(file), Mapper::Framed(frames)) if self.shared => (file, frames),
+            _ => return Vec::new(),
+        };
+        let written: Vec<VirtAddr> = file.dirty.range(start..end).copied().collect();
+        for vaddr in written.iter() {
+            file.dirty.remove(vaddr);
+            let offset = file.offset + (vaddr.as_usize() - self.start.as_usize());
+            page_cache::write_page(&file.inode, offset, &frames[vaddr]);
         }
+        written
     }
 
-    pub fn unmap(&mut self, vaddr: VirtAddr) {
-        if let Mapper::Framed(frames) = &mut self.mapper {
-            frames.remove(&vaddr);
+    /// Flags of the page at `vaddr` in page tables. Shared frames are read-only, so that
+    /// writes fault and copy them. Private file pages are shared with the page cache.
+    /// Clean pages of a shared file mapping are read-only, so that writes mark them
+    /// dirty.
+    pub fn page_flags(&self, vaddr: VirtAddr) -> MemFlags {
+        let is_cow = match &self.mapper {
+            Mapper::Framed(frames) => {
+                !self.shared
+                    && frames
+                        .get(&vaddr)
+                        .map_or(false, |frame| Arc::strong_count(frame) > 1)
+            }
+            Mapper::Offset(_) | Mapper::Shm(..) => false,
+        };
+        let is_clean = self.shared
+            && self
+                .file
+                .as_ref()
+                .map_or(false, |file| !file.dirty.contains(&vaddr));
+        if is_cow || is_clean {
+            self.flags - MemFlags::WRITE
+        } else {
+            self.flags
         }
     }
 
+    /// Makes the frame at `vaddr` owned only by this area before it is written, a shared
+    /// frame is copied. A page of a shared file mapping is marked dirty instead. Returns
+    /// the physical address of the frame, or `None` if it is not mapped or out of memory.
+    fn copy_on_write(&mut self, vaddr: VirtAddr) -> Option<PhysAddr> {
+        match &mut self.mapper {
+            Mapper::Framed(frames) if self.shared => {
+                let paddr = frames.get(&vaddr)?.start_paddr();
+                self.file.as_ref()?;
+                self.mark_dirty(vaddr);
+                Some(paddr)
+            }
+            Mapper::Offset(_) | Mapper::Shm(..) => None,
+            Mapper::Framed(frames) => {
+                let frame = frames.get_mut(&vaddr)?;
+                if Arc::get_mut(frame).is_none() {
+                    let mut new_frame = PhysFrame::alloc()?;
+                    new_frame.as_slice_mut().copy_from_slice(frame.as_slice());
+                    *frame = Arc::new(new_frame);
+                }
+                Some(frame.start_paddr())
+            }
+        }
+    }
+
+    /// Pages in page tables and their physical addresses. Framed pages are populated
+    /// on the first access, and no pages are mapped if the area is not accessible.
+    pub fn mapped_pages(&self) -> impl Iterator<Item = (VirtAddr, PhysAddr)> + '_ {
+        let (offset, frames, shm) = match &self.mapper {
+            _ if !self.flags.contains(MemFlags::READ) => (None, None, None),
+            Mapper::Offset(off) => (Some(*off), None, None),
+            Mapper::Framed(frames) => (None, Some(frames), None),
+            Mapper::Shm(shm, off) => (None, None, Some((shm, *off))),
+        };
+        let start = self.start.as_usize();
+        let offset_pages = offset.into_iter().flat_map(move |off| {
+            (start..start + self.size)
+                .step_by(PAGE_SIZE)
+                .map(move |vaddr| (VirtAddr::new(vaddr), PhysAddr::new(vaddr - off)))
+        });
+        let framed_pages = frames.into_iter().flat_map(|frames| {
+            frames
+                .iter()
+                .map(|(&vaddr, frame)| (vaddr, frame.start_paddr()))
+        });
+        let shm_pages = shm.into_iter().flat_map(move |(shm, off)| {
+            (start..start + self.size)
+                .step_by(PAGE_SIZE)
+                .filter_map(move |vaddr| {
+                    let paddr = shm.frame(off + vaddr - start)?;
+                    Some((VirtAddr::new(vaddr), paddr))
+                })
+        });
+        offset_pages.chain(framed_pages).chain(shm_pages)
+    }
+
+    /// Pages that may be in page tables, to be unmapped. Any page of a shared memory
+    /// object may be mapped, as the object may be shrunk after its pages are mapped.
+    pub fn possibly_mapped_pages(&self) -> impl Iterator<Item = VirtAddr> + '_ {
+        let is_shm = matches!(self.mapper, Mapper::Shm(..));
+        let start = self.start.as_usize();
+        let shm_pages = is_shm.then(|| {
+            (start..start + self.size)
+                .step_by(PAGE_SIZE)
+                .map(VirtAddr::new)
+        });
+        let other_pages = (!is_shm).then(|| self.mapped_pages().map(|(vaddr, _)| vaddr));
+        shm_pages
+            .into_iter()
+            .flatten()
+            .chain(other_pages.into_iter().flatten())
+    }
+
     pub fn write_data(&mut self, offset: usize, data: &[u8]) {
         assert!(offset < self.size);
         assert!(offset + data.len() <= self.size);
@@ -124,7 +423,7 @@ impl MapArea {
             let n = (PAGE_SIZE - pgoff).min(remain);
 
             let vaddr = VirtAddr::new(self.start.as_usize() + start_align);
-            let paddr = self.map(vaddr);
+            let paddr = self.map(vaddr).expect("out of memory");
             unsafe {
                 core::slice::from_raw_parts_mut(paddr.into_kvaddr().as_mut_ptr().add(pgoff), n)
                     .copy_from_slice(&data[processed..processed + n]);
@@ -141,6 +440,8 @@ impl MemorySet {
         Self {
             pt: PageTable::new(),
             areas: BTreeMap::new(),
+            brk_start: 0,
+            brk: 0,
         }
     }
 
@@ -151,47 +452,182 @@ impl MemorySet {
                 VirtAddr::new(KERNEL_ASPACE_BASE + KERNEL_ASPACE_SIZE),
             ),
             areas: BTreeMap::new(),
+            brk_start: 0,
+            brk: 0,
         }
     }
 
+    /// Inserts `area`, which is merged with the adjacent areas with the same attributes.
     pub fn insert(&mut self, area: MapArea) {
         if area.size > 0 {
-            // TODO: check overlap
-            if let Entry::Vacant(e) = self.areas.entry(area.start) {
-                self.pt.map_area(e.insert(area));
-            } else {
-                panic!(
-                    "MemorySet::insert: MepArea starts from {:#x?} is existed!",
-                    area.start
-                );
+            if !self.is_free(area.start, area.size) {
+                panic!("MemorySet::insert: {:#x?} overlaps existing areas!", area);
             }
+            let (start, end) = (area.start, area.end());
+            self.pt.map_area(&area);
+            self.areas.insert(start, area);
+            self.merge(start, end);
         }
     }
 
-    pub fn load_user(&mut self, elf_data: &[u8]) -> (VirtAddr, VirtAddr) {
-        use xmas_elf::program::{Flags, SegmentData, Type};
-        use xmas_elf::{header, ElfFile};
+    /// Whether `[start, start + size)` does not overlap any area.
+    fn is_free(&self, start: VirtAddr, size: usize) -> bool {
+        let end = VirtAddr::new(start.as_usize() + size);
+        self.areas
+            .range(..end)
+            .next_back()
+            .map_or(true, |(_, area)| area.end() <= start)
+    }
 
-        let elf = ElfFile::new(elf_data).expect("invalid ELF file");
-        assert_eq!(
-            elf.header.pt2.type_().as_type(),
-            header::Type::Executable,
-            "ELF is not an executable object"
-        );
-        let expect_arch = if cfg!(target_arch = "x86_64") {
-            header::Machine::X86_64
-        } else if cfg!(target_arch = "aarch64") {
-            header::Machine::AArch64
-        } else if cfg!(target_arch = "riscv64") {
-            header::Machine::RISC_V
-        } else {
-            panic!("Unsupported architecture!");
+    /// Finds `size` bytes of free user memory below the guard page of the user stack, at
+    /// `hint` if it is free, otherwise the highest free range.
+    pub fn find_free_area(&self, hint: usize, size: usize) -> Option<VirtAddr> {
+        let (lower, upper) = (USER_ASPACE_BASE.max(PAGE_SIZE), USER_STACK_GUARD);
+        if is_aligned(hint, PAGE_SIZE)
+            && hint >= lower
+            && hint.checked_add(size).map_or(false, |end| end <= upper)
+            && self.is_free(VirtAddr::new(hint), size)
+        {
+            return Some(VirtAddr::new(hint));
+        }
+        let fit = |low: usize, high: usize| high.checked_sub(size).filter(|&s| s >= low.max(lower));
+        let mut end = upper;
+        for (_, area) in self.areas.range(..VirtAddr::new(upper)).rev() {
+            if let Some(start) = fit(area.end().as_usize(), end) {
+                return Some(VirtAddr::new(start));
+            }
+            end = end.min(area.start.as_usize());
+        }
+        fit(lower, end).map(VirtAddr::new)
+    }
+
+    /// Splits the area containing `vaddr`, so that no area crosses `vaddr`.
+    fn split_at(&mut self, vaddr: VirtAddr) {
+        let upper = match self.areas.range_mut(..vaddr).next_back() {
+            Some((_, area)) if vaddr < area.end() => area.split_off(vaddr),
+            _ => return,
         };
-        assert_eq!(
-            elf.header.pt2.machine().as_machine(),
-            expect_arch,
-            "invalid ELF arch"
-        );
+        self.areas.insert(vaddr, upper);
+    }
+
+    /// Merges the adjacent areas with the same attributes in `[start, end]`, and the
+    /// area before `start`.
+    fn merge(&mut self, start: VirtAddr, end: VirtAddr) {
+        let first = self
+            .areas
+            .range(..start)
+            .next_back()
+            .map_or(start, |(&vaddr, _)| vaddr);
+        let starts: Vec<VirtAddr> = self.areas.range(first..=end).map(|(&v, _)| v).collect();
+        let mut prev = None;
+        for vaddr in starts {
+            match prev {
+                Some(prev) if self.areas[&prev].can_merge(&self.areas[&vaddr]) => {
+                    let next = self.areas.remove(&vaddr).unwrap();
+                    self.areas.get_mut(&prev).unwrap().append(next);
+                }
+                _ => prev = Some(vaddr),
+            }
+        }
+    }
+
+    /// Unmaps `[start, start + size)`, areas partially in it are split.
+    pub fn unmap(&mut self, start: VirtAddr, size: usize) {
+        let end = VirtAddr::new(start.as_usize() + size);
+        self.split_at(start);
+        self.split_at(end);
+        let mut removed = self.areas.split_off(&start);
+        self.areas.append(&mut removed.split_off(&end));
+        self.release_areas(removed);
+        instructions::flush_tlb_all();
+    }
+
+    /// Unmaps the removed `areas` and writes back their shared file pages. The cached
+    /// pages not mapped anymore are released.
+    fn release_areas(&mut self, mut areas: BTreeMap<VirtAddr, MapArea>) {
+        let mut inodes = Vec::new();
+        for area in areas.values_mut() {
+            self.pt.unmap_area(area);
+            let (start, end) = (area.start, area.end());
+            area.write_back(start, end);
+            if let Some(file) = &area.file {
+                inodes.push(file.inode.clone());
+            }
+        }
+        drop(areas);
+        for inode in inodes {
+            page_cache::release(&inode);
+        }
+    }
+
+    /// The start of the first area overlapping `[start, ..)`.
+    fn first_area_from(&self, start: VirtAddr) -> VirtAddr {
+        self.areas
+            .range(..=start)
+            .next_back()
+            .map_or(start, |(&vaddr, _)| vaddr)
+    }
+
+    /// Writes back the dirty shared file pages in `[start, start + size)`, returns
+    /// `false` if it is not entirely mapped.
+    pub fn sync(&mut self, start: VirtAddr, size: usize) -> bool {
+        if !self.contains(start, size, MemFlags::empty()) {
+            return false;
+        }
+        let end = VirtAddr::new(start.as_usize() + size);
+        let first = self.first_area_from(start);
+        for area in self.areas.range_mut(first..end).map(|(_, a)| a) {
+            // clean pages are read-only again, so that later writes are tracked
+            for vaddr in area.write_back(start, end) {
+                if let Some((paddr, _)) = self.pt.query(vaddr) {
+                    self.pt.remap(vaddr, paddr, area.page_flags(vaddr));
+                }
+            }
+        }
+        instructions::flush_tlb_all();
+        true
+    }
+
+    /// Changes the flags of `[start, start + size)`, returns `false` if it is not
+    /// entirely mapped, or if a shared mapping of a read-only file would be writable.
+    pub fn protect(&mut self, start: VirtAddr, size: usize, flags: MemFlags) -> bool {
+        if !self.contains(start, size, MemFlags::empty()) {
+            return false;
+        }
+        let end = VirtAddr::new(start.as_usize() + size);
+        let first = self.first_area_from(start);
+        let mut areas = self.areas.range(first..end).map(|(_, a)| a);
+        if flags.contains(MemFlags::WRITE) && areas.any(|area| !area.may_write()) {
+            return false;
+        }
+        self.split_at(start);
+        self.split_at(end);
+        for area in self.areas.range_mut(start..end).map(|(_, a)| a) {
+            self.pt.unmap_area(area);
+            if !flags.contains(MemFlags::WRITE) {
+                area.write_back(start, end);
+            }
+            area.flags = flags;
+            self.pt.map_area(area);
+        }
+        self.merge(start, end);
+        instructions::flush_tlb_all();
+        true
+    }
+
+    /// Loads the ELF executable checked by `parse_elf()` and sets up the user stack with
+    /// `args`, `envs` and the auxiliary vector, returns the entry and the initial user
+    /// stack pointer.
+    ///
+    /// Read-only segments are mapped from `file` if the executable is read from it.
+    pub fn load_user(
+        &mut self,
+        elf: &ElfFile,
+        file: Option<&Arc<Inode>>,
+        args: &[String],
+        envs: &[String],
+    ) -> (VirtAddr, VirtAddr) {
+        use xmas_elf::program::{Flags, SegmentData, Type};
 
         impl From<Flags> for MemFlags {
             fn from(f: Flags) -> Self {
@@ -209,55 +645,216 @@ impl MemorySet {
             }
         }
 
+        let ph_offset = elf.header.pt2.ph_offset();
+        let mut phdr = 0;
+        let mut segments_end = 0;
         for ph in elf.program_iter() {
             if ph.get_type() != Ok(Type::Load) {
                 continue;
             }
+            if (ph.offset()..ph.offset() + ph.file_size()).contains(&ph_offset) {
+                phdr = (ph.virtual_addr() + ph_offset - ph.offset()) as usize;
+            }
             let vaddr = VirtAddr::new(ph.virtual_addr() as usize);
             let offset = vaddr.page_offset();
             let area_start = vaddr.align_down();
             let area_end = VirtAddr::new((ph.virtual_addr() + ph.mem_size()) as usize).align_up();
-            let data = match ph.get_data(&elf).unwrap() {
-                SegmentData::Undefined(data) => data,
-                _ => panic!("failed to get ELF segment data"),
+            let area_size = area_end.as_usize() - area_start.as_usize();
+            segments_end = segments_end.max(area_end.as_usize());
+            let flags: MemFlags = ph.flags().into();
+            let area = match file {
+                Some(inode)
+                    if !flags.contains(MemFlags::WRITE)
+                        && ph.file_size() == ph.mem_size()
+                        && ph.offset() as usize % PAGE_SIZE == offset =>
+                {
+                    let file_offset = ph.offset() as usize - offset;
+                    let inode = inode.clone();
+                    MapArea::new_file(
+                        area_start,
+                        area_size,
+                        flags,
+                        inode,
+                        file_offset,
+                        false,
+                        false,
+                    )
+                }
+                _ => {
+                    // inside the file, as checked by `parse_elf()`
+                    let data = match ph.get_data(elf).unwrap() {
+                        SegmentData::Undefined(data) => data,
+                        _ => panic!("failed to get ELF segment data"),
+                    };
+                    let mut area = MapArea::new_framed(area_start, area_size, flags);
+                    area.write_data(offset, data);
+                    area
+                }
             };
-
-            let mut area = MapArea::new_framed(
-                area_start,
-                area_end.as_usize() - area_start.as_usize(),
-                ph.flags().into(),
-            );
-            area.write_data(offset, data);
             self.insert(area);
             instructions::flush_icache_all();
         }
+        self.brk_start = segments_end;
+        self.brk = segments_end;
+        let entry = elf.header.pt2.entry_point() as usize;
+        let auxv = [
+            (AT_PHDR, phdr),
+            (AT_PHENT, elf.header.pt2.ph_entry_size() as usize),
+            (AT_PHNUM, elf.header.pt2.ph_count() as usize),
+            (AT_PAGESZ, PAGE_SIZE),
+            (AT_ENTRY, entry),
+        ];
+
         // user stack
-        self.insert(MapArea::new_framed(
+        let mut ustack = MapArea::new_framed(
             VirtAddr::new(USER_STACK_BASE),
             USER_STACK_SIZE,
             MemFlags::READ | MemFlags::WRITE | MemFlags::USER,
-        ));
+        );
+        let (ustack_sp, data) =
+            init_user_stack(USER_STACK_BASE + USER_STACK_SIZE, args, envs, &auxv);
+        ustack.write_data(ustack_sp - USER_STACK_BASE, &data);
+        self.insert(ustack);
 
-        let entry = VirtAddr::new(elf.header.pt2.entry_point() as usize);
-        let ustack_top = VirtAddr::new(USER_STACK_BASE + USER_STACK_SIZE);
-        (entry, ustack_top)
+        (VirtAddr::new(entry), VirtAddr::new(ustack_sp))
     }
 
-    pub fn clear(&mut self) {
-        for area in self.areas.values_mut() {
-            self.pt.unmap_area(area);
+    /// Moves the program break to `brk` if possible, the heap is grown or shrunk in
+    /// pages. Returns the new program break.
+    pub fn set_brk(&mut self, brk: usize) -> usize {
+        if brk < self.brk_start || brk > USER_STACK_GUARD {
+            return self.brk;
         }
-        self.areas.clear();
+        let old_end = align_up(self.brk, PAGE_SIZE);
+        let new_end = align_up(brk, PAGE_SIZE);
+        if new_end > old_end {
+            let (start, size) = (VirtAddr::new(old_end), new_end - old_end);
+            if !self.is_free(start, size) {
+                return self.brk;
+            }
+            let flags = MemFlags::READ | MemFlags::WRITE | MemFlags::USER;
+            self.insert(MapArea::new_framed(start, size, flags));
+        } else if new_end < old_end {
+            self.unmap(VirtAddr::new(new_end), old_end - new_end);
+        }
+        self.brk = brk;
+        brk
     }
 
-    pub fn dup(&self) -> Self {
+    /// Whether `[start, start + size)` is mapped by areas with all of `flags`.
+    pub fn contains(&self, start: VirtAddr, size: usize, flags: MemFlags) -> bool {
+        let end = start.as_usize() + size;
+        let mut vaddr = start;
+        while vaddr.as_usize() < end {
+            match self.areas.range(..=vaddr).next_back() {
+                Some((_, area)) if vaddr < area.end() && area.flags.contains(flags) => {
+                    vaddr = area.end();
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Whether `vaddr` is in a guard page, the page below the initial user stack or an
+    /// inaccessible page of a thread stack.
+    pub fn is_guard_page(&self, vaddr: VirtAddr) -> bool {
+        let page = vaddr.align_down();
+        if page.as_usize() == USER_STACK_GUARD {
+            return true;
+        }
+        match self.areas.range(..=page).next_back() {
+            Some((_, area)) if page < area.end() => {
+                area.stack && !area.flags.contains(MemFlags::READ)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let areas = mem::take(&mut self.areas);
+        self.release_areas(areas);
+    }
+
+    /// Duplicates the address space for `fork`. Framed pages are shared copy-on-write
+    /// instead of copied, so they become read-only in both address spaces.
+    pub fn dup(&mut self) -> Self {
         let mut ms = Self::new();
+        ms.brk_start = self.brk_start;
+        ms.brk = self.brk;
         for area in self.areas.values() {
             ms.insert(area.dup());
+            if let Mapper::Framed(frames) = &area.mapper {
+                if area.flags.contains(MemFlags::WRITE) && !area.shared {
+                    for (&vaddr, frame) in frames {
+                        let flags = area.flags - MemFlags::WRITE;
+                        self.pt.remap(vaddr, frame.start_paddr(), flags);
+                    }
+                }
+            }
         }
+        instructions::flush_tlb_all();
         ms
     }
 
+    /// Handles a page fault at `vaddr` caused by `access`, one of `READ`, `WRITE` or
+    /// `EXECUTE`. Returns `false` if the access is not allowed.
+    ///
+    /// Pages that are not populated yet are allocated, and writes to copy-on-write
+    /// pages copy the shared frames.
+    pub fn handle_page_fault(&mut self, vaddr: VirtAddr, access: MemFlags) -> bool {
+        let vaddr = vaddr.align_down();
+        let area = match self.areas.range_mut(..=vaddr).next_back() {
+            Some((_, area)) if vaddr < area.end() => area,
+            _ => return false,
+        };
+        if !area.flags.contains(access) {
+            return false;
+        }
+        if self.pt.query(vaddr).is_none() {
+            let paddr = match area.map(vaddr) {
+                Some(paddr) => paddr,
+                None => return false,
+            };
+            if access.contains(MemFlags::WRITE) {
+                area.mark_dirty(vaddr);
+            }
+            self.pt.map(vaddr, paddr, area.page_flags(vaddr));
+            if area.flags.contains(MemFlags::EXECUTE) {
+                instructions::flush_icache_all();
+            }
+            return true;
+        }
+        if !access.contains(MemFlags::WRITE) {
+            return false;
+        }
+        match area.copy_on_write(vaddr) {
+            Some(paddr) => {
+                self.pt.remap(vaddr, paddr, area.flags);
+                instructions::flush_tlb_all();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the physical address of `vaddr` if `access` is allowed, populating or
+    /// copying the page like a page fault caused by `access`.
+    pub fn translate(&mut self, vaddr: VirtAddr, access: MemFlags) -> Option<PhysAddr> {
+        // a page may be populated first, then copied on write
+        for _ in 0..2 {
+            match self.pt.query(vaddr) {
+                Some((paddr, flags)) if flags.contains(access) => return Some(paddr),
+                _ if !self.handle_page_fault(vaddr, access) => return None,
+                _ => {}
+            }
+        }
+        match self.pt.query(vaddr) {
+            Some((paddr, flags)) if flags.contains(access) => Some(paddr),
+            _ => None,
+        }
+    }
+
     pub fn page_table_root(&self) -> PhysAddr {
         self.pt.root_paddr()
     }
@@ -269,6 +866,109 @@ impl Drop for MemorySet {
     }
 }
 
+/// Parses the ELF executable `elf_data` for `MemorySet::load_user()`. Returns `None` if
+/// it is not an executable of this architecture, or its loadable segments overlap each
+/// other, or are outside the file or the user memory below the stack.
+pub fn parse_elf(elf_data: &[u8]) -> Option<ElfFile<'_>> {
+    use xmas_elf::header::{Class, Machine, Type as ElfType};
+    use xmas_elf::program::{ProgramHeader64, Type};
+
+    let elf = ElfFile::new(elf_data).ok()?;
+    let expect_arch = if cfg!(target_arch = "x86_64") {
+        Machine::X86_64
+    } else if cfg!(target_arch = "aarch64") {
+        Machine::AArch64
+    } else if cfg!(target_arch = "riscv64") {
+        Machine::RISC_V
+    } else {
+        panic!("Unsupported architecture!");
+    };
+    if elf.header.pt1.class() != Class::SixtyFour
+        || elf.header.pt2.type_().as_type() != ElfType::Executable
+        || elf.header.pt2.machine().as_machine() != expect_arch
+    {
+        return None;
+    }
+
+    // program headers are read in place, so they must be inside the file and aligned
+    let ph_offset = elf.header.pt2.ph_offset() as usize;
+    let ph_size = elf.header.pt2.ph_entry_size() as usize;
+    let ph_end = (elf.header.pt2.ph_count() as usize)
+        .checked_mul(ph_size)?
+        .checked_add(ph_offset)?;
+    if ph_size != size_of::<ProgramHeader64>()
+        || (elf_data.as_ptr() as usize + ph_offset) % mem::align_of::<ProgramHeader64>() != 0
+        || ph_end > elf_data.len()
+    {
+        return None;
+    }
+
+    let mut ranges = Vec::new();
+    for ph in elf.program_iter() {
+        if ph.get_type() != Ok(Type::Load) {
+            continue;
+        }
+        let (start, mem_size) = (ph.virtual_addr() as usize, ph.mem_size() as usize);
+        let end = start.checked_add(mem_size)?;
+        let file_end = (ph.offset() as usize).checked_add(ph.file_size() as usize)?;
+        if ph.file_size() > ph.mem_size()
+            || file_end > elf_data.len()
+            || start < USER_ASPACE_BASE
+            || end > USER_STACK_GUARD
+        {
+            return None;
+        }
+        if mem_size > 0 {
+            ranges.push((align_down(start, PAGE_SIZE), align_up(end, PAGE_SIZE)));
+        }
+    }
+    ranges.sort_unstable();
+    if ranges.windows(2).any(|w| w[0].1 > w[1].0) {
+        return None;
+    }
+    Some(elf)
+}
+
+/// Returns the initial stack pointer and the stack content from it to `stack_top`, in
+/// the System V layout: `argc`, `argv`, `envp` and `auxv` from the stack pointer
+/// upwards, followed by the strings.
+fn init_user_stack(
+    stack_top: usize,
+    args: &[String],
+    envs: &[String],
+    auxv: &[(usize, usize)],
+) -> (usize, Vec<u8>) {
+    let strings_size: usize = args.iter().chain(envs).map(|s| s.len() + 1).sum();
+    let strings_start = stack_top - strings_size;
+    let nwords = 1 + (args.len() + 1) + (envs.len() + 1) + (auxv.len() + 1) * 2;
+    let sp = align_down(strings_start - nwords * size_of::<usize>(), 16);
+    assert!(stack_top - sp <= USER_STACK_SIZE, "arguments are too large");
+
+    let mut words = Vec::with_capacity(nwords);
+    let mut strings = Vec::with_capacity(strings_size);
+    words.push(args.len());
+    for list in [args, envs] {
+        for s in list {
+            words.push(strings_start + strings.len());
+            strings.extend_from_slice(s.as_bytes());
+            strings.push(b'\0');
+        }
+        words.push(0);
+    }
+    for &(key, value) in auxv.iter().chain(&[(AT_NULL, 0)]) {
+        words.push(key);
+        words.push(value);
+    }
+
+    let mut data = vec![0; stack_top - sp];
+    for (i, word) in words.iter().enumerate() {
+        let off = i * size_of::<usize>();
+        data[off..off + size_of::<usize>()].copy_from_slice(&word.to_ne_bytes());
+    }
+    data[strings_start - sp..].copy_from_slice(&strings);
+    (sp, data)
+}
+
 pub fn kernel_aspace<'a>() -> &'a MemorySet {
     &KERNEL_ASPACE
 }
@@ -348,6 +1048,7 @@ impl fmt::Debug for MapArea {
             .field("flags", &self.flags);
         match &self.mapper {
             Mapper::Framed(_) => s.field("mapper", &"Frame"),
+            Mapper::Shm(_, off) => s.field("mapper", &alloc::format!("Shm({})", off)),
             Mapper::Offset(off) => s.field("mapper", &alloc::format!("Offset({})", off)),
         }
         .finish()
@@ -8,7 +8,7 @@ pub mod paging;
 pub use address::{PhysAddr, VirtAddr};
 use alloc::vec::Vec;
 pub use frame_allocator::PhysFrame;
-pub use memory_set::{kernel_aspace, MapArea, MemorySet};
+pub use memory_set::{kernel_aspace, parse_elf, MapArea, MemorySet};
 pub use uaccess::{UserInOutPtr, UserInPtr, UserOutPtr};
 
 pub const PAGE_SIZE: usize = 0x1000;
@@ -114,6 +114,15 @@ impl<L: PageTableLevels, PTE: GenericPTE> PageTableImpl<L, PTE> {
         entry.clear();
     }
 
+    /// Changes the mapping of `vaddr`, which must be mapped.
+    pub fn remap(&mut self, vaddr: VirtAddr, paddr: PhysAddr, flags: MemFlags) {
+        let entry = self.get_entry_mut(vaddr).unwrap();
+        if entry.is_unused() {
+            panic!("{:#x?} is invalid before remapping", vaddr);
+        }
+        *entry = GenericPTE::new_page(paddr.align_down(), flags, false);
+    }
+
     pub fn query(&self, vaddr: VirtAddr) -> Option<(PhysAddr, MemFlags)> {
         let entry = self.get_entry_mut(vaddr)?;
         if entry.is_unused() {
@@ -123,23 +132,19 @@ impl<L: PageTableLevels, PTE: GenericPTE> PageTableImpl<L, PTE> {
         Some((PhysAddr::new(entry.paddr().as_usize() + off), entry.flags()))
     }
 
-    pub fn map_area(&mut self, area: &mut MapArea) {
-        let mut vaddr = area.start.as_usize();
-        let end = vaddr + area.size;
-        while vaddr < end {
-            let paddr = area.map(VirtAddr::new(vaddr));
-            self.map(VirtAddr::new(vaddr), paddr, area.flags);
-            vaddr += PAGE_SIZE;
+    /// Maps the populated pages of `area`, other pages are mapped on page faults.
+    pub fn map_area(&mut self, area: &MapArea) {
+        for (vaddr, paddr) in area.mapped_pages() {
+            self.map(vaddr, paddr, area.page_flags(vaddr));
         }
     }
 
-    pub fn unmap_area(&mut self, area: &mut MapArea) {
-        let mut vaddr = area.start.as_usize();
-        let end = vaddr + area.size;
-        while vaddr < end {
-            area.unmap(VirtAddr::new(vaddr));
-            self.unmap(VirtAddr::new(vaddr));
-            vaddr += PAGE_SIZE;
+    /// Unmaps the pages of `area` in page tables, the frames are kept in `area`.
+    pub fn unmap_area(&mut self, area: &MapArea) {
+        for vaddr in area.possibly_mapped_pages() {
+            if self.query(vaddr).is_some() {
+                self.unmap(vaddr);
+            }
         }
     }
 
@@ -111,6 +111,10 @@ impl<T, P: ReadPolicy> UserPtr<T, P> {
         }
     }
 
+    pub fn read_buf(&self, buf: &mut [T]) {
+        unsafe { copy_from_user(buf.as_mut_ptr(), self.ptr, buf.len()) };
+    }
+
     pub fn read_array<const N: usize>(&self, max_len: usize) -> [T; N] {
         let mut buf: [T; N] = unsafe { MaybeUninit::uninit().assume_init() };
         unsafe { copy_from_user(buf.as_mut_ptr(), self.ptr, max_len.min(N)) };
@@ -61,8 +61,7 @@ impl File for UDP {
     }
 
     fn write(&self, buf: crate::mm::UserBuffer) -> usize {
-        let lose_net_stack = LOSE_NET_STACK.0.exclusive_access();
-
+        // the user buffer may fault, copy it before the stack is locked with IRQs disabled
         let mut data = vec![0u8; buf.len()];
 
         let mut left = 0;
@@ -71,6 +70,8 @@ impl File for UDP {
             left += buf.buffers[i].len();
         }
 
+        let lose_net_stack = LOSE_NET_STACK.0.exclusive_access();
+
         let len = data.len();
 
         let udp_packet = UDPPacket::new(
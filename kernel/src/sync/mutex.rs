@@ -1 +1,125 @@
-pub use super::spin::SpinNoIrqLock as Mutex; // TODO: implement waiting queue.
+//! A sleeping mutex for task context. Use `SpinNoIrqLock` for data accessed in IRQ
+//! context or while holding other spin locks.
+
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::arch::instructions;
+use crate::task::{current, WaitQueue};
+
+pub struct Mutex<T: ?Sized> {
+    /// Address of the owner task, or 0 if unlocked.
+    owner: AtomicUsize,
+    wait_queue: WaitQueue,
+    data: UnsafeCell<T>,
+}
+
+pub struct MutexGuard<'a, T: ?Sized + 'a> {
+    lock: &'a Mutex<T>,
+    data: &'a mut T,
+}
+
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+
+fn current_owner() -> usize {
+    Arc::as_ptr(current().0) as usize
+}
+
+impl<T> Mutex<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            owner: AtomicUsize::new(0),
+            wait_queue: WaitQueue::new(),
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn is_locked(&self) -> bool {
+        self.owner.load(Ordering::Relaxed) != 0
+    }
+
+    /// Locks the mutex, the current task sleeps until it is unlocked by the owner.
+    pub fn lock(&self) -> MutexGuard<T> {
+        let me = current_owner();
+        loop {
+            match self
+                .owner
+                .compare_exchange(0, me, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(owner) => {
+                    assert_ne!(
+                        owner, me,
+                        "deadlock: the current task already owns the mutex"
+                    );
+                    assert!(
+                        !instructions::irqs_disabled(),
+                        "can not sleep with IRQs disabled"
+                    );
+                    self.wait_queue.wait_until(|| !self.is_locked());
+                }
+            }
+        }
+        MutexGuard {
+            lock: self,
+            data: unsafe { &mut *self.data.get() },
+        }
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<T>> {
+        self.owner
+            .compare_exchange(0, current_owner(), Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| MutexGuard {
+                lock: self,
+                data: unsafe { &mut *self.data.get() },
+            })
+    }
+
+    fn unlock(&self) {
+        self.owner.store(0, Ordering::Release);
+        self.wait_queue.notify_one();
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_lock() {
+            Some(guard) => write!(f, "Mutex {{ data: ")
+                .and_then(|()| (*guard).fmt(f))
+                .and_then(|()| write!(f, "}}")),
+            None => write!(f, "Mutex {{ <locked> }}"),
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.data
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MutexGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
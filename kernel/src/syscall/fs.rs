@@ -1,7 +1,8 @@
-use crate::fs::{open_file, OpenFlags};
-use crate::mm::UserBuffer;
+use crate::fs::{make_pipe, open_file, open_shm, unlink_shm, File, OpenFlags, SHM_DIR};
+use crate::mm::{UserBuffer, UserOutPtr};
 use crate::task::*;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 
 use core::result::Result;
@@ -9,6 +10,8 @@ const FD_STDIN: usize = 0;
 const FD_STDOUT: usize = 1;
 const FD_STDERR: usize = 2;
 const CHUNK_SIZE: usize = 256;
+/// File descriptors must be below it.
+const MAX_FDS: usize = 1024;
 
 // pub fn sys_write(fd: usize, buf: UserInPtr<u8>, len: usize) -> isize {
 //     match fd {
@@ -37,6 +40,8 @@ pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
             return -1;
         }
         let file = file.clone();
+        // user buffers may fault, do not access them with IRQs disabled
+        drop(inner);
         file.write(UserBuffer::new(translated_byte_buffer(buf, len))) as isize
     } else {
         -1
@@ -52,6 +57,7 @@ pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
         if !file.readable() {
             return -1;
         }
+        drop(inner);
         file.read(UserBuffer::new(translated_byte_buffer(buf, len))) as isize
     } else {
         -1
@@ -99,12 +105,14 @@ pub fn sys_open(path: *const u8, flags: u32) -> isize {
     let task = current();
     // 因为没有虚拟地址映射，所以直接访问切片的指针就行。
     let path = check_and_clone_cstr(path).unwrap();
-    if let Some(inode) = open_file(
-        path.as_str(),
-        OpenFlags::from_bits(flags).unwrap(),
-    ) {
+    let flags = OpenFlags::from_bits(flags).unwrap();
+    let file: Option<Arc<dyn File + Send + Sync>> = match path.strip_prefix(SHM_DIR) {
+        Some(name) => open_shm(name, flags).map(|shm| shm as _),
+        None => open_file(path.as_str(), flags).map(|inode| inode as _),
+    };
+    if let Some(file) = file {
         let fd = task.inner_exclusive_access().alloc_fd();
-        task.inner_exclusive_access().fd_table[fd] = Some(inode);
+        task.inner_exclusive_access().fd_table[fd] = Some(file);
         fd as isize
     } else {
         -1
@@ -139,3 +147,71 @@ pub fn sys_close(fd: usize) -> isize {
     task.inner_exclusive_access().fd_table[fd].take();
     0
 }
+
+/// Creates a pipe, writes the file descriptors of its read end and write end to `fds`.
+pub fn sys_pipe(mut fds: UserOutPtr<i32>) -> isize {
+    let (read_end, write_end) = make_pipe();
+    let task = current();
+    let mut inner = task.inner_exclusive_access();
+    let read_fd = inner.alloc_fd();
+    inner.fd_table[read_fd] = Some(read_end);
+    let write_fd = inner.alloc_fd();
+    inner.fd_table[write_fd] = Some(write_end);
+    drop(inner);
+    fds.write_buf(&[read_fd as i32, write_fd as i32]);
+    0
+}
+
+pub fn sys_dup(fd: usize) -> isize {
+    let task = current();
+    let mut inner = task.inner_exclusive_access();
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -1,
+    };
+    let new_fd = inner.alloc_fd();
+    inner.fd_table[new_fd] = Some(file);
+    new_fd as isize
+}
+
+/// Makes `new_fd` refer to the file of `old_fd`, closing its file first if any.
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    if new_fd >= MAX_FDS {
+        return -1;
+    }
+    let task = current();
+    let mut inner = task.inner_exclusive_access();
+    let file = match inner.fd_table.get(old_fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -1,
+    };
+    if new_fd >= inner.fd_table.len() {
+        inner.fd_table.resize(new_fd + 1, None);
+    }
+    let old_file = inner.fd_table[new_fd].replace(file);
+    // closed without IRQs disabled
+    drop(inner);
+    drop(old_file);
+    new_fd as isize
+}
+
+pub fn sys_ftruncate(fd: usize, len: usize) -> isize {
+    let file = match current().inner_exclusive_access().fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -1,
+    };
+    if file.truncate(len) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Only shared memory objects can be removed.
+pub fn sys_unlink(path: *const u8) -> isize {
+    let path = check_and_clone_cstr(path).unwrap();
+    match path.strip_prefix(SHM_DIR) {
+        Some(name) if unlink_shm(name) => 0,
+        _ => -1,
+    }
+}
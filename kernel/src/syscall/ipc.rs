@@ -0,0 +1,159 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+
+use crate::fs::{open_mqueue, unlink_mqueue, File, OpenFlags};
+use crate::mm::{UserInPtr, UserOutPtr};
+use crate::task::current;
+
+const MAX_NAME_LEN: usize = 256;
+
+/// Same as `struct mq_attr` of Linux.
+#[repr(C)]
+pub struct MqAttr {
+    mq_flags: isize,
+    mq_maxmsg: isize,
+    mq_msgsize: isize,
+    mq_curmsgs: isize,
+    reserved: [isize; 4],
+}
+
+fn get_file(fd: usize) -> Option<Arc<dyn File + Send + Sync>> {
+    current()
+        .inner_exclusive_access()
+        .fd_table()
+        .get(fd)?
+        .clone()
+}
+
+fn read_name(name: UserInPtr<u8>) -> Option<String> {
+    let (buf, len) = name.read_str::<MAX_NAME_LEN>();
+    core::str::from_utf8(&buf[..len]).ok().map(Into::into)
+}
+
+/// Opens the message queue `name`, with the capacity and maximum message size in
+/// `attr` if it is created.
+pub fn sys_mq_open(
+    name: UserInPtr<u8>,
+    flags: u32,
+    _mode: usize,
+    attr: UserInPtr<MqAttr>,
+) -> isize {
+    let (name, flags) = match (read_name(name), OpenFlags::from_bits(flags)) {
+        (Some(name), Some(flags)) => (name, flags),
+        _ => return -1,
+    };
+    let attr = if attr.is_null() {
+        None
+    } else {
+        let attr = attr.read();
+        Some((attr.mq_maxmsg as usize, attr.mq_msgsize as usize))
+    };
+    match open_mqueue(&name, flags, attr) {
+        Some(file) => {
+            let task = current();
+            let mut inner = task.inner_exclusive_access();
+            let fd = inner.alloc_fd();
+            inner.fd_table[fd] = Some(file);
+            fd as isize
+        }
+        None => -1,
+    }
+}
+
+pub fn sys_mq_unlink(name: UserInPtr<u8>) -> isize {
+    match read_name(name) {
+        Some(name) if unlink_mqueue(&name) => 0,
+        _ => -1,
+    }
+}
+
+/// Timeouts are not supported, `abs_timeout` must be null.
+pub fn sys_mq_timedsend(
+    mqd: usize,
+    msg: UserInPtr<u8>,
+    len: usize,
+    prio: u32,
+    abs_timeout: usize,
+) -> isize {
+    let file = match get_file(mqd) {
+        Some(file) if abs_timeout == 0 => file,
+        _ => return -1,
+    };
+    let mq = match file.mqueue() {
+        Some(mq) if len <= mq.queue().msg_size() => mq,
+        _ => return -1,
+    };
+    let mut data = vec![0; len];
+    if len > 0 {
+        msg.read_buf(&mut data);
+    }
+    if mq.send(&data, prio) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Returns the size of the received message. Timeouts are not supported,
+/// `abs_timeout` must be null.
+pub fn sys_mq_timedreceive(
+    mqd: usize,
+    mut msg: UserOutPtr<u8>,
+    len: usize,
+    mut prio: UserOutPtr<u32>,
+    abs_timeout: usize,
+) -> isize {
+    let file = match get_file(mqd) {
+        Some(file) if abs_timeout == 0 => file,
+        _ => return -1,
+    };
+    let mq = match file.mqueue() {
+        Some(mq) if len >= mq.queue().msg_size() => mq,
+        _ => return -1,
+    };
+    let mut data = vec![0; mq.queue().msg_size()];
+    match mq.receive(&mut data) {
+        Some((msg_len, msg_prio)) => {
+            if msg_len > 0 {
+                msg.write_buf(&data[..msg_len]);
+            }
+            if !prio.is_null() {
+                prio.write(msg_prio);
+            }
+            msg_len as isize
+        }
+        None => -1,
+    }
+}
+
+/// Only gets the attributes, `new_attr` must be null.
+pub fn sys_mq_getsetattr(
+    mqd: usize,
+    new_attr: UserInPtr<MqAttr>,
+    mut old_attr: UserOutPtr<MqAttr>,
+) -> isize {
+    let file = match get_file(mqd) {
+        Some(file) if new_attr.is_null() => file,
+        _ => return -1,
+    };
+    let mq = match file.mqueue() {
+        Some(mq) => mq,
+        None => return -1,
+    };
+    if !old_attr.is_null() {
+        let flags = if mq.nonblock() {
+            OpenFlags::NONBLOCK.bits() as isize
+        } else {
+            0
+        };
+        old_attr.write(MqAttr {
+            mq_flags: flags,
+            mq_maxmsg: mq.queue().max_msgs() as isize,
+            mq_msgsize: mq.queue().msg_size() as isize,
+            mq_curmsgs: mq.queue().num_msgs() as isize,
+            reserved: [0; 4],
+        });
+    }
+    0
+}
@@ -0,0 +1,170 @@
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
+use crate::mm::address::{align_up, is_aligned};
+use crate::mm::{MapArea, MemFlags, VirtAddr, PAGE_SIZE};
+use crate::task::current;
+
+bitflags::bitflags! {
+    pub struct MmapProt: usize {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXEC = 1 << 2;
+    }
+}
+
+bitflags::bitflags! {
+    pub struct MmapFlags: usize {
+        const SHARED = 0x01;
+        const PRIVATE = 0x02;
+        const FIXED = 0x10;
+        const ANONYMOUS = 0x20;
+        const STACK = 0x20000;
+    }
+}
+
+impl From<MmapProt> for MemFlags {
+    fn from(prot: MmapProt) -> Self {
+        let mut flags = MemFlags::USER;
+        // writable or executable pages must be readable on some architectures
+        if !prot.is_empty() {
+            flags |= MemFlags::READ;
+        }
+        if prot.contains(MmapProt::WRITE) {
+            flags |= MemFlags::WRITE;
+        }
+        if prot.contains(MmapProt::EXEC) {
+            flags |= MemFlags::EXECUTE;
+        }
+        flags
+    }
+}
+
+/// Returns the size of `[start, start + len)` rounded up to pages, or `None` if it is
+/// not a valid range of user pages.
+fn user_pages(start: usize, len: usize) -> Option<usize> {
+    if len == 0 || len > USER_ASPACE_SIZE || !is_aligned(start, PAGE_SIZE) {
+        return None;
+    }
+    let size = align_up(len, PAGE_SIZE);
+    let in_aspace = start >= USER_ASPACE_BASE
+        && start
+            .checked_add(size)
+            .map_or(false, |end| end <= USER_ASPACE_BASE + USER_ASPACE_SIZE);
+    in_aspace.then_some(size)
+}
+
+/// Maps anonymous memory, or the file or shared memory object `fd` from `offset`. `fd` and `offset` are
+/// ignored for anonymous mappings.
+pub fn sys_mmap(
+    addr: usize,
+    len: usize,
+    prot: usize,
+    flags: usize,
+    fd: usize,
+    offset: usize,
+) -> isize {
+    let (prot, flags) = match (MmapProt::from_bits(prot), MmapFlags::from_bits(flags)) {
+        (Some(prot), Some(flags)) => (prot, flags),
+        _ => return -1,
+    };
+    let shared = flags.contains(MmapFlags::SHARED);
+    if shared == flags.contains(MmapFlags::PRIVATE) {
+        return -1;
+    }
+    let size = match user_pages(0, len) {
+        Some(size) => size,
+        None => return -1,
+    };
+
+    let curr = current();
+    let file = if flags.contains(MmapFlags::ANONYMOUS) {
+        None
+    } else {
+        let file = match curr.inner_exclusive_access().fd_table().get(fd) {
+            Some(Some(file)) => file.clone(),
+            _ => return -1,
+        };
+        let writable = !shared || !prot.contains(MmapProt::WRITE) || file.writable();
+        // shared memory objects can only be mapped shared
+        let mappable = file.inode().is_some() || (shared && file.shm().is_some());
+        if !file.readable() || !writable || !mappable || !is_aligned(offset, PAGE_SIZE) {
+            return -1;
+        }
+        Some(file)
+    };
+    let mut vm = curr.vm().lock();
+    let fixed = flags.contains(MmapFlags::FIXED);
+    let start = if fixed {
+        if user_pages(addr, size).is_none() {
+            return -1;
+        }
+        VirtAddr::new(addr)
+    } else {
+        match vm.find_free_area(addr, size) {
+            Some(start) => start,
+            None => return -1,
+        }
+    };
+    let area = match file {
+        Some(file) => match file.shm() {
+            Some(shm) => MapArea::new_shm(start, size, prot.into(), shm, offset),
+            None => {
+                let inode = file.inode().unwrap();
+                let writable = file.writable();
+                MapArea::new_file(start, size, prot.into(), inode, offset, shared, writable)
+            }
+        },
+        None if shared => match MapArea::new_shared(start, size, prot.into()) {
+            Some(area) => area,
+            None => return -1,
+        },
+        None if flags.contains(MmapFlags::STACK) => MapArea::new_stack(start, size, prot.into()),
+        None => MapArea::new_framed(start, size, prot.into()),
+    };
+    // old mappings are kept if out of memory
+    if fixed {
+        vm.unmap(start, size);
+    }
+    vm.insert(area);
+    start.as_usize() as isize
+}
+
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+    match user_pages(addr, len) {
+        Some(size) => {
+            current().vm().lock().unmap(VirtAddr::new(addr), size);
+            0
+        }
+        None => -1,
+    }
+}
+
+pub fn sys_mprotect(addr: usize, len: usize, prot: usize) -> isize {
+    let prot = match MmapProt::from_bits(prot) {
+        Some(prot) => prot,
+        None => return -1,
+    };
+    let size = match user_pages(addr, len) {
+        Some(size) => size,
+        None => return -1,
+    };
+    let mut vm = current().vm().lock();
+    if vm.protect(VirtAddr::new(addr), size, prot.into()) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Writes back the shared file mappings in `[addr, addr + len)`, `flags` are ignored
+/// as the write back is always synchronous.
+pub fn sys_msync(addr: usize, len: usize, _flags: usize) -> isize {
+    match user_pages(addr, len) {
+        Some(size) if current().vm().lock().sync(VirtAddr::new(addr), size) => 0,
+        _ => -1,
+    }
+}
+
+/// Returns the new program break, or the current one if `brk` is invalid.
+pub fn sys_brk(brk: usize) -> isize {
+    current().vm().lock().set_brk(brk) as isize
+}
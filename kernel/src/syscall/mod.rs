@@ -2,7 +2,18 @@ const SYSCALL_READ: usize = 0;
 const SYSCALL_WRITE: usize = 1;
 const SYSCALL_OPEN: usize = 2;
 const SYSCALL_CLOSE: usize = 3;
+const SYSCALL_MMAP: usize = 9;
+const SYSCALL_MPROTECT: usize = 10;
+const SYSCALL_MUNMAP: usize = 11;
+const SYSCALL_BRK: usize = 12;
+const SYSCALL_RT_SIGACTION: usize = 13;
+const SYSCALL_RT_SIGPROCMASK: usize = 14;
+const SYSCALL_RT_SIGRETURN: usize = 15;
+const SYSCALL_PIPE: usize = 22;
 const SYSCALL_YIELD: usize = 24;
+const SYSCALL_MSYNC: usize = 26;
+const SYSCALL_DUP: usize = 32;
+const SYSCALL_DUP2: usize = 33;
 pub const SYSCALL_CONNECT: usize = 29;
 const SYSCALL_GETPID: usize = 39;
 const SYSCALL_CLONE: usize = 56;
@@ -10,46 +21,101 @@ const SYSCALL_FORK: usize = 57;
 const SYSCALL_EXEC: usize = 59;
 const SYSCALL_EXIT: usize = 60;
 const SYSCALL_WAITPID: usize = 61;
+const SYSCALL_KILL: usize = 62;
+const SYSCALL_FTRUNCATE: usize = 77;
+const SYSCALL_UNLINK: usize = 87;
 const SYSCALL_GET_TIME_MS: usize = 96;
+const SYSCALL_GETRUSAGE: usize = 98;
+const SYSCALL_TIMES: usize = 100;
+const SYSCALL_GETPRIORITY: usize = 140;
+const SYSCALL_SETPRIORITY: usize = 141;
+const SYSCALL_SCHED_SETPARAM: usize = 142;
+const SYSCALL_SCHED_GETPARAM: usize = 143;
+const SYSCALL_SCHED_SETSCHEDULER: usize = 144;
+const SYSCALL_SCHED_GETSCHEDULER: usize = 145;
+const SYSCALL_ARCH_PRCTL: usize = 158;
+const SYSCALL_GETTID: usize = 186;
+const SYSCALL_FUTEX: usize = 202;
+const SYSCALL_SET_TID_ADDRESS: usize = 218;
 const SYSCALL_CLOCK_GETTIME: usize = 228;
 const SYSCALL_CLOCK_NANOSLEEP: usize = 230;
+const SYSCALL_EXIT_GROUP: usize = 231;
+const SYSCALL_MQ_OPEN: usize = 240;
+const SYSCALL_MQ_UNLINK: usize = 241;
+const SYSCALL_MQ_TIMEDSEND: usize = 242;
+const SYSCALL_MQ_TIMEDRECEIVE: usize = 243;
+const SYSCALL_MQ_GETSETATTR: usize = 245;
+const SYSCALL_SCHED_SETATTR: usize = 314;
+const SYSCALL_SCHED_GETATTR: usize = 315;
 
 mod fs;
+mod ipc;
+mod mm;
+mod signal;
 mod task;
 mod time;
 mod net;
 use self::fs::*;
+use self::ipc::*;
+use self::mm::*;
+use self::signal::*;
 use self::task::*;
 use self::time::*;
 use self::net::*;
 use crate::arch::{instructions, TrapFrame};
-pub fn syscall(
-    tf: &mut TrapFrame,
-    syscall_id: usize,
-    arg0: usize,
-    arg1: usize,
-    arg2: usize,
-) -> isize {
+pub fn syscall(tf: &mut TrapFrame, syscall_id: usize, args: [usize; 6]) -> isize {
     instructions::enable_irqs();
-    debug!(
-        "syscall {} enter <= ({:#x}, {:#x}, {:#x})",
-        syscall_id, arg0, arg1, arg2
-    );
+    debug!("syscall {} enter <= {:#x?}", syscall_id, args);
+    let [arg0, arg1, arg2, arg3, arg4, arg5] = args;
     let ret = match syscall_id {
         SYSCALL_READ => sys_read(arg0, arg1 as *const u8, arg2),
         SYSCALL_WRITE => sys_write(arg0, arg1 as *const u8, arg2),
         SYSCALL_OPEN => sys_open(arg0 as *const u8, arg1 as u32),
         SYSCALL_CLOSE => sys_close(arg0),
+        SYSCALL_MMAP => sys_mmap(arg0, arg1, arg2, arg3, arg4, arg5),
+        SYSCALL_MPROTECT => sys_mprotect(arg0, arg1, arg2),
+        SYSCALL_MUNMAP => sys_munmap(arg0, arg1),
+        SYSCALL_BRK => sys_brk(arg0),
+        SYSCALL_RT_SIGACTION => sys_rt_sigaction(arg0, arg1.into(), arg2.into()),
+        SYSCALL_RT_SIGPROCMASK => sys_rt_sigprocmask(arg0, arg1.into(), arg2.into()),
+        SYSCALL_RT_SIGRETURN => sys_rt_sigreturn(tf),
+        SYSCALL_PIPE => sys_pipe(arg0.into()),
         SYSCALL_YIELD => sys_yield(),
+        SYSCALL_MSYNC => sys_msync(arg0, arg1, arg2),
+        SYSCALL_DUP => sys_dup(arg0),
+        SYSCALL_DUP2 => sys_dup2(arg0, arg1),
         SYSCALL_GETPID => sys_getpid(),
-        SYSCALL_CLONE => sys_clone(arg0, tf),
+        SYSCALL_CLONE => sys_clone(arg0, arg1, arg2, arg3, tf),
         SYSCALL_FORK => sys_fork(tf),
-        SYSCALL_EXEC => sys_exec(arg0.into(), tf),
+        SYSCALL_EXEC => sys_exec(arg0.into(), arg1.into(), arg2.into(), tf),
         SYSCALL_EXIT => sys_exit(arg0 as i32),
         SYSCALL_WAITPID => sys_waitpid(arg0 as _, arg1.into(), arg2 as _),
+        SYSCALL_KILL => sys_kill(arg0 as _, arg1),
+        SYSCALL_FTRUNCATE => sys_ftruncate(arg0, arg1),
+        SYSCALL_UNLINK => sys_unlink(arg0 as *const u8),
         SYSCALL_GET_TIME_MS => sys_get_time_ms(),
+        SYSCALL_GETRUSAGE => sys_getrusage(arg0 as _, arg1.into()),
+        SYSCALL_TIMES => sys_times(arg0.into()),
+        SYSCALL_GETPRIORITY => sys_getpriority(arg0, arg1),
+        SYSCALL_SETPRIORITY => sys_setpriority(arg0, arg1, arg2 as _),
+        SYSCALL_SCHED_SETPARAM => sys_sched_setparam(arg0, arg1.into()),
+        SYSCALL_SCHED_GETPARAM => sys_sched_getparam(arg0, arg1.into()),
+        SYSCALL_SCHED_SETSCHEDULER => sys_sched_setscheduler(arg0, arg1, arg2.into()),
+        SYSCALL_SCHED_GETSCHEDULER => sys_sched_getscheduler(arg0),
+        SYSCALL_ARCH_PRCTL => sys_arch_prctl(arg0, arg1, tf),
+        SYSCALL_GETTID => sys_gettid(),
+        SYSCALL_FUTEX => sys_futex(arg0, arg1 as _, arg2 as _, arg3, arg4, arg5 as _),
+        SYSCALL_SET_TID_ADDRESS => sys_set_tid_address(arg0),
         SYSCALL_CLOCK_GETTIME => sys_clock_gettime(arg0 as _, arg1.into()),
         SYSCALL_CLOCK_NANOSLEEP => sys_clock_nanosleep(arg0 as _, arg1 as _, arg2.into()),
+        SYSCALL_EXIT_GROUP => sys_exit_group(arg0 as i32),
+        SYSCALL_MQ_OPEN => sys_mq_open(arg0.into(), arg1 as _, arg2, arg3.into()),
+        SYSCALL_MQ_UNLINK => sys_mq_unlink(arg0.into()),
+        SYSCALL_MQ_TIMEDSEND => sys_mq_timedsend(arg0, arg1.into(), arg2, arg3 as _, arg4),
+        SYSCALL_MQ_TIMEDRECEIVE => sys_mq_timedreceive(arg0, arg1.into(), arg2, arg3.into(), arg4),
+        SYSCALL_MQ_GETSETATTR => sys_mq_getsetattr(arg0, arg1.into(), arg2.into()),
+        SYSCALL_SCHED_SETATTR => sys_sched_setattr(arg0, arg1.into(), arg2),
+        SYSCALL_SCHED_GETATTR => sys_sched_getattr(arg0, arg1.into(), arg2),
         // socket
         SYSCALL_CONNECT => sys_connect(arg0 as _, arg1 as _, arg2 as _),
         _ => {
@@ -58,6 +124,7 @@ pub fn syscall(
         }
     };
     debug!("syscall {} ret => {:#x}", syscall_id, ret);
+    crate::task::resched_if_needed();
     instructions::disable_irqs();
     ret
 }
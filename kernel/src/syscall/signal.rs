@@ -0,0 +1,65 @@
+use crate::arch::TrapFrame;
+use crate::mm::{UserInPtr, UserOutPtr};
+use crate::task::find_task;
+use crate::task::signal::{self, SigAction};
+
+/// Only sends signals to a single task, process groups are not supported.
+pub fn sys_kill(pid: isize, sig: usize) -> isize {
+    if pid <= 0 || (sig != 0 && !signal::is_valid(sig)) {
+        return -1;
+    }
+    match find_task((pid as usize).into()) {
+        Some(t) if !t.is_kernel_task() => {
+            // signal 0 only checks if the task exists
+            if sig != 0 {
+                signal::send_signal(&t, sig);
+            }
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Signal sets are always 64 bits, `sigsetsize` is not checked.
+pub fn sys_rt_sigaction(
+    sig: usize,
+    act: UserInPtr<SigAction>,
+    mut oldact: UserOutPtr<SigAction>,
+) -> isize {
+    if !signal::is_valid(sig) {
+        return -1;
+    }
+    let act = if act.is_null() {
+        None
+    } else {
+        Some(act.read())
+    };
+    if let Some(old) = signal::sigaction(sig, act) {
+        if !oldact.is_null() {
+            oldact.write(old);
+        }
+        0
+    } else {
+        -1
+    }
+}
+
+pub fn sys_rt_sigprocmask(how: usize, set: UserInPtr<u64>, mut oldset: UserOutPtr<u64>) -> isize {
+    let set = if set.is_null() {
+        None
+    } else {
+        Some(set.read())
+    };
+    if let Some(old) = signal::sigprocmask(how, set) {
+        if !oldset.is_null() {
+            oldset.write(old);
+        }
+        0
+    } else {
+        -1
+    }
+}
+
+pub fn sys_rt_sigreturn(tf: &mut TrapFrame) -> isize {
+    signal::sigreturn(tf)
+}
@@ -1,13 +1,71 @@
+use super::time::TimeSpec;
 use crate::arch::TrapFrame;
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
 use crate::mm::{UserInPtr, UserOutPtr};
-use crate::task::{current, spawn_task};
+use crate::task::futex::{futex_requeue, futex_wait, futex_wake};
+use crate::task::{
+    current, find_task, spawn_task, SchedPolicy, Task, DL_MIN_RUNTIME_NS, TASK_MANAGER,
+};
+use crate::timer::current_time;
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::mem::size_of;
 
 const MAX_STR_LEN: usize = 256;
+/// Maximum size of arguments and environment variables on the user stack.
+const ARG_MAX: usize = 4096;
+const PRIO_PROCESS: usize = 0;
 
+const CLONE_SETTLS: usize = 0x8_0000;
+const CLONE_CHILD_CLEARTID: usize = 0x20_0000;
+const CLONE_CHILD_SETTID: usize = 0x100_0000;
+
+const ARCH_SET_FS: usize = 0x1002;
+
+const FUTEX_WAIT: u32 = 0;
+const FUTEX_WAKE: u32 = 1;
+const FUTEX_REQUEUE: u32 = 3;
+const FUTEX_CMP_REQUEUE: u32 = 4;
+const FUTEX_PRIVATE_FLAG: u32 = 128;
+
+#[repr(C)]
+pub struct SchedParam {
+    sched_priority: i32,
+}
+
+/// Same as `struct sched_attr` of Linux, times are in nanoseconds.
+#[repr(C)]
+pub struct SchedAttr {
+    size: u32,
+    sched_policy: u32,
+    sched_flags: u64,
+    sched_nice: i32,
+    sched_priority: u32,
+    sched_runtime: u64,
+    sched_deadline: u64,
+    sched_period: u64,
+}
+
+const SCHED_ATTR_SIZE: usize = core::mem::size_of::<SchedAttr>();
+
+/// Returns the current task if `pid` is 0, otherwise the task with `pid`.
+fn task_by_pid(pid: usize) -> Option<Arc<Task>> {
+    if pid == 0 {
+        Some(current().clone_task())
+    } else {
+        find_task(pid.into())
+    }
+}
+
+/// Exits the current thread.
 pub fn sys_exit(exit_code: i32) -> ! {
     current().exit(exit_code);
 }
 
+/// Exits all threads of the process.
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    current().exit_group(exit_code);
+}
+
 pub fn sys_yield() -> isize {
     current().yield_now();
     0
@@ -17,11 +75,98 @@ pub fn sys_getpid() -> isize {
     current().pid().as_usize() as isize
 }
 
-pub fn sys_clone(newsp: usize, tf: &TrapFrame) -> isize {
-    let new_task = current().new_clone(newsp, tf);
-    let pid = new_task.pid().as_usize() as isize;
+pub fn sys_gettid() -> isize {
+    current().tid().as_usize() as isize
+}
+
+/// Creates a thread with the user stack `newsp`, returns the thread id. Only
+/// `CLONE_SETTLS`, `CLONE_CHILD_SETTID` and `CLONE_CHILD_CLEARTID` of `flags` are used,
+/// other resources are always shared.
+pub fn sys_clone(newsp: usize, flags: usize, ctid: usize, tls: usize, tf: &TrapFrame) -> isize {
+    let tls = if flags & CLONE_SETTLS != 0 {
+        // the FS base of x86_64 must be canonical
+        if !(USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&tls) {
+            return -1;
+        }
+        Some(tls)
+    } else {
+        None
+    };
+    let clear_tid = if flags & CLONE_CHILD_CLEARTID != 0 {
+        ctid
+    } else {
+        0
+    };
+    let new_task = current().new_clone(newsp, tls, clear_tid, tf);
+    let tid = new_task.tid().as_usize() as isize;
+    if flags & CLONE_CHILD_SETTID != 0 {
+        // written before the thread runs, so that it is not written after cleared
+        UserOutPtr::<i32>::from(ctid).write(tid as i32);
+    }
     spawn_task(new_task);
-    pid
+    tid
+}
+
+/// Only supports `ARCH_SET_FS`, which sets the user thread pointer of the current
+/// thread to `addr`. It is the FS base on x86_64.
+pub fn sys_arch_prctl(code: usize, addr: usize, tf: &mut TrapFrame) -> isize {
+    match code {
+        ARCH_SET_FS => {
+            // the FS base of x86_64 must be canonical
+            if !(USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&addr) {
+                return -1;
+            }
+            current().set_user_tls(tf, addr);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Sets the thread id cleared on exit, returns the thread id.
+pub fn sys_set_tid_address(tidptr: usize) -> isize {
+    current().set_clear_child_tid(tidptr);
+    current().tid().as_usize() as isize
+}
+
+/// Supports `FUTEX_WAIT`, `FUTEX_WAKE`, `FUTEX_REQUEUE` and `FUTEX_CMP_REQUEUE`. The
+/// timeout of `FUTEX_WAIT` is relative, and `timeout` is the maximum number of requeued
+/// tasks for the others.
+pub fn sys_futex(
+    uaddr: usize,
+    op: u32,
+    val: u32,
+    timeout: usize,
+    uaddr2: usize,
+    val3: u32,
+) -> isize {
+    let private = op & FUTEX_PRIVATE_FLAG != 0;
+    match op & !FUTEX_PRIVATE_FLAG {
+        FUTEX_WAIT => {
+            let deadline = if timeout == 0 {
+                None
+            } else {
+                Some(current_time() + UserInPtr::<TimeSpec>::from(timeout).read().into())
+            };
+            if futex_wait(uaddr, private, val, deadline) {
+                0
+            } else {
+                -1
+            }
+        }
+        FUTEX_WAKE => futex_wake(uaddr, private, val as usize).map_or(-1, |woken| woken as isize),
+        FUTEX_REQUEUE => match futex_requeue(uaddr, private, val as usize, uaddr2, timeout, None) {
+            Some((woken, _)) => woken as isize,
+            None => -1,
+        },
+        FUTEX_CMP_REQUEUE => {
+            match futex_requeue(uaddr, private, val as usize, uaddr2, timeout, Some(val3)) {
+                Some((woken, requeued)) => (woken + requeued) as isize,
+                None => -1,
+            }
+        }
+        _ => -1,
+    }
 }
 
 pub fn sys_fork(tf: &TrapFrame) -> isize {
@@ -31,10 +176,43 @@ pub fn sys_fork(tf: &TrapFrame) -> isize {
     pid
 }
 
-pub fn sys_exec(path: UserInPtr<u8>, tf: &mut TrapFrame) -> isize {
+/// Reads a null-terminated array of strings such as `argv`, and adds the size they take
+/// on the user stack to `total`. Returns `None` if `total` exceeds `ARG_MAX`.
+fn read_str_array(array: UserInPtr<usize>, total: &mut usize) -> Option<Vec<String>> {
+    let mut strs = Vec::new();
+    if array.is_null() {
+        return Some(strs);
+    }
+    loop {
+        let ptr = unsafe { array.add(strs.len()) }.read();
+        if ptr == 0 {
+            return Some(strs);
+        }
+        let (buf, len) = UserInPtr::<u8>::from(ptr).read_str::<MAX_STR_LEN>();
+        *total += len + 1 + size_of::<usize>();
+        if *total > ARG_MAX {
+            return None;
+        }
+        strs.push(String::from_utf8_lossy(&buf[..len]).into_owned());
+    }
+}
+
+pub fn sys_exec(
+    path: UserInPtr<u8>,
+    argv: UserInPtr<usize>,
+    envp: UserInPtr<usize>,
+    tf: &mut TrapFrame,
+) -> isize {
     let (path_buf, len) = path.read_str::<MAX_STR_LEN>();
     let path = core::str::from_utf8(&path_buf[..len]).unwrap();
-    current().exec(path, tf)
+    let mut total = 0;
+    match (
+        read_str_array(argv, &mut total),
+        read_str_array(envp, &mut total),
+    ) {
+        (Some(args), Some(envs)) => current().exec(path, &args, &envs, tf),
+        _ => -1,
+    }
 }
 
 pub fn sys_waitpid(pid: isize, mut exit_code_ptr: UserOutPtr<i32>, options: u32) -> isize {
@@ -47,3 +225,147 @@ pub fn sys_waitpid(pid: isize, mut exit_code_ptr: UserOutPtr<i32>, options: u32)
         -1
     }
 }
+
+/// Returns `20 - nice` like Linux, so that a valid result is never negative.
+pub fn sys_getpriority(which: usize, who: usize) -> isize {
+    if which != PRIO_PROCESS {
+        return -1;
+    }
+    match task_by_pid(who) {
+        Some(t) => (20 - t.nice()) as isize,
+        None => -1,
+    }
+}
+
+pub fn sys_setpriority(which: usize, who: usize, nice: i32) -> isize {
+    if which != PRIO_PROCESS {
+        return -1;
+    }
+    match task_by_pid(who) {
+        Some(t) => {
+            t.set_nice(nice);
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Sets a non-deadline policy, deadline tasks can only be set by `sched_setattr()`.
+fn set_scheduler(t: &Arc<Task>, policy: SchedPolicy, prio: i32) -> isize {
+    if policy.is_deadline() || prio < 0 || !policy.is_valid_prio(prio as usize) {
+        return -1;
+    }
+    TASK_MANAGER.lock().set_scheduler(t, policy, prio as u8);
+    0
+}
+
+pub fn sys_sched_setscheduler(pid: usize, policy: usize, param: UserInPtr<SchedParam>) -> isize {
+    if param.is_null() {
+        return -1;
+    }
+    match (task_by_pid(pid), SchedPolicy::from_usize(policy)) {
+        (Some(t), Some(policy)) => set_scheduler(&t, policy, param.read().sched_priority),
+        _ => -1,
+    }
+}
+
+pub fn sys_sched_getscheduler(pid: usize) -> isize {
+    match task_by_pid(pid) {
+        Some(t) => t.sched_policy() as isize,
+        None => -1,
+    }
+}
+
+pub fn sys_sched_setparam(pid: usize, param: UserInPtr<SchedParam>) -> isize {
+    if param.is_null() {
+        return -1;
+    }
+    match task_by_pid(pid) {
+        Some(t) => set_scheduler(&t, t.sched_policy(), param.read().sched_priority),
+        None => -1,
+    }
+}
+
+pub fn sys_sched_getparam(pid: usize, mut param: UserOutPtr<SchedParam>) -> isize {
+    if param.is_null() {
+        return -1;
+    }
+    match task_by_pid(pid) {
+        Some(t) => {
+            param.write(SchedParam {
+                sched_priority: t.rt_priority() as i32,
+            });
+            0
+        }
+        None => -1,
+    }
+}
+
+pub fn sys_sched_setattr(pid: usize, attr: UserInPtr<SchedAttr>, flags: usize) -> isize {
+    if attr.is_null() || flags != 0 {
+        return -1;
+    }
+    let attr = attr.read();
+    if (attr.size != 0 && (attr.size as usize) < SCHED_ATTR_SIZE) || attr.sched_flags != 0 {
+        return -1;
+    }
+    let (t, policy) = match (
+        task_by_pid(pid),
+        SchedPolicy::from_usize(attr.sched_policy as _),
+    ) {
+        (Some(t), Some(policy)) => (t, policy),
+        _ => return -1,
+    };
+    if !policy.is_deadline() {
+        if !policy.is_rt() {
+            t.set_nice(attr.sched_nice);
+        }
+        return set_scheduler(&t, policy, attr.sched_priority as i32);
+    }
+
+    let (runtime, deadline) = (attr.sched_runtime, attr.sched_deadline);
+    let period = if attr.sched_period == 0 {
+        deadline
+    } else {
+        attr.sched_period
+    };
+    if runtime < DL_MIN_RUNTIME_NS || runtime > deadline || deadline > period {
+        return -1;
+    }
+    if TASK_MANAGER
+        .lock()
+        .set_deadline(&t, runtime, deadline, period)
+    {
+        0
+    } else {
+        -1
+    }
+}
+
+pub fn sys_sched_getattr(pid: usize, mut attr: UserOutPtr<SchedAttr>, size: usize) -> isize {
+    if attr.is_null() || size < SCHED_ATTR_SIZE {
+        return -1;
+    }
+    match task_by_pid(pid) {
+        Some(t) => {
+            let policy = t.sched_policy();
+            let (runtime, deadline, period) = if policy.is_deadline() {
+                t.dl_sched_params()
+            } else {
+                (0, 0, 0)
+            };
+            attr.write(SchedAttr {
+                size: SCHED_ATTR_SIZE as u32,
+                sched_policy: policy as u32,
+                sched_flags: 0,
+                sched_nice: t.nice(),
+                sched_priority: t.rt_priority() as u32,
+                sched_runtime: runtime,
+                sched_deadline: deadline,
+                sched_period: period,
+            });
+            0
+        }
+        None => -1,
+    }
+}
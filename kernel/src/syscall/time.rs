@@ -1,8 +1,15 @@
 use crate::mm::{UserInPtr, UserOutPtr};
-use crate::timer::{current_time, TimeValue};
+use crate::task::current;
+use crate::timer::{current_time, current_time_nanos, TimeValue, NANOS_PER_SEC};
 
 const TIMER_ABSTIME: u32 = 1;
 
+/// Clock ticks per second of `times()`, same as `sysconf(_SC_CLK_TCK)` on Linux.
+const USER_HZ: u64 = 100;
+
+const RUSAGE_SELF: isize = 0;
+const RUSAGE_CHILDREN: isize = -1;
+
 #[repr(C)]
 pub struct TimeSpec {
     /// seconds
@@ -11,6 +18,43 @@ pub struct TimeSpec {
     pub nsec: usize,
 }
 
+#[repr(C)]
+pub struct TimeVal {
+    /// seconds
+    pub sec: usize,
+    /// micro seconds
+    pub usec: usize,
+}
+
+#[repr(C)]
+pub struct Tms {
+    tms_utime: usize,
+    tms_stime: usize,
+    tms_cutime: usize,
+    tms_cstime: usize,
+}
+
+/// Same as `struct rusage` of Linux, only the times are filled.
+#[repr(C)]
+pub struct RUsage {
+    ru_utime: TimeVal,
+    ru_stime: TimeVal,
+    ru_others: [isize; 14],
+}
+
+impl TimeVal {
+    fn from_nanos(nanos: u64) -> Self {
+        Self {
+            sec: (nanos / NANOS_PER_SEC) as _,
+            usec: (nanos % NANOS_PER_SEC / 1000) as _,
+        }
+    }
+}
+
+fn nanos_to_clock_ticks(nanos: u64) -> usize {
+    (nanos / (NANOS_PER_SEC / USER_HZ)) as _
+}
+
 impl From<TimeSpec> for TimeValue {
     fn from(ts: TimeSpec) -> Self {
         Self::new(ts.sec as _, ts.nsec as _)
@@ -41,6 +85,41 @@ pub fn sys_clock_nanosleep(_clock_id: u32, flags: u32, req: UserInPtr<TimeSpec>)
     } else {
         current_time() + req.read().into()
     };
-    crate::task::current().sleep(deadline);
+    if crate::task::current().sleep(deadline) {
+        0
+    } else {
+        -1 // interrupted by a signal
+    }
+}
+
+/// Returns the clock ticks elapsed since boot.
+pub fn sys_times(mut tms: UserOutPtr<Tms>) -> isize {
+    if !tms.is_null() {
+        let times = current().cpu_times();
+        tms.write(Tms {
+            tms_utime: nanos_to_clock_ticks(times.utime),
+            tms_stime: nanos_to_clock_ticks(times.stime),
+            tms_cutime: nanos_to_clock_ticks(times.cutime),
+            tms_cstime: nanos_to_clock_ticks(times.cstime),
+        });
+    }
+    nanos_to_clock_ticks(current_time_nanos()) as isize
+}
+
+pub fn sys_getrusage(who: isize, mut usage: UserOutPtr<RUsage>) -> isize {
+    if usage.is_null() {
+        return -1;
+    }
+    let times = current().cpu_times();
+    let (utime, stime) = match who {
+        RUSAGE_SELF => (times.utime, times.stime),
+        RUSAGE_CHILDREN => (times.cutime, times.cstime),
+        _ => return -1,
+    };
+    usage.write(RUsage {
+        ru_utime: TimeVal::from_nanos(utime),
+        ru_stime: TimeVal::from_nanos(stime),
+        ru_others: [0; 14],
+    });
     0
 }
@@ -0,0 +1,174 @@
+//! Futexes, user space locks that only enter the kernel to block or wake up tasks.
+//!
+//! Waiters of a private futex are keyed by the address space and the virtual address
+//! of the futex word, which do not change when a copy-on-write page is copied. Waiters
+//! of a shared futex are keyed by the physical address, so that a futex in a shared
+//! mapping works across address spaces.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use super::manager::{TaskManager, TASK_MANAGER};
+use super::{current, Task};
+use crate::config::{USER_ASPACE_BASE, USER_ASPACE_SIZE};
+use crate::mm::{MemFlags, PhysAddr, VirtAddr};
+use crate::sync::SpinNoIrqLock;
+use crate::timer::{current_time, set_timer, TimeValue};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FutexKey {
+    /// The address space and the virtual address.
+    Private(usize, usize),
+    /// The physical address.
+    Shared(usize),
+}
+
+/// Waiting tasks with the keys of their futexes, in FIFO order. It is a single list so
+/// that a waiter can be requeued to another futex, and still be found when it stops
+/// waiting.
+static FUTEX_WAITERS: SpinNoIrqLock<Vec<(FutexKey, Arc<Task>)>> = SpinNoIrqLock::new(Vec::new());
+
+/// Returns the physical address of the futex word at `uaddr` of the current task, if
+/// `access` is allowed.
+fn translate(uaddr: usize, access: MemFlags) -> Option<usize> {
+    let user_range = USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE;
+    if uaddr % 4 != 0 || !user_range.contains(&uaddr) {
+        return None;
+    }
+    let vaddr = VirtAddr::new(uaddr);
+    let paddr = current().vm().lock().translate(vaddr, access)?;
+    Some(paddr.as_usize())
+}
+
+fn private_key(uaddr: usize) -> FutexKey {
+    FutexKey::Private(Arc::as_ptr(current().vm()) as usize, uaddr)
+}
+
+/// Returns the key of the futex at `uaddr` and the physical address of the futex word.
+/// For a shared futex, copy-on-write pages are copied first, so that the key does not
+/// change when the futex is written later.
+fn futex_key(uaddr: usize, private: bool) -> Option<(FutexKey, usize)> {
+    if private {
+        let paddr = translate(uaddr, MemFlags::READ)?;
+        return Some((private_key(uaddr), paddr));
+    }
+    let writable = MemFlags::READ | MemFlags::WRITE;
+    let paddr = translate(uaddr, writable).or_else(|| translate(uaddr, MemFlags::READ))?;
+    Some((FutexKey::Shared(paddr), paddr))
+}
+
+fn futex_word<'a>(paddr: usize) -> &'a AtomicU32 {
+    unsafe { &*(PhysAddr::new(paddr).into_kvaddr().as_ptr() as *const AtomicU32) }
+}
+
+/// Blocks the current task if the futex at `uaddr` holds `val`, until it is woken up,
+/// a signal arrives, or `deadline` is reached. Returns `true` only if it is woken up by
+/// `futex_wake()` or `futex_requeue()`.
+pub fn futex_wait(uaddr: usize, private: bool, val: u32, deadline: Option<TimeValue>) -> bool {
+    let (key, paddr) = match futex_key(uaddr, private) {
+        Some(key) => key,
+        None => return false,
+    };
+    let curr_task = current();
+    let mut m = TASK_MANAGER.lock();
+    // checked with `TASK_MANAGER` locked, so a wake-up after the check is not lost
+    if futex_word(paddr).load(Ordering::SeqCst) != val
+        || curr_task.has_pending_signals()
+        || deadline.map_or(false, |deadline| current_time() >= deadline)
+    {
+        return false;
+    }
+    FUTEX_WAITERS.lock().push((key, curr_task.clone_task()));
+
+    // The timer may fire after the task is woken up and blocked again somewhere else,
+    // so it only wakes up the task if this wait has not finished.
+    let waiting = Arc::new(AtomicBool::new(true));
+    if let Some(deadline) = deadline {
+        let (task, timer_waiting) = (curr_task.clone_task(), waiting.clone());
+        set_timer(deadline, move |_| {
+            let mut m = TASK_MANAGER.lock();
+            if timer_waiting.load(Ordering::Acquire) {
+                m.unblock_task(task);
+            }
+        });
+    }
+    m.block_current_interruptible(&curr_task);
+
+    // `TASK_MANAGER` is locked again here, the task is still queued if it is not woken
+    // up by a futex operation
+    waiting.store(false, Ordering::Release);
+    let mut waiters = FUTEX_WAITERS.lock();
+    let queued = waiters
+        .iter()
+        .position(|(_, t)| Arc::ptr_eq(t, curr_task.0));
+    match queued {
+        Some(idx) => {
+            waiters.remove(idx);
+            false
+        }
+        None => true,
+    }
+}
+
+fn wake_locked(m: &mut TaskManager, futex: FutexKey, max: usize) -> usize {
+    let mut woken = 0;
+    FUTEX_WAITERS.lock().retain(|(key, task)| {
+        if woken < max && *key == futex {
+            m.unblock_task(task.clone());
+            woken += 1;
+            false
+        } else {
+            true
+        }
+    });
+    woken
+}
+
+/// Wakes up at most `max` tasks waiting on the futex at `uaddr`, returns the number of
+/// woken tasks.
+pub fn futex_wake(uaddr: usize, private: bool, max: usize) -> Option<usize> {
+    let (key, _) = futex_key(uaddr, private)?;
+    Some(wake_locked(&mut TASK_MANAGER.lock(), key, max))
+}
+
+/// Wakes up at most `max_wake` tasks waiting on the futex at `uaddr`, and moves at most
+/// `max_requeue` of the remaining ones to wait on the futex at `uaddr2`. Fails if the
+/// futex does not hold `expected` when it is given. Returns the numbers of woken and
+/// requeued tasks.
+pub fn futex_requeue(
+    uaddr: usize,
+    private: bool,
+    max_wake: usize,
+    uaddr2: usize,
+    max_requeue: usize,
+    expected: Option<u32>,
+) -> Option<(usize, usize)> {
+    let (key, paddr) = futex_key(uaddr, private)?;
+    let (key2, _) = futex_key(uaddr2, private)?;
+    let mut m = TASK_MANAGER.lock();
+    if expected.map_or(false, |val| futex_word(paddr).load(Ordering::SeqCst) != val) {
+        return None;
+    }
+    let woken = wake_locked(&mut m, key, max_wake);
+    let mut requeued = 0;
+    for (waiter_key, _) in FUTEX_WAITERS.lock().iter_mut() {
+        if requeued < max_requeue && *waiter_key == key {
+            *waiter_key = key2;
+            requeued += 1;
+        }
+    }
+    Some((woken, requeued))
+}
+
+/// Clears the thread id at `uaddr` and wakes up a task waiting on it as a private or
+/// shared futex, so that threads can be joined.
+pub(super) fn clear_child_tid(uaddr: usize) {
+    if let Some(paddr) = translate(uaddr, MemFlags::READ | MemFlags::WRITE) {
+        futex_word(paddr).store(0, Ordering::SeqCst);
+        let mut m = TASK_MANAGER.lock();
+        if wake_locked(&mut m, private_key(uaddr), 1) == 0 {
+            wake_locked(&mut m, FutexKey::Shared(paddr), 1);
+        }
+    }
+}
@@ -1,42 +1,175 @@
 use alloc::sync::Arc;
 use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
 
-use super::schedule::{Scheduler, SchedulerTrait};
+use super::schedule::{EDFScheduler, RTScheduler, SchedPolicy, Scheduler, SchedulerTrait};
 use super::structs::{CurrentTask, Task, TaskState, ROOT_TASK};
 use crate::percpu::PerCpu;
 use crate::sync::{LazyInit, SpinNoIrqLock};
 use crate::timer::{current_time, TimeValue};
 
 pub struct TaskManager {
+    /// The normal class, selected by the `sched-*` feature.
     pub scheduler: Scheduler,
+    /// The real-time class, its tasks always run before normal tasks.
+    rt_scheduler: RTScheduler,
+    /// The deadline class, its tasks always run before real-time tasks.
+    dl_scheduler: EDFScheduler,
 }
 
 impl TaskManager {
     fn new() -> Self {
         Self {
             scheduler: Scheduler::new(),
+            rt_scheduler: RTScheduler::new(),
+            dl_scheduler: EDFScheduler::new(),
         }
     }
 
     pub fn scheduler_timer_tick(&mut self) {
-        self.scheduler.timer_tick();
+        let curr_task = CurrentTask::get();
+        let policy = curr_task.sched_policy();
+        if policy.is_deadline() {
+            self.dl_scheduler.timer_tick();
+        } else if policy.is_rt() {
+            self.rt_scheduler.timer_tick();
+            if self.dl_scheduler.has_ready_tasks() {
+                curr_task.set_need_resched();
+            }
+        } else {
+            self.scheduler.timer_tick();
+            if self.dl_scheduler.has_ready_tasks() || self.rt_scheduler.highest_prio().is_some() {
+                curr_task.set_need_resched();
+            }
+        }
     }
 
     pub fn spawn(&mut self, t: Arc<Task>) {
         assert!(t.state() == TaskState::Ready);
-        self.scheduler.push_ready_task_back(t);
+        self.push_ready_task_back(t);
+    }
+
+    fn push_ready_task_back(&mut self, t: Arc<Task>) {
+        let policy = t.sched_policy();
+        if policy.is_deadline() {
+            self.dl_scheduler.push_ready_task_back(t);
+        } else if policy.is_rt() {
+            self.rt_scheduler.push_ready_task_back(t);
+        } else {
+            self.scheduler.push_ready_task_back(t);
+        }
+    }
+
+    fn push_ready_task_front(&mut self, t: Arc<Task>) {
+        let policy = t.sched_policy();
+        if policy.is_deadline() {
+            self.dl_scheduler.push_ready_task_front(t);
+        } else if policy.is_rt() {
+            self.rt_scheduler.push_ready_task_front(t);
+        } else {
+            self.scheduler.push_ready_task_front(t);
+        }
+    }
+
+    fn pick_next_task(&mut self) -> Option<Arc<Task>> {
+        self.dl_scheduler
+            .pick_next_task()
+            .or_else(|| self.rt_scheduler.pick_next_task())
+            .or_else(|| self.scheduler.pick_next_task())
+    }
+
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool {
+        let policy = t.sched_policy();
+        if policy.is_deadline() {
+            self.dl_scheduler.remove_task(t)
+        } else if policy.is_rt() {
+            self.rt_scheduler.remove_task(t)
+        } else {
+            self.scheduler.remove_task(t)
+        }
+    }
+
+    /// Sets `need_resched` of the current task if the queued task `t` should preempt it.
+    ///
+    /// Tasks of a higher class always preempt, otherwise the class decides.
+    fn check_preempt(&mut self, t: &Arc<Task>) {
+        let curr_task = CurrentTask::get();
+        let (policy, curr_policy) = (t.sched_policy(), curr_task.sched_policy());
+        let preempt = if curr_task.is_idle() {
+            true
+        } else if policy.class_rank() != curr_policy.class_rank() {
+            policy.class_rank() > curr_policy.class_rank()
+        } else if policy.is_deadline() {
+            self.dl_scheduler.check_preempt_wakeup(curr_task.0, t)
+        } else if policy.is_rt() {
+            self.rt_scheduler.check_preempt_wakeup(curr_task.0, t)
+        } else {
+            self.scheduler.check_preempt_wakeup(curr_task.0, t)
+        };
+        if preempt {
+            curr_task.set_need_resched();
+        }
+    }
+
+    /// Moves a task to its new class after `change` has updated its scheduling parameters.
+    fn change_class(&mut self, t: &Arc<Task>, change: impl FnOnce(&mut Self)) {
+        let queued = t.state() == TaskState::Ready && self.remove_task(t);
+        change(self);
+        let curr_task = CurrentTask::get();
+        if queued {
+            self.push_ready_task_back(t.clone());
+            self.check_preempt(t);
+        } else if Arc::ptr_eq(t, curr_task.0) {
+            // let the current task be re-queued to its new class
+            curr_task.set_need_resched();
+        }
+    }
+
+    /// Changes the scheduling policy and the real-time priority of a task, moving it to
+    /// the ready queue of the new class if it is ready.
+    pub fn set_scheduler(&mut self, t: &Arc<Task>, policy: SchedPolicy, rt_priority: u8) {
+        self.change_class(t, |this| {
+            if t.sched_policy().is_deadline() {
+                this.dl_scheduler.release(t);
+            }
+            t.set_sched_policy(policy, rt_priority);
+        });
+    }
+
+    /// Moves a task to the deadline class with the given parameters in nanoseconds,
+    /// returns `false` if the bandwidth can not be reserved.
+    pub fn set_deadline(
+        &mut self,
+        t: &Arc<Task>,
+        runtime: u64,
+        deadline: u64,
+        period: u64,
+    ) -> bool {
+        let mut admitted = false;
+        self.change_class(t, |this| {
+            admitted = this.dl_scheduler.admit(t, runtime, deadline, period);
+            if admitted {
+                t.set_sched_policy(SchedPolicy::Deadline, 0);
+            }
+        });
+        admitted
     }
 
     fn switch_to(&self, curr_task: &Arc<Task>, next_task: Arc<Task>) {
         trace!(
             "context switch: {:?} -> {:?}",
-            curr_task.pid(),
-            next_task.pid()
+            curr_task.tid(),
+            next_task.tid()
         );
         next_task.set_state(TaskState::Running);
         if Arc::ptr_eq(curr_task, &next_task) {
             return;
         }
+        if curr_task.is_idle() {
+            crate::timer::start_periodic_tick();
+        }
+        curr_task.account_cpu_time(false);
+        next_task.start_cpu_accounting();
         let curr_ctx_ptr = curr_task.context().as_ptr();
         let next_ctx_ptr = next_task.context().as_ptr();
         // Decrement the strong reference count of `curr_task` and `next_task`,
@@ -53,7 +186,8 @@ impl TaskManager {
 
     fn resched(&mut self, curr_task: &CurrentTask) {
         assert!(curr_task.state() != TaskState::Running);
-        if let Some(next_task) = self.scheduler.pick_next_task() {
+        curr_task.clear_need_resched();
+        if let Some(next_task) = self.pick_next_task() {
             // let `next_task` hold its ownership to avoid clone
             self.switch_to(curr_task, next_task);
         } else {
@@ -67,7 +201,7 @@ impl TaskManager {
         inner.set_state(TaskState::Ready);
         drop(inner);
         if !inner.is_idle() {
-            self.scheduler.push_ready_task_back(curr_task.clone_task());
+            self.push_ready_task_back(curr_task.clone_task());
         }
         self.resched(curr_task);
     }
@@ -75,13 +209,23 @@ impl TaskManager {
     pub fn unblock_task(&mut self, task: Arc<Task>) -> bool {
         if task.state() == TaskState::Sleeping {
             task.set_state(TaskState::Ready);
-            self.scheduler.push_ready_task_front(task);
+            self.push_ready_task_front(task.clone());
+            self.check_preempt(&task);
             true
         } else {
             false
         }
     }
 
+    /// Re-queues a throttled deadline task when its budget is replenished.
+    pub fn unthrottle_task(&mut self, task: Arc<Task>) {
+        if task.state() == TaskState::Throttled {
+            task.set_state(TaskState::Ready);
+            self.push_ready_task_front(task.clone());
+            self.check_preempt(&task);
+        }
+    }
+
     pub fn block_current(&mut self, curr_task: &CurrentTask) {
         // assert not in spin lock
         assert!(curr_task.state() == TaskState::Running);
@@ -90,49 +234,100 @@ impl TaskManager {
         self.resched(curr_task);
     }
 
+    /// Same as `block_current()`, but a signal also wakes up the task. Returns
+    /// immediately if a signal is pending.
+    pub fn block_current_interruptible(&mut self, curr_task: &CurrentTask) {
+        if curr_task.has_pending_signals() {
+            return;
+        }
+        curr_task.set_interruptible(true);
+        self.block_current(curr_task);
+        curr_task.set_interruptible(false);
+    }
+
+    /// Wakes up `t` for a signal if it is in an interruptible sleep.
+    pub fn interrupt_task(&mut self, t: &Arc<Task>) {
+        if t.is_interruptible() {
+            self.unblock_task(t.clone());
+        }
+    }
+
+    /// Blocks the current task until `deadline` or a signal arrives.
     pub fn sleep_current(&mut self, curr_task: &CurrentTask, deadline: TimeValue) {
         assert!(curr_task.state() == TaskState::Running);
         assert!(!curr_task.is_idle());
         if current_time() < deadline {
-            let curr_task_clone = curr_task.clone_task();
+            // The timer may fire after the task is interrupted and blocked again
+            // somewhere else, so it only wakes up the task if this sleep has not finished.
+            let sleeping = Arc::new(AtomicBool::new(true));
+            let (task, timer_sleeping) = (curr_task.clone_task(), sleeping.clone());
             crate::timer::set_timer(deadline, move |_| {
-                TASK_MANAGER.lock().unblock_task(curr_task_clone);
-                CurrentTask::get().set_need_resched();
+                let mut m = TASK_MANAGER.lock();
+                if timer_sleeping.load(Ordering::Acquire) {
+                    m.unblock_task(task);
+                }
             });
-            self.block_current(curr_task);
+            self.block_current_interruptible(curr_task);
+            sleeping.store(false, Ordering::Release);
         }
     }
 
-    pub fn exit_current(&mut self, curr_task: &CurrentTask, exit_code: i32) -> ! {
+    /// Exits the current thread, `last_thread` tells whether it is the last thread of
+    /// the process.
+    pub fn exit_current(
+        &mut self,
+        curr_task: &CurrentTask,
+        exit_code: i32,
+        last_thread: bool,
+    ) -> ! {
         assert!(!curr_task.is_idle());
         assert!(!curr_task.is_root());
         assert!(curr_task.state() == TaskState::Running);
+        let leader = curr_task.group_leader();
         // Make all child tasks as the children of the root task
-        {
+        if last_thread {
             let mut notify = false;
-            let mut children = curr_task.children.lock();
+            let mut children = leader.children.lock();
             for c in children.iter() {
                 ROOT_TASK.add_child(c);
-                if c.state() == TaskState::Zombie {
+                if c.is_reapable() {
                     notify = true;
                 }
             }
             children.clear();
             if notify {
-                ROOT_TASK.wait_children_exit.notify_locked(self);
+                ROOT_TASK.wait_children_exit.notify_all_locked(self);
             }
         }
 
+        if curr_task.sched_policy().is_deadline() {
+            self.dl_scheduler.release(curr_task);
+        }
         curr_task.set_state(TaskState::Zombie);
         curr_task.set_exit_code(exit_code);
 
-        curr_task
-            .parent
-            .lock()
-            .upgrade()
-            .unwrap()
-            .wait_children_exit
-            .notify_locked(self);
+        if !curr_task.is_group_leader() {
+            // Threads are reaped by the root task. The parent of the thread is still
+            // the leader, as it is not waited for by the leader.
+            leader.add_thread_times(curr_task);
+            leader
+                .threads
+                .lock()
+                .retain(|t| !Arc::ptr_eq(t, curr_task.0));
+            ROOT_TASK.children.lock().push(curr_task.clone_task());
+            ROOT_TASK.wait_children_exit.notify_all_locked(self);
+        }
+        // the process can be waited for after all its threads exit
+        if leader.is_reapable() {
+            leader
+                .parent
+                .lock()
+                .upgrade()
+                .unwrap()
+                .wait_children_exit
+                .notify_all_locked(self);
+        }
+        drop(leader);
 
         self.resched(curr_task);
         unreachable!("task exited!");
@@ -144,26 +339,29 @@ impl TaskManager {
             return;
         }
         println!(
-            "{:>4} {:>4} {:>6} {:>4}  STATE",
-            "PID", "PPID", "#CHILD", "#REF",
+            "{:>4} {:>4} {:>6} {:>4} {:>8} {:>8}  STATE",
+            "PID", "PPID", "#CHILD", "#REF", "UTIME", "STIME",
         );
         ROOT_TASK.traverse(&|t: &Arc<Task>| {
-            let pid = t.pid().as_usize();
+            let pid = t.tid().as_usize();
             let ref_count = Arc::strong_count(t);
             let children_count = t.children.lock().len();
             let state = t.state();
-            let shared = if t.is_shared_with_parent() { 'S' } else { ' ' };
+            // in milliseconds
+            let times = t.cpu_times();
+            let (utime, stime) = (times.utime / 1_000_000, times.stime / 1_000_000);
+            let shared = if t.is_group_leader() { ' ' } else { 'S' };
             let parent = t.parent.lock();
             if let Some(p) = parent.upgrade() {
-                let ppid = p.pid().as_usize();
+                let ppid = p.tid().as_usize();
                 println!(
-                    "{:>4}{}{:>4} {:>6} {:>4}  {:?}",
-                    pid, shared, ppid, children_count, ref_count, state
+                    "{:>4}{}{:>4} {:>6} {:>4} {:>8} {:>8}  {:?}",
+                    pid, shared, ppid, children_count, ref_count, utime, stime, state
                 );
             } else {
                 println!(
-                    "{:>4} {:>4} {:>6} {:>4}  {:?}",
-                    pid, '-', children_count, ref_count, state
+                    "{:>4} {:>4} {:>6} {:>4} {:>8} {:>8}  {:?}",
+                    pid, '-', children_count, ref_count, utime, stime, state
                 );
             }
         });
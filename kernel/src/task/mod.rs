@@ -1,16 +1,21 @@
+pub mod futex;
 mod manager;
 mod schedule;
+pub mod signal;
 mod structs;
 mod wait_queue;
 
-pub use structs::{CurrentTask, Task, TaskId};
+pub use schedule::{SchedPolicy, DL_MIN_RUNTIME_NS};
+pub use structs::{CpuTimes, CurrentTask, Task, TaskId};
 
 use alloc::sync::Arc;
 use core::sync::atomic::{AtomicBool, Ordering};
 
 pub use self::manager::TASK_MANAGER;
-use self::structs::ROOT_TASK;
+pub use self::wait_queue::WaitQueue;
+use self::structs::{TaskState, ROOT_TASK};
 use crate::arch::instructions;
+use crate::mm::MemFlags;
 
 static TASK_INITED: AtomicBool = AtomicBool::new(false);
 
@@ -41,11 +46,15 @@ pub fn init() {
         0
     };
 
+    // may read the file system, so do not hold the lock
+    let init_path = option_env!("INIT").unwrap_or("user_shell");
+    let init_task = Task::new_user(init_path).expect("failed to load the init program");
+
     let mut m = TASK_MANAGER.lock();
     m.spawn(ROOT_TASK.clone());
     m.spawn(Task::new_kernel(test_kernel_task, 0xdead));
     m.spawn(Task::new_kernel(test_kernel_task, 0xbeef));
-    m.spawn(Task::new_user("user_shell"));
+    m.spawn(init_task);
 
     TASK_INITED.store(true, Ordering::SeqCst);
 }
@@ -55,18 +64,55 @@ pub fn current<'a>() -> CurrentTask<'a> {
 }
 
 pub fn handle_irq(vector: usize) {
-    let curr = current();
-    curr.clear_need_resched();
     crate::drivers::interrupt::handle_irq(vector);
+    resched_if_needed();
+}
+
+/// Yields the CPU if a woken up task or the scheduler asked to preempt the current task.
+///
+/// Called on returning from interrupts and syscalls, `need_resched` is cleared on the
+/// next context switch.
+pub fn resched_if_needed() {
+    let curr = current();
     if curr.need_resched() {
         curr.yield_now();
     }
 }
 
+/// Charges the time before a trap to the current task, as user time if the trap came
+/// from user mode.
+pub fn account_trap_enter(from_user: bool) {
+    current().account_cpu_time(from_user);
+}
+
+/// Charges the time spent handling a trap to the current task as system time.
+pub fn account_trap_exit() {
+    current().account_cpu_time(false);
+}
+
+/// Handles a page fault of the current task at `vaddr`, returns `false` if the `access`
+/// is not allowed. It may sleep on the lock of the address space, so IRQs are enabled
+/// during it if they were before the fault.
+pub fn handle_page_fault(vaddr: usize, access: MemFlags, irqs_enabled: bool) -> bool {
+    if irqs_enabled {
+        instructions::enable_irqs();
+    }
+    let ret = current().handle_page_fault(vaddr, access);
+    instructions::disable_irqs();
+    ret
+}
+
 pub fn timer_tick_periodic() {
     TASK_MANAGER.lock().scheduler_timer_tick();
 }
 
+/// Finds a live task by its thread id.
+pub fn find_task(tid: TaskId) -> Option<Arc<Task>> {
+    ROOT_TASK
+        .find(tid)
+        .filter(|t| t.state() != TaskState::Zombie)
+}
+
 pub fn spawn_task(task: Arc<Task>) {
     TASK_MANAGER.lock().spawn(task);
 }
@@ -77,6 +123,13 @@ pub fn run() -> ! {
     current().yield_now(); // current task is idle at this time
     loop {
         current().yield_now();
-        // instructions::wait_for_ints(); // the `HLT` instruction has poor performance
+        // No task is ready if we come back, halt until an IRQ wakes up some task. The
+        // periodic tick is restarted when switching to it.
+        instructions::disable_irqs();
+        if !current().need_resched() {
+            crate::timer::stop_periodic_tick();
+            instructions::enable_irqs_and_wait();
+        }
+        instructions::enable_irqs();
     }
 }
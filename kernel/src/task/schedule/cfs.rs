@@ -1,18 +1,221 @@
-use super::rbt::*;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use rbtree::RBTree;
 
-#[allow(dead_code)]
-#[derive(Debug)]
-pub struct CFSchedulerState{
-    vtime : usize,
+use super::SchedulerTrait;
+use crate::task::{current, Task};
+use crate::timer::current_time_nanos;
+
+/// Targeted preemption latency: every runnable task should run once within this period.
+const SCHED_LATENCY_NS: u64 = 20_000_000;
+/// Minimal time slice, used when there are too many runnable tasks to fit in the latency.
+const MIN_GRANULARITY_NS: u64 = 4_000_000;
+/// The leftmost task must lead the current task by this much vruntime to preempt it.
+const WAKEUP_GRANULARITY_NS: u64 = 5_000_000;
+
+/// Load weight of nice 0.
+const NICE_0_LOAD: u64 = 1024;
+
+/// Load weights of nice -20 to 19, each nice level is worth about 10% of CPU time (same as Linux).
+#[rustfmt::skip]
+const NICE_TO_WEIGHT: [u64; 40] = [
+    /* -20 */ 88761, 71755, 56483, 46273, 36291,
+    /* -15 */ 29154, 23254, 18705, 14949, 11916,
+    /* -10 */  9548,  7620,  6100,  4904,  3906,
+    /*  -5 */  3121,  2501,  1991,  1586,  1277,
+    /*   0 */  1024,   820,   655,   526,   423,
+    /*   5 */   335,   272,   215,   172,   137,
+    /*  10 */   110,    87,    70,    56,    45,
+    /*  15 */    36,    29,    23,    18,    15,
+];
+
+fn task_weight(t: &Task) -> u64 {
+    NICE_TO_WEIGHT[(t.nice() + 20) as usize]
+}
+
+#[derive(Default)]
+pub struct CFSchedulerState {
+    /// Virtual runtime in nanoseconds, the key of the ready queue.
+    vruntime: AtomicU64,
+    /// Timestamp of the last runtime accounting while running.
+    exec_start: AtomicU64,
+    /// Total runtime in nanoseconds.
+    sum_exec_runtime: AtomicU64,
+    /// `sum_exec_runtime` when the task was picked to run.
+    prev_sum_exec_runtime: AtomicU64,
+    /// Whether the task is running and its runtime is accounted by this class.
+    on_cpu: AtomicBool,
+    /// The key in the ready queue and the load weight accounted when the task was queued.
+    queued_vruntime: AtomicU64,
+    queued_seq: AtomicUsize,
+    queued_weight: AtomicU64,
+}
+
+pub struct CFScheduler {
+    /// Ready tasks ordered by `(vruntime, seq)`, `seq` keeps FIFO order on ties.
+    ready_queue: RBTree<(u64, usize), Arc<Task>>,
+    /// Monotonically increasing lower bound of the vruntime of all runnable tasks.
+    min_vruntime: u64,
+    /// Total load weight of the ready queue.
+    load_weight: u64,
+    next_seq: usize,
+}
+
+impl CFSchedulerState {
+    fn vruntime(&self) -> u64 {
+        self.vruntime.load(Ordering::Acquire)
+    }
+
+    fn set_vruntime(&self, vruntime: u64) {
+        self.vruntime.store(vruntime, Ordering::Release);
+    }
+
+    fn slice_exec_runtime(&self) -> u64 {
+        self.sum_exec_runtime.load(Ordering::Acquire)
+            - self.prev_sum_exec_runtime.load(Ordering::Acquire)
+    }
+
+    fn start_running(&self, now: u64) {
+        self.on_cpu.store(true, Ordering::Release);
+        self.exec_start.store(now, Ordering::Release);
+        self.prev_sum_exec_runtime.store(
+            self.sum_exec_runtime.load(Ordering::Acquire),
+            Ordering::Release,
+        );
+    }
+
+    /// Charges the time since the last accounting to the task, the vruntime of
+    /// heavier tasks grows slower.
+    fn update_runtime(&self, now: u64, weight: u64) {
+        let delta = now.saturating_sub(self.exec_start.swap(now, Ordering::AcqRel));
+        self.sum_exec_runtime.fetch_add(delta, Ordering::AcqRel);
+        self.vruntime
+            .fetch_add(delta * NICE_0_LOAD / weight, Ordering::AcqRel);
+    }
 }
 
-pub struct CFScheduler{
-    rbt : RBTree,
+impl CFScheduler {
+    /// Length of the time slice of the current task, proportional to its weight.
+    fn sched_slice(&self, curr_weight: u64) -> u64 {
+        let nr_running = self.ready_queue.len() as u64 + 1;
+        let period = SCHED_LATENCY_NS.max(nr_running * MIN_GRANULARITY_NS);
+        period * curr_weight / (self.load_weight + curr_weight)
+    }
+
+    fn leftmost_vruntime(&self) -> Option<u64> {
+        self.ready_queue
+            .first_entry()
+            .map(|(&(vruntime, _), _)| vruntime)
+    }
+
+    fn update_min_vruntime(&mut self, curr_vruntime: Option<u64>) {
+        let vruntime = match (curr_vruntime, self.leftmost_vruntime()) {
+            (Some(c), Some(l)) => c.min(l),
+            (Some(v), None) | (None, Some(v)) => v,
+            (None, None) => return,
+        };
+        self.min_vruntime = self.min_vruntime.max(vruntime);
+    }
+
+    /// Updates the runtime statistics of the running task `t`.
+    fn update_curr(&mut self, t: &Task) {
+        let state = t.sched_state();
+        if !state.on_cpu.load(Ordering::Acquire) {
+            return;
+        }
+        state.update_runtime(current_time_nanos(), task_weight(t));
+        self.update_min_vruntime(Some(state.vruntime()));
+    }
+
+    fn enqueue(&mut self, t: Arc<Task>) {
+        let state = t.sched_state();
+        let weight = task_weight(&t);
+        let key = (state.vruntime(), self.next_seq);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        state.queued_vruntime.store(key.0, Ordering::Release);
+        state.queued_seq.store(key.1, Ordering::Release);
+        state.queued_weight.store(weight, Ordering::Release);
+        self.load_weight += weight;
+        self.ready_queue.insert(key, t);
+    }
 }
 
+impl SchedulerTrait for CFScheduler {
+    fn new() -> Self {
+        Self {
+            ready_queue: RBTree::new(),
+            min_vruntime: 0,
+            load_weight: 0,
+            next_seq: 0,
+        }
+    }
+
+    /// Re-queues a preempted or yielding task, or queues a new one.
+    fn push_ready_task_back(&mut self, t: Arc<Task>) {
+        if Arc::ptr_eq(&t, current().0) {
+            self.update_curr(&t);
+        }
+        // New tasks start from `min_vruntime`, so they can not monopolize the CPU.
+        let state = t.sched_state();
+        state.set_vruntime(state.vruntime().max(self.min_vruntime));
+        self.enqueue(t);
+    }
+
+    /// Re-queues a task that was woken up.
+    ///
+    /// Sleepers get a bonus of half the latency below `min_vruntime` so that they run soon
+    /// after waking up, but can not accumulate credits by sleeping for a long time.
+    fn push_ready_task_front(&mut self, t: Arc<Task>) {
+        let state = t.sched_state();
+        let floor = self.min_vruntime.saturating_sub(SCHED_LATENCY_NS / 2);
+        state.set_vruntime(state.vruntime().max(floor));
+        self.enqueue(t);
+    }
+
+    fn pick_next_task(&mut self) -> Option<Arc<Task>> {
+        let prev = current();
+        self.update_curr(&prev);
+        prev.sched_state().on_cpu.store(false, Ordering::Release);
+        let (_, next) = self.ready_queue.pop_first()?;
+        self.load_weight -= next.sched_state().queued_weight.load(Ordering::Acquire);
+        next.sched_state().start_running(current_time_nanos());
+        self.update_min_vruntime(Some(next.sched_state().vruntime()));
+        Some(next)
+    }
 
-impl CFSchedulerTrait for CFScheduler{
-    fn new() -> Self{
-        // let mut  vec  = crate_vec()
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool {
+        let state = t.sched_state();
+        let key = (
+            state.queued_vruntime.load(Ordering::Acquire),
+            state.queued_seq.load(Ordering::Acquire),
+        );
+        if self.ready_queue.remove(&key).is_some() {
+            self.load_weight -= state.queued_weight.load(Ordering::Acquire);
+            true
+        } else {
+            false
+        }
     }
-}
\ No newline at end of file
+
+    /// The woken up task preempts if its vruntime leads by more than the wakeup granularity.
+    fn check_preempt_wakeup(&mut self, curr: &Arc<Task>, t: &Arc<Task>) -> bool {
+        self.update_curr(curr);
+        t.sched_state().vruntime() + WAKEUP_GRANULARITY_NS < curr.sched_state().vruntime()
+    }
+
+    fn timer_tick(&mut self) {
+        let curr_task = current();
+        if curr_task.is_idle() {
+            return;
+        }
+        self.update_curr(&curr_task);
+        let state = curr_task.sched_state();
+        if state.slice_exec_runtime() >= self.sched_slice(task_weight(&curr_task)) {
+            curr_task.set_need_resched();
+        } else if let Some(leftmost) = self.leftmost_vruntime() {
+            if leftmost + WAKEUP_GRANULARITY_NS < state.vruntime() {
+                curr_task.set_need_resched();
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use rbtree::RBTree;
+
+use super::SchedulerTrait;
+use crate::task::structs::{CurrentTask, TaskState};
+use crate::task::{current, Task, TASK_MANAGER};
+use crate::timer::{current_time_nanos, set_timer, TimeValue};
+
+/// Fixed-point shift of bandwidths (utilizations).
+const BW_SHIFT: u32 = 20;
+/// At most 95% of the CPU can be reserved by deadline tasks.
+const MAX_BW: u64 = (1 << BW_SHIFT) * 95 / 100;
+/// The minimal runtime can be reserved, same as Linux.
+pub const DL_MIN_RUNTIME_NS: u64 = 1 << 10;
+
+fn to_bw(runtime: u64, period: u64) -> u64 {
+    (runtime << BW_SHIFT) / period
+}
+
+/// Per-task state of the deadline class, a constant bandwidth server (CBS) with
+/// budget `dl_runtime` every `dl_period`, and relative deadline `dl_deadline`.
+#[derive(Default)]
+pub struct DLSchedulerState {
+    dl_runtime: AtomicU64,
+    dl_deadline: AtomicU64,
+    dl_period: AtomicU64,
+    /// Remaining budget of the current instance, in nanoseconds.
+    runtime: AtomicI64,
+    /// Absolute deadline of the current instance, in nanoseconds.
+    deadline: AtomicU64,
+    /// Whether the budget is exhausted and the task waits for replenishment.
+    throttled: AtomicBool,
+    /// Whether the task is running and its runtime is accounted by this class.
+    on_cpu: AtomicBool,
+    exec_start: AtomicU64,
+    /// Incremented every time the task is picked, to identify stale budget timers.
+    pick_seq: AtomicUsize,
+    queued_deadline: AtomicU64,
+    queued_seq: AtomicUsize,
+}
+
+/// The earliest deadline first (EDF) class, its tasks run before real-time tasks.
+pub struct EDFScheduler {
+    /// Ready tasks ordered by `(absolute deadline, seq)`.
+    ready_queue: RBTree<(u64, usize), Arc<Task>>,
+    /// Total bandwidth of all deadline tasks.
+    total_bw: u64,
+    next_seq: usize,
+}
+
+impl DLSchedulerState {
+    /// Returns `(runtime, deadline, period)`.
+    pub fn params(&self) -> (u64, u64, u64) {
+        (
+            self.dl_runtime.load(Ordering::Acquire),
+            self.dl_deadline.load(Ordering::Acquire),
+            self.dl_period.load(Ordering::Acquire),
+        )
+    }
+
+    pub fn deadline(&self) -> u64 {
+        self.deadline.load(Ordering::Acquire)
+    }
+
+    fn runtime(&self) -> i64 {
+        self.runtime.load(Ordering::Acquire)
+    }
+
+    fn bw(&self) -> u64 {
+        let (runtime, _, period) = self.params();
+        to_bw(runtime, period)
+    }
+
+    /// Starts a new instance at `now` with a full budget.
+    fn reset(&self, now: u64) {
+        let (runtime, deadline, _) = self.params();
+        self.deadline.store(now + deadline, Ordering::Release);
+        self.runtime.store(runtime as i64, Ordering::Release);
+    }
+
+    /// Refills the budget and postpones the deadline by whole periods until the
+    /// budget is positive.
+    fn replenish(&self, now: u64) {
+        let (dl_runtime, _, dl_period) = self.params();
+        let mut runtime = self.runtime();
+        let mut deadline = self.deadline();
+        while runtime <= 0 {
+            runtime += dl_runtime as i64;
+            deadline += dl_period;
+        }
+        self.runtime.store(runtime, Ordering::Release);
+        self.deadline.store(deadline, Ordering::Release);
+        if deadline <= now {
+            // lagged too far behind, start over
+            self.reset(now);
+        }
+    }
+
+    /// The CBS wake-up rule: whether keeping the current deadline and budget would let the
+    /// task use more than its bandwidth before the deadline.
+    fn cbs_overflow(&self, now: u64) -> bool {
+        let deadline = self.deadline();
+        if deadline <= now {
+            return true;
+        }
+        let (dl_runtime, _, dl_period) = self.params();
+        let runtime = self.runtime().max(0) as u128;
+        runtime * dl_period as u128 > dl_runtime as u128 * (deadline - now) as u128
+    }
+
+    /// Charges the time since the last accounting to the budget.
+    fn update_runtime(&self, now: u64) {
+        if !self.on_cpu.load(Ordering::Acquire) {
+            return;
+        }
+        let delta = now.saturating_sub(self.exec_start.swap(now, Ordering::AcqRel));
+        self.runtime.fetch_sub(delta as i64, Ordering::AcqRel);
+    }
+
+    /// Start time of the next period, when the budget will be replenished.
+    fn next_period(&self) -> u64 {
+        let (_, dl_deadline, dl_period) = self.params();
+        self.deadline() - dl_deadline + dl_period
+    }
+}
+
+impl EDFScheduler {
+    /// Reserves the bandwidth `runtime / period` for `t`, returns `false` if the total
+    /// bandwidth would exceed the limit.
+    ///
+    /// The caller must change the policy of `t` to `SchedPolicy::Deadline` if succeeded.
+    pub fn admit(&mut self, t: &Task, runtime: u64, deadline: u64, period: u64) -> bool {
+        let state = t.dl_sched_state();
+        let old_bw = if t.sched_policy().is_deadline() {
+            state.bw()
+        } else {
+            0
+        };
+        let new_total_bw = self.total_bw - old_bw + to_bw(runtime, period);
+        if new_total_bw > MAX_BW {
+            return false;
+        }
+        self.total_bw = new_total_bw;
+        state.dl_runtime.store(runtime, Ordering::Release);
+        state.dl_deadline.store(deadline, Ordering::Release);
+        state.dl_period.store(period, Ordering::Release);
+        // start a new instance at the next enqueue
+        state.deadline.store(0, Ordering::Release);
+        state.throttled.store(false, Ordering::Release);
+        true
+    }
+
+    /// Releases the bandwidth reserved by `t`, when it exits or leaves the deadline class.
+    pub fn release(&mut self, t: &Task) {
+        self.total_bw -= t.dl_sched_state().bw();
+    }
+
+    pub fn has_ready_tasks(&self) -> bool {
+        !self.ready_queue.is_empty()
+    }
+
+    fn enqueue(&mut self, t: Arc<Task>) {
+        let state = t.dl_sched_state();
+        let key = (state.deadline(), self.next_seq);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        state.queued_deadline.store(key.0, Ordering::Release);
+        state.queued_seq.store(key.1, Ordering::Release);
+        self.ready_queue.insert(key, t);
+    }
+
+    /// Stops `t` until the start of its next period. It is not sleeping, so only the
+    /// replenishment wakes it up.
+    fn throttle(&mut self, t: Arc<Task>) {
+        let state = t.dl_sched_state();
+        state.throttled.store(true, Ordering::Release);
+        t.set_state(TaskState::Throttled);
+        set_timer(TimeValue::from_nanos(state.next_period()), move |_| {
+            TASK_MANAGER.lock().unthrottle_task(t);
+        });
+    }
+
+    /// Arms a timer to preempt `t` when it runs out of its budget.
+    fn start_budget_timer(&self, t: &Arc<Task>, now: u64) {
+        let state = t.dl_sched_state();
+        let seq = state.pick_seq.fetch_add(1, Ordering::AcqRel) + 1;
+        let deadline = now + state.runtime().max(0) as u64;
+        let task = Arc::downgrade(t);
+        set_timer(TimeValue::from_nanos(deadline), move |_| {
+            if let Some(t) = task.upgrade() {
+                let curr_task = CurrentTask::get();
+                if Arc::ptr_eq(&t, curr_task.0)
+                    && t.dl_sched_state().pick_seq.load(Ordering::Acquire) == seq
+                {
+                    curr_task.set_need_resched();
+                }
+            }
+        });
+    }
+}
+
+impl SchedulerTrait for EDFScheduler {
+    fn new() -> Self {
+        Self {
+            ready_queue: RBTree::new(),
+            total_bw: 0,
+            next_seq: 0,
+        }
+    }
+
+    /// Re-queues a preempted or yielding task, or queues a new one. The task is throttled
+    /// if it has overrun its budget.
+    ///
+    /// Must not be called in timer callbacks, as it may set a new timer.
+    fn push_ready_task_back(&mut self, t: Arc<Task>) {
+        let state = t.dl_sched_state();
+        let now = current_time_nanos();
+        state.update_runtime(now);
+        if state.deadline() <= now {
+            state.reset(now);
+        } else if state.runtime() <= 0 {
+            self.throttle(t);
+            return;
+        }
+        self.enqueue(t);
+    }
+
+    /// Re-queues a task that was woken up or replenished.
+    fn push_ready_task_front(&mut self, t: Arc<Task>) {
+        let state = t.dl_sched_state();
+        let now = current_time_nanos();
+        if state.throttled.swap(false, Ordering::AcqRel) || state.runtime() <= 0 {
+            state.replenish(now);
+        } else if state.cbs_overflow(now) {
+            state.reset(now);
+        }
+        self.enqueue(t);
+    }
+
+    fn pick_next_task(&mut self) -> Option<Arc<Task>> {
+        let now = current_time_nanos();
+        let prev = current();
+        prev.dl_sched_state().update_runtime(now);
+        prev.dl_sched_state().on_cpu.store(false, Ordering::Release);
+
+        let (_, next) = self.ready_queue.pop_first()?;
+        let state = next.dl_sched_state();
+        state.on_cpu.store(true, Ordering::Release);
+        state.exec_start.store(now, Ordering::Release);
+        self.start_budget_timer(&next, now);
+        Some(next)
+    }
+
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool {
+        let state = t.dl_sched_state();
+        let key = (
+            state.queued_deadline.load(Ordering::Acquire),
+            state.queued_seq.load(Ordering::Acquire),
+        );
+        self.ready_queue.remove(&key).is_some()
+    }
+
+    fn check_preempt_wakeup(&mut self, curr: &Arc<Task>, t: &Arc<Task>) -> bool {
+        t.dl_sched_state().deadline() < curr.dl_sched_state().deadline()
+    }
+
+    fn timer_tick(&mut self) {
+        let curr_task = current();
+        let state = curr_task.dl_sched_state();
+        state.update_runtime(current_time_nanos());
+        if state.runtime() <= 0 {
+            curr_task.set_need_resched();
+        } else if let Some((&(deadline, _), _)) = self.ready_queue.first_entry() {
+            if deadline < state.deadline() {
+                curr_task.set_need_resched();
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+
+use super::SchedulerTrait;
+use crate::task::Task;
+
+/// A non-preemptive scheduler: tasks run in arrival order until they block or yield.
+#[derive(Default)]
+pub struct FIFOSchedulerState;
+
+pub struct FIFOScheduler {
+    ready_queue: VecDeque<Arc<Task>>,
+}
+
+impl SchedulerTrait for FIFOScheduler {
+    fn new() -> Self {
+        Self {
+            ready_queue: VecDeque::new(),
+        }
+    }
+
+    fn push_ready_task_back(&mut self, t: Arc<Task>) {
+        self.ready_queue.push_back(t);
+    }
+
+    fn push_ready_task_front(&mut self, t: Arc<Task>) {
+        // woken up tasks wait for their turn as well
+        self.ready_queue.push_back(t);
+    }
+
+    fn pick_next_task(&mut self) -> Option<Arc<Task>> {
+        self.ready_queue.pop_front()
+    }
+
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool {
+        if let Some(idx) = self.ready_queue.iter().position(|q| Arc::ptr_eq(q, t)) {
+            self.ready_queue.remove(idx);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn check_preempt_wakeup(&mut self, _curr: &Arc<Task>, _t: &Arc<Task>) -> bool {
+        false
+    }
+
+    fn timer_tick(&mut self) {}
+}
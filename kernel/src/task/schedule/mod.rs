@@ -1,19 +1,94 @@
-#![allow(unused_imports)]
-
-mod round_robin;
+mod edf;
+mod rt;
 
 use crate::task::Task;
 use alloc::sync::Arc;
 
-use round_robin::{RRScheduler, RRSchedulerState};
+pub use edf::{DLSchedulerState, EDFScheduler, DL_MIN_RUNTIME_NS};
+pub use rt::{RTScheduler, RTSchedulerState};
+
+pub const RT_PRIO_MIN: u8 = 1;
+pub const RT_PRIO_MAX: u8 = 99;
+
+/// Scheduling policies, the values are the same as Linux.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SchedPolicy {
+    /// `SCHED_OTHER`, scheduled by the normal class selected by the `sched-*` feature.
+    Normal = 0,
+    /// `SCHED_FIFO`, real-time without time slices.
+    Fifo = 1,
+    /// `SCHED_RR`, real-time with time slices.
+    RoundRobin = 2,
+    /// `SCHED_DEADLINE`, earliest deadline first with bandwidth reservation.
+    Deadline = 6,
+}
+
+impl SchedPolicy {
+    pub fn from_usize(policy: usize) -> Option<Self> {
+        match policy {
+            0 => Some(Self::Normal),
+            1 => Some(Self::Fifo),
+            2 => Some(Self::RoundRobin),
+            6 => Some(Self::Deadline),
+            _ => None,
+        }
+    }
+
+    pub fn is_rt(self) -> bool {
+        matches!(self, Self::Fifo | Self::RoundRobin)
+    }
+
+    pub fn is_deadline(self) -> bool {
+        self == Self::Deadline
+    }
+
+    /// Tasks of a class with a higher rank always run first.
+    pub fn class_rank(self) -> u8 {
+        match self {
+            Self::Normal => 0,
+            Self::Fifo | Self::RoundRobin => 1,
+            Self::Deadline => 2,
+        }
+    }
+
+    /// Checks whether `prio` is a valid static priority of the policy.
+    pub fn is_valid_prio(self, prio: usize) -> bool {
+        if self.is_rt() {
+            (RT_PRIO_MIN as usize..=RT_PRIO_MAX as usize).contains(&prio)
+        } else {
+            prio == 0
+        }
+    }
+}
 
 pub trait SchedulerTrait {
     fn new() -> Self;
     fn push_ready_task_front(&mut self, t: Arc<Task>);
     fn push_ready_task_back(&mut self, t: Arc<Task>);
     fn pick_next_task(&mut self) -> Option<Arc<Task>>;
+    /// Removes a ready task from the queue, returns `false` if it is not queued.
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool;
+    /// Returns whether the woken up task `t`, which has been queued, should preempt the
+    /// current task `curr` of the same class.
+    fn check_preempt_wakeup(&mut self, curr: &Arc<Task>, t: &Arc<Task>) -> bool;
     fn timer_tick(&mut self);
 }
 
-pub type SchedulerState = RRSchedulerState;
-pub type Scheduler = RRScheduler;
+cfg_if! {
+    if #[cfg(feature = "sched-rr")] {
+        mod round_robin;
+        pub type SchedulerState = round_robin::RRSchedulerState;
+        pub type Scheduler = round_robin::RRScheduler;
+    } else if #[cfg(feature = "sched-fifo")] {
+        mod fifo;
+        pub type SchedulerState = fifo::FIFOSchedulerState;
+        pub type Scheduler = fifo::FIFOScheduler;
+    } else if #[cfg(feature = "sched-cfs")] {
+        mod cfs;
+        pub type SchedulerState = cfs::CFSchedulerState;
+        pub type Scheduler = cfs::CFScheduler;
+    } else {
+        compile_error!("No scheduler policy is selected, enable one of the `sched-*` features");
+    }
+}
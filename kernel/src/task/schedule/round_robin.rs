@@ -5,7 +5,7 @@ use core::sync::atomic::{AtomicUsize, Ordering};
 use super::SchedulerTrait;
 use crate::task::{current, Task};
 
-const MAX_QUANTUM: usize = 5;
+const DEFAULT_QUANTUM: usize = 5;
 
 #[derive(Default)]
 pub struct RRSchedulerState {
@@ -17,8 +17,10 @@ pub struct RRScheduler {
 }
 
 impl RRSchedulerState {
-    fn reset(&self) {
-        self.quantum.store(MAX_QUANTUM, Ordering::Release);
+    /// Refills the quantum, nice -20 gets twice the default quantum and nice 19 gets one tick.
+    fn reset(&self, nice: i32) {
+        let quantum = (DEFAULT_QUANTUM as i32 * (20 - nice) / 20).max(1);
+        self.quantum.store(quantum as usize, Ordering::Release);
     }
 
     fn decrease(&self) -> usize {
@@ -36,12 +38,12 @@ impl SchedulerTrait for RRScheduler {
     }
 
     fn push_ready_task_back(&mut self, t: Arc<Task>) {
-        t.sched_state().reset();
+        t.sched_state().reset(t.nice());
         self.ready_queue.push_back(t);
     }
 
     fn push_ready_task_front(&mut self, t: Arc<Task>) {
-        t.sched_state().reset();
+        t.sched_state().reset(t.nice());
         self.ready_queue.push_front(t);
     }
 
@@ -49,6 +51,19 @@ impl SchedulerTrait for RRScheduler {
         self.ready_queue.pop_front()
     }
 
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool {
+        if let Some(idx) = self.ready_queue.iter().position(|q| Arc::ptr_eq(q, t)) {
+            self.ready_queue.remove(idx);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn check_preempt_wakeup(&mut self, curr: &Arc<Task>, t: &Arc<Task>) -> bool {
+        t.nice() < curr.nice()
+    }
+
     fn timer_tick(&mut self) {
         let curr_task = current();
         if !curr_task.is_idle() && curr_task.sched_state().decrease() == 0 {
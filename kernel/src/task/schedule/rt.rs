@@ -0,0 +1,102 @@
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{SchedPolicy, SchedulerTrait, RT_PRIO_MAX};
+use crate::task::{current, Task};
+
+/// Time slice of `SCHED_RR` tasks in ticks.
+const RR_TIMESLICE: usize = 10;
+
+#[derive(Default)]
+pub struct RTSchedulerState {
+    time_slice: AtomicUsize,
+}
+
+/// The real-time class: one FIFO queue per priority, the highest priority runs first.
+pub struct RTScheduler {
+    queues: Vec<VecDeque<Arc<Task>>>,
+    nr_running: usize,
+}
+
+impl RTSchedulerState {
+    fn reset(&self) {
+        self.time_slice.store(RR_TIMESLICE, Ordering::Release);
+    }
+
+    fn decrease(&self) -> usize {
+        let time_slice = self.time_slice.load(Ordering::Acquire).saturating_sub(1);
+        self.time_slice.store(time_slice, Ordering::Release);
+        time_slice
+    }
+}
+
+impl RTScheduler {
+    /// Returns the priority of the highest priority ready task.
+    pub fn highest_prio(&self) -> Option<u8> {
+        if self.nr_running == 0 {
+            return None;
+        }
+        (1..=RT_PRIO_MAX)
+            .rev()
+            .find(|&p| !self.queues[p as usize].is_empty())
+    }
+
+    fn queue_of(&mut self, t: &Task) -> &mut VecDeque<Arc<Task>> {
+        &mut self.queues[t.rt_priority() as usize]
+    }
+}
+
+impl SchedulerTrait for RTScheduler {
+    fn new() -> Self {
+        Self {
+            queues: (0..=RT_PRIO_MAX).map(|_| VecDeque::new()).collect(),
+            nr_running: 0,
+        }
+    }
+
+    fn push_ready_task_back(&mut self, t: Arc<Task>) {
+        if t.rt_sched_state().time_slice.load(Ordering::Acquire) == 0 {
+            t.rt_sched_state().reset();
+        }
+        self.nr_running += 1;
+        self.queue_of(&t).push_back(t);
+    }
+
+    fn push_ready_task_front(&mut self, t: Arc<Task>) {
+        // like Linux, woken up tasks are queued at the tail of their priority
+        self.push_ready_task_back(t);
+    }
+
+    fn pick_next_task(&mut self) -> Option<Arc<Task>> {
+        let prio = self.highest_prio()?;
+        self.nr_running -= 1;
+        self.queues[prio as usize].pop_front()
+    }
+
+    fn remove_task(&mut self, t: &Arc<Task>) -> bool {
+        let queue = self.queue_of(t);
+        if let Some(idx) = queue.iter().position(|q| Arc::ptr_eq(q, t)) {
+            queue.remove(idx);
+            self.nr_running -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn check_preempt_wakeup(&mut self, curr: &Arc<Task>, t: &Arc<Task>) -> bool {
+        t.rt_priority() > curr.rt_priority()
+    }
+
+    fn timer_tick(&mut self) {
+        let curr_task = current();
+        if curr_task.sched_policy() == SchedPolicy::RoundRobin
+            && curr_task.rt_sched_state().decrease() == 0
+        {
+            // go to the tail of its priority queue with a new time slice
+            curr_task.set_need_resched();
+        }
+    }
+}
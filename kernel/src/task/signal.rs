@@ -0,0 +1,346 @@
+//! POSIX-style signals, delivered on the return path to user mode.
+//!
+//! A task killed by signal `sig` exits with code `-sig`.
+
+#![allow(dead_code)]
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{cell::RefCell, mem::size_of};
+
+use super::manager::TASK_MANAGER;
+use super::structs::{TaskState, ROOT_TASK};
+use super::{current, Task};
+use crate::arch::{instructions, TrapFrame};
+use crate::mm::{UserInPtr, UserOutPtr};
+use crate::sync::SpinNoIrqLock;
+
+pub const NSIG: usize = 64;
+
+pub const SIGHUP: usize = 1;
+pub const SIGINT: usize = 2;
+pub const SIGQUIT: usize = 3;
+pub const SIGILL: usize = 4;
+pub const SIGTRAP: usize = 5;
+pub const SIGABRT: usize = 6;
+pub const SIGBUS: usize = 7;
+pub const SIGFPE: usize = 8;
+pub const SIGKILL: usize = 9;
+pub const SIGUSR1: usize = 10;
+pub const SIGSEGV: usize = 11;
+pub const SIGUSR2: usize = 12;
+pub const SIGPIPE: usize = 13;
+pub const SIGALRM: usize = 14;
+pub const SIGTERM: usize = 15;
+pub const SIGCHLD: usize = 17;
+pub const SIGCONT: usize = 18;
+pub const SIGSTOP: usize = 19;
+pub const SIGTSTP: usize = 20;
+pub const SIGTTIN: usize = 21;
+pub const SIGTTOU: usize = 22;
+pub const SIGURG: usize = 23;
+pub const SIGWINCH: usize = 28;
+
+pub const SIG_DFL: usize = 0;
+pub const SIG_IGN: usize = 1;
+
+pub const SA_RESTORER: usize = 0x0400_0000;
+pub const SA_NODEFER: usize = 0x4000_0000;
+pub const SA_RESETHAND: usize = 0x8000_0000;
+
+pub const SIG_BLOCK: usize = 0;
+pub const SIG_UNBLOCK: usize = 1;
+pub const SIG_SETMASK: usize = 2;
+
+/// Signals that can not be caught, blocked or ignored.
+const UNCATCHABLE: u64 = sig_bit(SIGKILL) | sig_bit(SIGSTOP);
+
+/// The bit of `sig` in a signal set.
+pub const fn sig_bit(sig: usize) -> u64 {
+    1 << (sig - 1)
+}
+
+pub fn is_valid(sig: usize) -> bool {
+    (1..=NSIG).contains(&sig)
+}
+
+/// Whether the default action of `sig` is to ignore it, otherwise the task is
+/// terminated. Job control is not supported, so stop signals are ignored.
+fn ignored_by_default(sig: usize) -> bool {
+    matches!(
+        sig,
+        SIGCHLD | SIGCONT | SIGSTOP | SIGTSTP | SIGTTIN | SIGTTOU | SIGURG | SIGWINCH
+    )
+}
+
+/// Same as `struct sigaction` of the Linux kernel on x86_64, on all architectures.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SigAction {
+    pub handler: usize,
+    pub flags: usize,
+    pub restorer: usize,
+    pub mask: u64,
+}
+
+/// Saved on the user stack before calling a handler, restored by `rt_sigreturn`.
+#[repr(C)]
+struct SignalFrame {
+    tf: TrapFrame,
+    blocked: u64,
+}
+
+pub struct SignalState {
+    pending: u64,
+    blocked: u64,
+    /// Shared by the threads of a process.
+    actions: Arc<SpinNoIrqLock<[SigAction; NSIG]>>,
+}
+
+impl SigAction {
+    const DEFAULT: Self = Self {
+        handler: SIG_DFL,
+        flags: 0,
+        restorer: 0,
+        mask: 0,
+    };
+}
+
+impl SignalState {
+    pub fn new() -> Self {
+        Self {
+            pending: 0,
+            blocked: 0,
+            actions: Arc::new(SpinNoIrqLock::new([SigAction::DEFAULT; NSIG])),
+        }
+    }
+
+    /// The state of a forked child, which inherits the actions and the blocked set
+    /// but no pending signals.
+    pub fn fork(&self) -> Self {
+        Self {
+            pending: 0,
+            blocked: self.blocked,
+            actions: Arc::new(SpinNoIrqLock::new(*self.actions.lock())),
+        }
+    }
+
+    /// The state of a new thread, which shares the actions with the creating thread.
+    pub fn clone_thread(&self) -> Self {
+        Self {
+            pending: 0,
+            blocked: self.blocked,
+            actions: self.actions.clone(),
+        }
+    }
+
+    /// Handlers do not exist in the new program, so caught signals are reset to the
+    /// default action. Ignored signals remain ignored.
+    pub fn exec(&mut self) {
+        for action in self.actions.lock().iter_mut() {
+            if action.handler != SIG_IGN {
+                *action = SigAction::DEFAULT;
+            }
+        }
+    }
+
+    pub fn has_deliverable(&self) -> bool {
+        self.pending & !self.blocked != 0
+    }
+
+    fn set_blocked(&mut self, blocked: u64) {
+        self.blocked = blocked & !UNCATCHABLE;
+    }
+
+    /// Returns `false` if the action of `sig` can not be changed.
+    fn set_action(&mut self, sig: usize, action: SigAction) -> bool {
+        if sig_bit(sig) & UNCATCHABLE != 0 {
+            return false;
+        }
+        self.actions.lock()[sig - 1] = action;
+        if action.handler == SIG_IGN {
+            // discard the pending one
+            self.pending &= !sig_bit(sig);
+        }
+        true
+    }
+
+    /// Whether `sig` would be discarded on delivery, blocked signals are kept pending
+    /// as the action may change before they are unblocked.
+    fn is_ignored(&self, sig: usize) -> bool {
+        let handler = self.actions.lock()[sig - 1].handler;
+        self.blocked & sig_bit(sig) == 0
+            && (handler == SIG_IGN || (handler == SIG_DFL && ignored_by_default(sig)))
+    }
+
+    fn add_pending(&mut self, sig: usize) {
+        self.pending |= sig_bit(sig);
+    }
+
+    /// Takes the lowest numbered pending signal that is not blocked.
+    fn dequeue(&mut self) -> Option<usize> {
+        let deliverable = self.pending & !self.blocked;
+        if deliverable == 0 {
+            None
+        } else {
+            let sig = deliverable.trailing_zeros() as usize + 1;
+            self.pending &= !sig_bit(sig);
+            Some(sig)
+        }
+    }
+}
+
+/// Sends `sig` to `t`, and wakes it up if it is in an interruptible sleep.
+pub fn send_signal(t: &Arc<Task>, sig: usize) {
+    let mut m = TASK_MANAGER.lock();
+    let mut state = t.signal.lock();
+    if state.is_ignored(sig) {
+        return;
+    }
+    state.add_pending(sig);
+    let wakeup = state.has_deliverable();
+    drop(state);
+    if wakeup {
+        m.interrupt_task(t);
+    }
+}
+
+/// Sends `sig` to all live user tasks.
+pub fn send_signal_to_user_tasks(sig: usize) {
+    // collect them first, as children lists are locked during the traversal
+    let tasks = RefCell::new(Vec::new());
+    ROOT_TASK.traverse(&|t: &Arc<Task>| {
+        if !t.is_kernel_task() && t.state() != TaskState::Zombie {
+            tasks.borrow_mut().push(t.clone());
+        }
+    });
+    for t in tasks.into_inner() {
+        send_signal(&t, sig);
+    }
+}
+
+/// Sends a signal raised by a fault of the current task. It is delivered even if it
+/// is blocked or ignored, as returning to the faulting instruction is pointless.
+pub fn force_signal(sig: usize) {
+    let curr = current();
+    let mut state = curr.signal.lock();
+    let ignored = state.actions.lock()[sig - 1].handler == SIG_IGN;
+    if state.blocked & sig_bit(sig) != 0 || ignored {
+        state.blocked &= !sig_bit(sig);
+        state.actions.lock()[sig - 1] = SigAction::DEFAULT;
+    }
+    state.add_pending(sig);
+}
+
+/// Delivers pending signals before returning to user mode with `tf`: the task either
+/// exits by the default action, or `tf` is set up to run the handler.
+///
+/// It is called with IRQs disabled, but writing the signal frame and exiting may fault
+/// or sleep on the lock of the address space, so IRQs are enabled while delivering.
+pub fn handle_signals(tf: &mut TrapFrame) {
+    if !current().has_pending_signals() {
+        return;
+    }
+    instructions::enable_irqs();
+    deliver_signals(tf);
+    instructions::disable_irqs();
+}
+
+fn deliver_signals(tf: &mut TrapFrame) {
+    let curr = current();
+    loop {
+        let mut state = curr.signal.lock();
+        let sig = match state.dequeue() {
+            Some(sig) => sig,
+            None => return,
+        };
+        let action = state.actions.lock()[sig - 1];
+        match action.handler {
+            SIG_IGN => {}
+            SIG_DFL => {
+                if !ignored_by_default(sig) {
+                    drop(state);
+                    info!("task {:?} killed by signal {}", curr.tid(), sig);
+                    curr.exit_group(-(sig as i32));
+                }
+            }
+            handler => {
+                let saved_blocked = state.blocked;
+                let mut blocked = state.blocked | action.mask;
+                if action.flags & SA_NODEFER == 0 {
+                    blocked |= sig_bit(sig);
+                }
+                state.set_blocked(blocked);
+                if action.flags & SA_RESETHAND != 0 {
+                    state.actions.lock()[sig - 1] = SigAction::DEFAULT;
+                }
+                drop(state);
+
+                // leave space below the frame for the return address pushed on x86_64
+                let frame_size = size_of::<SignalFrame>();
+                let sp = tf.user_sp().wrapping_sub(frame_size) & !0xf;
+                if !curr.user_range_writable(sp.wrapping_sub(16), frame_size + 16) {
+                    warn!(
+                        "Failed to set up the frame of signal {}, kernel killed it.",
+                        sig
+                    );
+                    curr.exit_group(-(SIGSEGV as i32));
+                }
+                UserOutPtr::<SignalFrame>::from(sp).write(SignalFrame {
+                    tf: *tf,
+                    blocked: saved_blocked,
+                });
+                tf.set_signal_handler(handler, sig, sp, action.restorer);
+                return;
+            }
+        }
+    }
+}
+
+/// Returns from a signal handler, restores the user registers and the blocked set
+/// saved in the signal frame at the user stack.
+pub fn sigreturn(tf: &mut TrapFrame) -> isize {
+    let curr = current();
+    let sp = tf.user_sp();
+    if !curr.user_range_writable(sp, size_of::<SignalFrame>()) {
+        warn!("Invalid signal frame @ {:#x}, kernel killed it.", sp);
+        curr.exit_group(-(SIGSEGV as i32));
+    }
+    let frame = UserInPtr::<SignalFrame>::from(sp).read();
+    if !tf.restore_user_regs(&frame.tf) {
+        warn!("Invalid signal frame @ {:#x}, kernel killed it.", sp);
+        curr.exit_group(-(SIGSEGV as i32));
+    }
+    curr.signal.lock().set_blocked(frame.blocked);
+    // the dispatcher puts the return value to the register saved in the frame
+    tf.ret_value() as isize
+}
+
+/// Changes the action of `sig` of the current task if `act` is given, returns the old
+/// action, or `None` if it can not be changed.
+pub fn sigaction(sig: usize, act: Option<SigAction>) -> Option<SigAction> {
+    let curr = current();
+    let mut state = curr.signal.lock();
+    let old = state.actions.lock()[sig - 1];
+    match act {
+        Some(act) if !state.set_action(sig, act) => None,
+        _ => Some(old),
+    }
+}
+
+/// Changes the blocked set of the current task by `how` if `set` is given, returns the
+/// old blocked set, or `None` if `how` is invalid.
+pub fn sigprocmask(how: usize, set: Option<u64>) -> Option<u64> {
+    let curr = current();
+    let mut state = curr.signal.lock();
+    let old = state.blocked;
+    if let Some(set) = set {
+        let blocked = match how {
+            SIG_BLOCK => old | set,
+            SIG_UNBLOCK => old & !set,
+            SIG_SETMASK => set,
+            _ => return None,
+        };
+        state.set_blocked(blocked);
+    }
+    Some(old)
+}
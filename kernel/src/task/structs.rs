@@ -1,23 +1,29 @@
+use super::futex;
 use super::manager::{TaskLockedCell, TASK_MANAGER};
-use super::schedule::SchedulerState;
-use super::wait_queue::WaitCurrent;
+use super::schedule::{DLSchedulerState, RTSchedulerState, SchedPolicy, SchedulerState};
+use super::signal::{send_signal, SignalState, SIGKILL};
+use super::wait_queue::WaitQueue;
 use crate::arch::{instructions, TaskContext, TrapFrame};
-use crate::config::KERNEL_STACK_SIZE;
+use crate::config::{KERNEL_STACK_SIZE, USER_ASPACE_BASE, USER_ASPACE_SIZE};
 use crate::fs::{File, Stdin, Stdout};
 use crate::loader;
-use crate::mm::{kernel_aspace, MemorySet, VirtAddr};
+use crate::mm::{kernel_aspace, parse_elf, MemFlags, MemorySet, VirtAddr};
 use crate::percpu::PerCpu;
 use crate::sync::UPIntrFreeCell;
 use crate::sync::UPIntrRefMut;
-use crate::sync::{LazyInit, Mutex};
-use crate::timer::TimeValue;
+use crate::sync::{LazyInit, Mutex, SpinNoIrqLock};
+use crate::timer::{current_time, current_time_nanos, TimeValue};
+use alloc::string::String;
 use alloc::sync::{Arc, Weak};
 use alloc::vec;
 use alloc::{boxed::Box, vec::Vec};
 use core::cell::RefMut;
-use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU8, AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 pub(super) static ROOT_TASK: LazyInit<Arc<Task>> = LazyInit::new();
 
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+
 #[derive(Debug)]
 enum EntryState {
     Kernel { pc: usize, arg: usize },
@@ -34,25 +40,65 @@ pub enum TaskState {
     Running = 2,
     Sleeping = 3,
     Zombie = 4,
+    /// A deadline task out of its budget, only woken up when it is replenished.
+    Throttled = 5,
+}
+
+/// State shared by the threads of a process.
+struct ThreadGroup {
+    /// The number of threads that have not exited.
+    live_threads: AtomicUsize,
+    /// Set by `exit_group()`, the other threads exit with it when they are killed.
+    exit_code: SpinNoIrqLock<Option<i32>>,
+}
+
+/// CPU time consumed by a task and its waited-for children, in nanoseconds.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpuTimes {
+    pub utime: u64,
+    pub stime: u64,
+    pub cutime: u64,
+    pub cstime: u64,
 }
 
 pub struct Task {
     id: TaskId,
+    /// The id of the thread group, which is the id of its leader.
+    tgid: TaskId,
+    group: Arc<ThreadGroup>,
     kstack: Stack<KERNEL_STACK_SIZE>,
     need_resched: AtomicBool,
     state: AtomicU8,
     sched_state: SchedulerState,
+    rt_sched_state: RTSchedulerState,
+    dl_sched_state: DLSchedulerState,
+    nice: AtomicI32,
+    policy: AtomicU8,
+    rt_priority: AtomicU8,
+    utime: AtomicU64,
+    stime: AtomicU64,
+    cutime: AtomicU64,
+    cstime: AtomicU64,
+    /// Timestamp of the last CPU time accounting.
+    last_account: AtomicU64,
     ctx: TaskLockedCell<TaskContext>,
     is_kernel: bool,
     entry: EntryState,
     vm: Option<Arc<Mutex<MemorySet>>>,
-    is_shared: bool,
-    pub(super) wait_children_exit: WaitCurrent,
-    pub(super) children: Mutex<Vec<Arc<Task>>>,
+    pub(super) wait_children_exit: WaitQueue,
+    /// Child processes, only of a group leader.
+    pub(super) children: SpinNoIrqLock<Vec<Arc<Task>>>,
+    /// Other threads of the group that have not exited, only of a group leader.
+    pub(super) threads: SpinNoIrqLock<Vec<Arc<Task>>>,
     exit_code: AtomicI32,
-    pub(super) parent: Mutex<Weak<Task>>,
-    // mutable
-    pub inner: UPIntrFreeCell<TaskInner>,
+    pub(super) parent: SpinNoIrqLock<Weak<Task>>,
+    pub(super) signal: SpinNoIrqLock<SignalState>,
+    /// Whether the task is sleeping and can be woken up by signals.
+    interruptible: AtomicBool,
+    /// The user address of the thread id cleared on exit, with a futex wake-up on it.
+    clear_child_tid: AtomicUsize,
+    // mutable, shared by the threads of a process
+    pub inner: Arc<UPIntrFreeCell<TaskInner>>,
 }
 pub struct TaskInner {
 
@@ -84,6 +130,7 @@ impl From<u8> for TaskState {
             2 => Self::Running,
             3 => Self::Sleeping,
             4 => Self::Zombie,
+            5 => Self::Throttled,
             _ => panic!("invalid task state: {}", state),
         }
     }
@@ -96,20 +143,38 @@ impl Task {
     fn new_common(id: TaskId) -> Self {
         Self {
             id,
+            tgid: id,
+            group: Arc::new(ThreadGroup {
+                live_threads: AtomicUsize::new(1),
+                exit_code: SpinNoIrqLock::new(None),
+            }),
             kstack: Stack::default(),
             need_resched: AtomicBool::new(false),
             state: AtomicU8::new(TaskState::Ready as u8),
             sched_state: SchedulerState::default(),
+            rt_sched_state: RTSchedulerState::default(),
+            dl_sched_state: DLSchedulerState::default(),
+            nice: AtomicI32::new(0),
+            policy: AtomicU8::new(SchedPolicy::Normal as u8),
+            rt_priority: AtomicU8::new(0),
+            utime: AtomicU64::new(0),
+            stime: AtomicU64::new(0),
+            cutime: AtomicU64::new(0),
+            cstime: AtomicU64::new(0),
+            last_account: AtomicU64::new(0),
             ctx: TaskLockedCell::new(TaskContext::default()),
             is_kernel: false,
             entry: EntryState::Kernel { pc: 0, arg: 0 },
             vm: None,
-            is_shared: false,
-            wait_children_exit: WaitCurrent::new(),
-            children: Mutex::new(Vec::new()),
+            wait_children_exit: WaitQueue::new(),
+            children: SpinNoIrqLock::new(Vec::new()),
+            threads: SpinNoIrqLock::new(Vec::new()),
             exit_code: AtomicI32::new(0),
-            parent: Mutex::new(Weak::default()),
-            inner: unsafe {
+            parent: SpinNoIrqLock::new(Weak::default()),
+            signal: SpinNoIrqLock::new(SignalState::new()),
+            interruptible: AtomicBool::new(false),
+            clear_child_tid: AtomicUsize::new(0),
+            inner: Arc::new(unsafe {
                 UPIntrFreeCell::new(TaskInner {
                     fd_table: vec![
                         // 0 -> stdin
@@ -120,7 +185,7 @@ impl Task {
                         Some(Arc::new(Stdout)),
                     ],
                 })
-            },
+            }),
         }
     }
 
@@ -165,10 +230,13 @@ impl Task {
         t
     }
 
-    pub fn new_user(path: &str) -> Arc<Self> {
-        let elf_data = loader::get_app_data_by_name(path).expect("new_user: no such app");
+    /// Creates a process running the executable `path`, returns `None` if it does not
+    /// exist or is invalid.
+    pub fn new_user(path: &str) -> Option<Arc<Self>> {
+        let (elf_data, file) = loader::load_app(path)?;
+        let elf = parse_elf(&elf_data)?;
         let mut vm = MemorySet::new();
-        let (entry, ustack_top) = vm.load_user(elf_data);
+        let (entry, ustack_top) = vm.load_user(&elf, file.as_ref(), &[path.into()], &[]);
 
         let mut t = Self::new_common(TaskId::alloc());
         t.entry = EntryState::User(Box::new(TrapFrame::new_user(entry, ustack_top, 0)));
@@ -178,52 +246,114 @@ impl Task {
         t.vm = Some(Arc::new(Mutex::new(vm)));
         let t = Arc::new(t);
         ROOT_TASK.add_child(&t);
-        t
-    }
-
-    pub fn new_clone(self: &Arc<Self>, newsp: usize, tf: &TrapFrame) -> Arc<Self> {
+        Some(t)
+    }
+
+    /// Creates a thread in the group of this task, with the user stack `newsp` and the
+    /// thread pointer `tls` if given. The thread id at `ctid` is cleared when the thread
+    /// exits, if it is not null.
+    pub fn new_clone(
+        self: &Arc<Self>,
+        newsp: usize,
+        tls: Option<usize>,
+        ctid: usize,
+        tf: &TrapFrame,
+    ) -> Arc<Self> {
         assert!(!self.is_kernel_task());
         let mut t = Self::new_common(TaskId::alloc());
-        t.is_shared = true;
+        t.tgid = self.tgid;
+        t.group = self.group.clone();
+        t.group.live_threads.fetch_add(1, Ordering::AcqRel);
+        t.inner = self.inner.clone();
+        t.clear_child_tid = AtomicUsize::new(ctid);
+        t.inherit_sched_params(self);
+        t.signal = SpinNoIrqLock::new(self.signal.lock().clone_thread());
         let vm = self.vm.as_ref().unwrap().clone();
-        t.entry = EntryState::User(Box::new(tf.new_clone(VirtAddr::new(newsp))));
+        let mut tf = tf.new_clone(VirtAddr::new(newsp));
         t.ctx.get_mut().init(
             task_entry as _,
             t.kstack.top(),
             vm.lock().page_table_root(),
             false,
         );
+        t.ctx.get_mut().init_user_tls(&mut tf, tls);
+        t.entry = EntryState::User(Box::new(tf));
         t.vm = Some(vm);
         let t = Arc::new(t);
-        self.add_child(&t);
+        let leader = self.group_leader();
+        *t.parent.lock() = Arc::downgrade(&leader);
+        leader.threads.lock().push(t.clone());
         t
     }
 
     pub fn new_fork(self: &Arc<Self>, tf: &TrapFrame) -> Arc<Self> {
         assert!(!self.is_kernel_task());
         let mut t = Self::new_common(TaskId::alloc());
+        t.inherit_sched_params(self);
+        t.signal = SpinNoIrqLock::new(self.signal.lock().fork());
         let vm = self.vm.as_ref().unwrap().lock().dup();
-        t.entry = EntryState::User(Box::new(tf.new_fork()));
+        t.inner.exclusive_access().fd_table = self.inner_exclusive_access().fd_table.clone();
+        let mut tf = tf.new_fork();
         t.ctx
             .get_mut()
             .init(task_entry as _, t.kstack.top(), vm.page_table_root(), false);
+        t.ctx.get_mut().init_user_tls(&mut tf, None);
+        t.entry = EntryState::User(Box::new(tf));
         t.vm = Some(Arc::new(Mutex::new(vm)));
         let t = Arc::new(t);
-        self.add_child(&t);
+        self.group_leader().add_child(&t);
         t
     }
 
+    /// The process id, which is the id of the thread group.
     pub const fn pid(&self) -> TaskId {
+        self.tgid
+    }
+
+    /// The thread id.
+    pub const fn tid(&self) -> TaskId {
         self.id
     }
+
+    pub const fn is_group_leader(&self) -> bool {
+        self.id.as_usize() == self.tgid.as_usize()
+    }
+
+    /// The leader owns the other threads and the children of the process, and it is
+    /// the parent of the other threads.
+    pub(super) fn group_leader(self: &Arc<Self>) -> Arc<Task> {
+        if self.is_group_leader() {
+            self.clone()
+        } else {
+            self.parent.lock().upgrade().unwrap()
+        }
+    }
+
+    /// Whether the process can be reaped, a leader waits for the other threads.
+    pub(super) fn is_reapable(&self) -> bool {
+        self.state() == TaskState::Zombie && self.threads.lock().is_empty()
+    }
     pub const fn is_idle(&self) -> bool {
         self.id.as_usize() == 0
     }
     pub const fn is_root(&self) -> bool {
         self.id.as_usize() == 1
     }
+    pub(super) fn find(self: &Arc<Self>, tid: TaskId) -> Option<Arc<Task>> {
+        if self.id == tid {
+            return Some(self.clone());
+        }
+        if let Some(t) = self.threads.lock().iter().find(|t| t.id == tid) {
+            return Some(t.clone());
+        }
+        self.children.lock().iter().find_map(|c| c.find(tid))
+    }
+
     pub(super) fn traverse(self: &Arc<Self>, func: &impl Fn(&Arc<Task>)) {
         func(self);
+        for t in self.threads.lock().iter() {
+            func(t);
+        }
         for c in self.children.lock().iter() {
             c.traverse(func);
         }
@@ -244,6 +374,150 @@ impl Task {
     pub(super) const fn sched_state(&self) -> &SchedulerState {
         &self.sched_state
     }
+    /// The nice value in `[NICE_MIN, NICE_MAX]`, a lower value means a higher priority.
+    pub fn nice(&self) -> i32 {
+        self.nice.load(Ordering::Acquire)
+    }
+
+    pub fn set_nice(&self, nice: i32) {
+        self.nice
+            .store(nice.clamp(NICE_MIN, NICE_MAX), Ordering::Release)
+    }
+
+    pub fn sched_policy(&self) -> SchedPolicy {
+        SchedPolicy::from_usize(self.policy.load(Ordering::Acquire) as usize).unwrap()
+    }
+
+    /// The real-time priority in `[1, 99]`, or 0 for non real-time tasks.
+    pub fn rt_priority(&self) -> u8 {
+        self.rt_priority.load(Ordering::Acquire)
+    }
+
+    /// Must be called with the task removed from the ready queue, see
+    /// `TaskManager::set_scheduler()`.
+    pub(super) fn set_sched_policy(&self, policy: SchedPolicy, rt_priority: u8) {
+        self.policy.store(policy as u8, Ordering::Release);
+        self.rt_priority.store(rt_priority, Ordering::Release);
+    }
+
+    pub(super) const fn rt_sched_state(&self) -> &RTSchedulerState {
+        &self.rt_sched_state
+    }
+
+    pub(super) const fn dl_sched_state(&self) -> &DLSchedulerState {
+        &self.dl_sched_state
+    }
+
+    /// Returns `(runtime, deadline, period)` of the deadline class in nanoseconds.
+    pub fn dl_sched_params(&self) -> (u64, u64, u64) {
+        self.dl_sched_state.params()
+    }
+
+    /// Children of deadline tasks fall back to the normal policy, as they have no
+    /// bandwidth reserved.
+    fn inherit_sched_params(&self, parent: &Task) {
+        self.set_nice(parent.nice());
+        if !parent.sched_policy().is_deadline() {
+            self.set_sched_policy(parent.sched_policy(), parent.rt_priority());
+        }
+    }
+
+    pub fn cpu_times(&self) -> CpuTimes {
+        CpuTimes {
+            utime: self.utime.load(Ordering::Acquire),
+            stime: self.stime.load(Ordering::Acquire),
+            cutime: self.cutime.load(Ordering::Acquire),
+            cstime: self.cstime.load(Ordering::Acquire),
+        }
+    }
+
+    /// Charges the time since the last accounting to the user or system time.
+    pub(super) fn account_cpu_time(&self, user: bool) {
+        let now = current_time_nanos();
+        let delta = now.saturating_sub(self.last_account.swap(now, Ordering::AcqRel));
+        let time = if user { &self.utime } else { &self.stime };
+        time.fetch_add(delta, Ordering::AcqRel);
+    }
+
+    /// Starts accounting from now on, when the task is switched to.
+    pub(super) fn start_cpu_accounting(&self) {
+        self.last_account
+            .store(current_time_nanos(), Ordering::Release);
+    }
+
+    /// Adds the times of a reaped child and its own children to the children times.
+    fn add_child_times(&self, child: &Task) {
+        let times = child.cpu_times();
+        self.cutime
+            .fetch_add(times.utime + times.cutime, Ordering::AcqRel);
+        self.cstime
+            .fetch_add(times.stime + times.cstime, Ordering::AcqRel);
+    }
+
+    /// Adds the times of an exited thread to its group leader.
+    pub(super) fn add_thread_times(&self, thread: &Task) {
+        let times = thread.cpu_times();
+        self.utime.fetch_add(times.utime, Ordering::AcqRel);
+        self.stime.fetch_add(times.stime, Ordering::AcqRel);
+        self.cutime.fetch_add(times.cutime, Ordering::AcqRel);
+        self.cstime.fetch_add(times.cstime, Ordering::AcqRel);
+    }
+
+    /// Whether there is a pending signal that is not blocked.
+    pub fn has_pending_signals(&self) -> bool {
+        self.signal.lock().has_deliverable()
+    }
+
+    pub(super) fn is_interruptible(&self) -> bool {
+        self.interruptible.load(Ordering::Acquire)
+    }
+
+    pub(super) fn set_interruptible(&self, interruptible: bool) {
+        self.interruptible.store(interruptible, Ordering::Release)
+    }
+
+    /// Whether `[start, start + size)` is in the user address space and writable.
+    pub(super) fn user_range_writable(&self, start: usize, size: usize) -> bool {
+        let in_aspace = start >= USER_ASPACE_BASE
+            && start
+                .checked_add(size)
+                .map_or(false, |end| end <= USER_ASPACE_BASE + USER_ASPACE_SIZE);
+        in_aspace
+            && self.vm.as_ref().map_or(false, |vm| {
+                vm.lock()
+                    .contains(VirtAddr::new(start), size, MemFlags::USER | MemFlags::WRITE)
+            })
+    }
+
+    /// The address space of a user task.
+    pub fn vm(&self) -> &Arc<Mutex<MemorySet>> {
+        self.vm.as_ref().expect("not a user task")
+    }
+
+    /// Handles a page fault at the user address `vaddr`, returns `false` if the `access`
+    /// is not allowed. Faults in guard pages are reported as stack overflows.
+    pub fn handle_page_fault(&self, vaddr: usize, access: MemFlags) -> bool {
+        if !(USER_ASPACE_BASE..USER_ASPACE_BASE + USER_ASPACE_SIZE).contains(&vaddr) {
+            return false;
+        }
+        let mut vm = match &self.vm {
+            Some(vm) => vm.lock(),
+            None => return false,
+        };
+        let vaddr = VirtAddr::new(vaddr);
+        if vm.handle_page_fault(vaddr, access) {
+            return true;
+        }
+        if vm.is_guard_page(vaddr) {
+            warn!(
+                "Stack overflow of thread {} into the guard page @ {:#x}.",
+                self.tid().as_usize(),
+                vaddr.as_usize(),
+            );
+        }
+        false
+    }
+
     pub(super) const fn context(&self) -> &TaskLockedCell<TaskContext> {
         &self.ctx
     }
@@ -251,10 +525,16 @@ impl Task {
         self.is_kernel
     }
 
-    pub const fn is_shared_with_parent(&self) -> bool {
-        self.is_shared
+    /// Sets the user thread pointer of the current task, which must be this task.
+    pub fn set_user_tls(&self, tf: &mut TrapFrame, tls: usize) {
+        let _guard = TASK_MANAGER.lock();
+        unsafe { (*self.ctx.as_ptr()).set_user_tls(tf, tls) };
+    }
+
+    /// Sets the user address of the thread id cleared on exit, 0 to disable it.
+    pub fn set_clear_child_tid(&self, ctid: usize) {
+        self.clear_child_tid.store(ctid, Ordering::Release);
     }
-    
     pub fn exit_code(&self) -> i32 {
         self.exit_code.load(Ordering::SeqCst)
     }
@@ -280,7 +560,7 @@ impl TaskInner {
 }
 impl Drop for Task {
     fn drop(&mut self) {
-        debug!("Task({}) dropped", self.pid().as_usize());
+        debug!("Task({}) dropped", self.tid().as_usize());
     }
 }
 
@@ -323,61 +603,112 @@ impl<'a> CurrentTask<'a> {
         TASK_MANAGER.lock().yield_current(self);
     }
 
-    pub fn sleep(&self, deadline: TimeValue) {
+    /// Sleeps until `deadline`, returns `false` if woken up by a signal before it.
+    pub fn sleep(&self, deadline: TimeValue) -> bool {
         TASK_MANAGER.lock().sleep_current(self, deadline);
+        current_time() >= deadline
     }
 
+    /// Exits the current thread, the process exits when its last thread exits.
     pub fn exit(&self, exit_code: i32) -> ! {
+        let exit_code = self.group.exit_code.lock().unwrap_or(exit_code);
         info!("task exit with code {}", exit_code);
-        if let Some(vm) = self.vm.as_ref() {
-            if Arc::strong_count(vm) == 1 {
+        let ctid = self.clear_child_tid.load(Ordering::Acquire);
+        if ctid != 0 {
+            futex::clear_child_tid(ctid);
+        }
+        let last_thread = self.group.live_threads.fetch_sub(1, Ordering::AcqRel) == 1;
+        if last_thread {
+            if let Some(vm) = self.vm.as_ref() {
                 vm.lock().clear(); // drop memory set before lock
             }
+            // close files before lock, pipes wake up their waiters when closed
+            let files = core::mem::take(&mut self.inner_exclusive_access().fd_table);
+            drop(files);
         }
-        TASK_MANAGER.lock().exit_current(self, exit_code)
+        TASK_MANAGER
+            .lock()
+            .exit_current(self, exit_code, last_thread)
+    }
+
+    /// Exits all threads of the process with `exit_code`, the other threads are killed
+    /// by `SIGKILL`.
+    pub fn exit_group(&self, exit_code: i32) -> ! {
+        let first = {
+            let mut group_exit_code = self.group.exit_code.lock();
+            let first = group_exit_code.is_none();
+            group_exit_code.get_or_insert(exit_code);
+            first
+        };
+        if first {
+            let leader = self.group_leader();
+            let mut threads = leader.threads.lock().clone();
+            threads.push(leader);
+            for t in threads.iter().filter(|t| !Arc::ptr_eq(t, self.0)) {
+                send_signal(t, SIGKILL);
+            }
+        }
+        self.exit(exit_code)
     }
 
-    pub fn exec(&self, path: &str, tf: &mut TrapFrame) -> isize {
+    pub fn exec(&self, path: &str, args: &[String], envs: &[String], tf: &mut TrapFrame) -> isize {
         assert!(!self.is_kernel_task());
-        assert_eq!(Arc::strong_count(self.vm.as_ref().unwrap()), 1);
-        if let Some(elf_data) = loader::get_app_data_by_name(path) {
-            let mut vm = self.vm.as_ref().unwrap().lock();
-            vm.clear();
-            let (entry, ustack_top) = vm.load_user(elf_data);
-            *tf = TrapFrame::new_user(entry, ustack_top, 0);
-            instructions::flush_tlb_all();
-            0
-        } else {
-            -1
+        if self.group.live_threads.load(Ordering::Acquire) > 1 {
+            // the other threads would lose their address space
+            return -1;
         }
-    }
-
+        let (elf_data, file) = match loader::load_app(path) {
+            Some(app) => app,
+            None => return -1,
+        };
+        // checked before the old address space is cleared, so that exec can fail
+        let elf = match parse_elf(&elf_data) {
+            Some(elf) => elf,
+            None => return -1,
+        };
+        let mut vm = self.vm.as_ref().unwrap().lock();
+        vm.clear();
+        let (entry, ustack_top) = vm.load_user(&elf, file.as_ref(), args, envs);
+        *tf = TrapFrame::new_user(entry, ustack_top, 0);
+        self.set_user_tls(tf, 0);
+        self.signal.lock().exec();
+        instructions::flush_tlb_all();
+        0
+    }
+
+    /// Waits for a child to exit and reaps it, returns `None` if there is no such
+    /// child or the wait is interrupted by a signal. Any thread can wait for the
+    /// children of the process.
     pub fn waitpid(&self, pid: isize, _options: u32) -> Option<(TaskId, i32)> {
-        let mut found_pid = false;
-        for t in self.children.lock().iter() {
-            if pid == -1 || t.pid().as_usize() == pid as usize {
-                found_pid = true;
-                break;
-            }
-        }
-        if !found_pid {
-            return None;
-        }
-
+        let matches = |t: &Arc<Task>| pid == -1 || t.pid().as_usize() == pid as usize;
+        let leader = self.group_leader();
         loop {
             {
-                let mut children = self.children.lock();
-                for (idx, t) in children.iter().enumerate() {
-                    if (pid == -1 || t.pid().as_usize() == pid as usize)
-                        && t.state() == TaskState::Zombie
-                    {
-                        let child = children.remove(idx);
-                        assert_eq!(Arc::strong_count(&child), 1);
-                        return Some((child.pid(), child.exit_code()));
+                let mut children = leader.children.lock();
+                if !children.iter().any(matches) {
+                    // no such child, or it was waited for by another task
+                    return None;
+                }
+                if let Some(idx) = children.iter().position(|t| matches(t) && t.is_reapable()) {
+                    let child = children.remove(idx);
+                    assert_eq!(Arc::strong_count(&child), 1);
+                    // the times of threads reaped by the root task are added to their
+                    // leaders on exit
+                    if child.is_group_leader() {
+                        leader.add_child_times(&child);
                     }
+                    return Some((child.pid(), child.exit_code()));
                 }
             }
-            self.wait_children_exit.wait();
+            // until there is a child to reap, or no child to wait for
+            let woken = leader.wait_children_exit.wait_until_interruptible(|| {
+                let children = leader.children.lock();
+                children.iter().any(|t| matches(t) && t.is_reapable())
+                    || !children.iter().any(matches)
+            });
+            if !woken {
+                return None;
+            }
         }
     }
 }
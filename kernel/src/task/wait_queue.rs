@@ -1,44 +1,92 @@
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 
 use super::manager::{TaskManager, TASK_MANAGER};
 use super::{current, Task};
 use crate::sync::SpinNoIrqLock;
 
-pub struct WaitCurrent {
-    current: SpinNoIrqLock<Option<Arc<Task>>>,
+/// A queue of tasks waiting for some condition, any number of tasks can wait on it.
+pub struct WaitQueue {
+    /// Waiting tasks in FIFO order, a `Vec` so that `new()` can be `const`.
+    queue: SpinNoIrqLock<Vec<Arc<Task>>>,
 }
 
-impl WaitCurrent {
+impl WaitQueue {
     pub const fn new() -> Self {
         Self {
-            current: SpinNoIrqLock::new(None),
+            queue: SpinNoIrqLock::new(Vec::new()),
         }
     }
 
-    pub fn wait(&self) {
+    /// Blocks the current task until `condition` returns `true`.
+    ///
+    /// The condition is checked with `TASK_MANAGER` locked, so a notification between
+    /// the check and blocking is not lost.
+    pub fn wait_until(&self, condition: impl Fn() -> bool) {
         assert!(!TASK_MANAGER.is_locked());
-        let mut m = TASK_MANAGER.lock();
-        let curr_task = current();
-        assert!(self
-            .current
-            .lock()
-            .replace(curr_task.clone_task())
-            .is_none());
-        m.block_current(&curr_task);
-    }
-
-    #[allow(dead_code)]
-    pub fn notify(&self) -> bool {
+        loop {
+            let mut m = TASK_MANAGER.lock();
+            if condition() {
+                break;
+            }
+            let curr_task = current();
+            self.queue.lock().push(curr_task.clone_task());
+            m.block_current(&curr_task);
+        }
+    }
+
+    /// Same as `wait_until()`, but returns `false` if a signal arrives first.
+    pub fn wait_until_interruptible(&self, condition: impl Fn() -> bool) -> bool {
+        assert!(!TASK_MANAGER.is_locked());
+        loop {
+            let mut m = TASK_MANAGER.lock();
+            if condition() {
+                return true;
+            }
+            let curr_task = current();
+            if curr_task.has_pending_signals() {
+                return false;
+            }
+            self.queue.lock().push(curr_task.clone_task());
+            m.block_current_interruptible(&curr_task);
+            // still queued if woken up by a signal
+            self.remove(&curr_task);
+        }
+    }
+
+    /// Wakes up the first sleeping task, returns `false` if there is none.
+    pub fn notify_one(&self) -> bool {
+        assert!(!TASK_MANAGER.is_locked());
+        self.notify_one_locked(&mut TASK_MANAGER.lock())
+    }
+
+    /// Wakes up all waiting tasks.
+    pub fn notify_all(&self) {
         assert!(!TASK_MANAGER.is_locked());
-        self.notify_locked(&mut TASK_MANAGER.lock())
+        self.notify_all_locked(&mut TASK_MANAGER.lock())
+    }
+
+    pub(super) fn notify_one_locked(&self, m: &mut TaskManager) -> bool {
+        assert!(TASK_MANAGER.is_locked());
+        let mut queue = self.queue.lock();
+        // tasks woken up by a signal are still queued until they run, the notification
+        // goes to the next one
+        while !queue.is_empty() {
+            if m.unblock_task(queue.remove(0)) {
+                return true;
+            }
+        }
+        false
     }
 
-    pub(super) fn notify_locked(&self, m: &mut TaskManager) -> bool {
+    pub(super) fn notify_all_locked(&self, m: &mut TaskManager) {
         assert!(TASK_MANAGER.is_locked());
-        if let Some(t) = self.current.lock().take() {
-            m.unblock_task(t)
-        } else {
-            false
+        for t in self.queue.lock().drain(..) {
+            m.unblock_task(t);
         }
     }
+
+    fn remove(&self, t: &Arc<Task>) {
+        self.queue.lock().retain(|q| !Arc::ptr_eq(q, t));
+    }
 }
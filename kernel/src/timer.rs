@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use core::sync::atomic::{AtomicU64, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use crate::sync::{LazyInit, SpinNoIrqLock};
 use crate::utils::timer_list::TimerList;
@@ -12,9 +12,13 @@ pub const NANOS_PER_SEC: u64 = 1_000_000_000;
 pub const MICROS_PER_SEC: u64 = 1_000_000;
 
 const PERIODIC_INTERVAL_NANOS: u64 = NANOS_PER_SEC / crate::config::TICKS_PER_SEC;
+/// The longest time to wait for an interrupt when the periodic tick is stopped and no
+/// timer is set.
+const MAX_IDLE_NANOS: u64 = NANOS_PER_SEC;
 
 static NEXT_DEADLINE: AtomicU64 = AtomicU64::new(0);
 static NEXT_PERIODIC_DEADLINE: AtomicU64 = AtomicU64::new(0);
+static PERIODIC_TICK_ENABLED: AtomicBool = AtomicBool::new(true);
 
 static TIMER_LIST: LazyInit<SpinNoIrqLock<TimerList>> = LazyInit::new();
 
@@ -38,6 +42,33 @@ pub fn init() {
     update_deadline(deadline);
 }
 
+/// Stops the periodic tick, so that an idle CPU is only woken up by timers and devices.
+pub fn stop_periodic_tick() {
+    assert!(crate::arch::instructions::irqs_disabled());
+    if !PERIODIC_TICK_ENABLED.swap(false, Ordering::AcqRel) {
+        return;
+    }
+    let next_deadline = TIMER_LIST
+        .lock()
+        .next_deadline()
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_else(|| current_time_nanos() + MAX_IDLE_NANOS);
+    update_deadline(next_deadline);
+}
+
+/// Restarts the periodic tick from now on, when a CPU leaves idle.
+pub fn start_periodic_tick() {
+    assert!(crate::arch::instructions::irqs_disabled());
+    if PERIODIC_TICK_ENABLED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let deadline = current_time_nanos() + PERIODIC_INTERVAL_NANOS;
+    NEXT_PERIODIC_DEADLINE.store(deadline, Ordering::Release);
+    if deadline < NEXT_DEADLINE.load(Ordering::Acquire) {
+        update_deadline(deadline);
+    }
+}
+
 pub fn set_timer(deadline: TimeValue, callback: impl FnOnce(TimeValue) + Send + Sync + 'static) {
     TIMER_LIST.lock().set(deadline, callback);
     let deadline_ns = deadline.as_nanos() as u64;
@@ -48,15 +79,18 @@ pub fn set_timer(deadline: TimeValue, callback: impl FnOnce(TimeValue) + Send +
 
 pub fn handle_timer_irq() {
     assert!(crate::arch::instructions::irqs_disabled());
+    crate::fs::poll_console();
 
     let now_ns = current_time_nanos();
-    let mut next_deadline = NEXT_PERIODIC_DEADLINE.load(Ordering::Acquire);
-
-    if now_ns >= next_deadline {
-        crate::task::timer_tick_periodic();
-        NEXT_PERIODIC_DEADLINE.fetch_add(PERIODIC_INTERVAL_NANOS, Ordering::Release);
-        next_deadline = NEXT_PERIODIC_DEADLINE.load(Ordering::Acquire);
-    }
+    let mut next_deadline = if PERIODIC_TICK_ENABLED.load(Ordering::Acquire) {
+        if now_ns >= NEXT_PERIODIC_DEADLINE.load(Ordering::Acquire) {
+            crate::task::timer_tick_periodic();
+            NEXT_PERIODIC_DEADLINE.fetch_add(PERIODIC_INTERVAL_NANOS, Ordering::Release);
+        }
+        NEXT_PERIODIC_DEADLINE.load(Ordering::Acquire)
+    } else {
+        now_ns + MAX_IDLE_NANOS
+    };
 
     let mut timers = TIMER_LIST.lock();
     while timers.expire_one(current_time()).is_some() {}
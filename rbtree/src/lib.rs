@@ -0,0 +1,699 @@
+//! An arena-based Red-Black tree.
+//!
+//! Nodes are kept in a `Vec` and linked by index, so the tree can hold any
+//! `K: Ord` and `V` (e.g. `Arc<Task>`) without extra per-node allocations
+//! after the arena has grown. Freed slots are recycled by later insertions.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::{Ord, Ordering};
+use core::fmt;
+
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: usize,
+    right: usize,
+    parent: usize,
+    red: bool,
+}
+
+/// A Red-Black tree ordered by `K`
+pub struct RBTree<K: Ord, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    root: usize,
+    len: usize,
+}
+
+impl<K: Ord, V> RBTree<K, V> {
+    /// Creates an empty tree
+    pub const fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: NIL,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of occupied nodes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree contains no elements
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the tree
+    ///
+    /// This function runs in `O(n)`, where `n` - is the number of nodes
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = NIL;
+        self.len = 0;
+    }
+
+    /// Returns true if the map contains a value for the specified key
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    #[must_use]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(k) != NIL
+    }
+
+    /// Returns a key-value pair corresponding to the supplied key
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    #[must_use]
+    pub fn get_entry<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entry_at(self.find(k))
+    }
+
+    /// Returns the value corresponding to the key
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    #[must_use]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_entry(k).map(|(_, v)| v)
+    }
+
+    /// Inserts a new key-value pair and returns the old value if it was present
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let mut parent = NIL;
+        let mut cur = self.root;
+        let mut go_left = false;
+        while cur != NIL {
+            parent = cur;
+            match k.cmp(&self.node(cur).key) {
+                Ordering::Less => {
+                    go_left = true;
+                    cur = self.node(cur).left;
+                }
+                Ordering::Greater => {
+                    go_left = false;
+                    cur = self.node(cur).right;
+                }
+                Ordering::Equal => {
+                    return Some(core::mem::replace(&mut self.node_mut(cur).value, v));
+                }
+            }
+        }
+
+        let z = self.alloc_node(Node {
+            key: k,
+            value: v,
+            left: NIL,
+            right: NIL,
+            parent,
+            red: true,
+        });
+        if parent == NIL {
+            self.root = z;
+        } else if go_left {
+            self.node_mut(parent).left = z;
+        } else {
+            self.node_mut(parent).right = z;
+        }
+        self.len += 1;
+        self.insert_fixup(z);
+        None
+    }
+
+    /// Deletes entry and returns the value
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// Deletes entry and returns the key-value pair
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let z = self.find(key);
+        if z == NIL {
+            None
+        } else {
+            Some(self.delete_node(z))
+        }
+    }
+
+    /// Returns the first key-value pair in the map
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    #[must_use]
+    pub fn first_entry(&self) -> Option<(&K, &V)> {
+        if self.root == NIL {
+            return None;
+        }
+        self.entry_at(self.minimum(self.root))
+    }
+
+    /// Returns the last key-value pair in the map
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    #[must_use]
+    pub fn last_entry(&self) -> Option<(&K, &V)> {
+        if self.root == NIL {
+            return None;
+        }
+        let mut cur = self.root;
+        while self.node(cur).right != NIL {
+            cur = self.node(cur).right;
+        }
+        self.entry_at(cur)
+    }
+
+    /// Removes and returns the first key-value pair in the map
+    ///
+    /// This function runs in `O(log(n))`, where `n` - is the number of nodes
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.root == NIL {
+            return None;
+        }
+        let first = self.minimum(self.root);
+        Some(self.delete_node(first))
+    }
+
+    /// Creates an iterator over key-value pairs, in order by key
+    #[must_use]
+    pub fn pairs(&self) -> PairsIterator<'_, K, V> {
+        PairsIterator {
+            tree: self,
+            next: if self.root == NIL {
+                NIL
+            } else {
+                self.minimum(self.root)
+            },
+        }
+    }
+
+    /// Creates an iterator over keys, from smallest to biggest
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.pairs().map(|(k, _)| k)
+    }
+
+    /// Creates an iterator over values, in order by key
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.pairs().map(|(_, v)| v)
+    }
+}
+
+impl<K: Ord, V> RBTree<K, V> {
+    fn node(&self, i: usize) -> &Node<K, V> {
+        self.nodes[i].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut Node<K, V> {
+        self.nodes[i].as_mut().unwrap()
+    }
+
+    fn is_red(&self, i: usize) -> bool {
+        i != NIL && self.node(i).red
+    }
+
+    fn set_red(&mut self, i: usize, red: bool) {
+        if i != NIL {
+            self.node_mut(i).red = red;
+        }
+    }
+
+    fn set_parent(&mut self, i: usize, parent: usize) {
+        if i != NIL {
+            self.node_mut(i).parent = parent;
+        }
+    }
+
+    fn entry_at(&self, i: usize) -> Option<(&K, &V)> {
+        if i == NIL {
+            None
+        } else {
+            let n = self.node(i);
+            Some((&n.key, &n.value))
+        }
+    }
+
+    fn alloc_node(&mut self, node: Node<K, V>) -> usize {
+        if let Some(i) = self.free.pop() {
+            self.nodes[i] = Some(node);
+            i
+        } else {
+            self.nodes.push(Some(node));
+            self.nodes.len() - 1
+        }
+    }
+
+    fn find<Q>(&self, k: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut cur = self.root;
+        while cur != NIL {
+            let n = self.node(cur);
+            cur = match k.cmp(n.key.borrow()) {
+                Ordering::Less => n.left,
+                Ordering::Greater => n.right,
+                Ordering::Equal => return cur,
+            };
+        }
+        NIL
+    }
+
+    fn minimum(&self, mut i: usize) -> usize {
+        while self.node(i).left != NIL {
+            i = self.node(i).left;
+        }
+        i
+    }
+
+    fn successor(&self, mut i: usize) -> usize {
+        if self.node(i).right != NIL {
+            return self.minimum(self.node(i).right);
+        }
+        let mut p = self.node(i).parent;
+        while p != NIL && i == self.node(p).right {
+            i = p;
+            p = self.node(p).parent;
+        }
+        p
+    }
+
+    fn rotate_left(&mut self, x: usize) {
+        let y = self.node(x).right;
+        let y_left = self.node(y).left;
+        self.node_mut(x).right = y_left;
+        self.set_parent(y_left, x);
+        let xp = self.node(x).parent;
+        self.node_mut(y).parent = xp;
+        if xp == NIL {
+            self.root = y;
+        } else if x == self.node(xp).left {
+            self.node_mut(xp).left = y;
+        } else {
+            self.node_mut(xp).right = y;
+        }
+        self.node_mut(y).left = x;
+        self.node_mut(x).parent = y;
+    }
+
+    fn rotate_right(&mut self, x: usize) {
+        let y = self.node(x).left;
+        let y_right = self.node(y).right;
+        self.node_mut(x).left = y_right;
+        self.set_parent(y_right, x);
+        let xp = self.node(x).parent;
+        self.node_mut(y).parent = xp;
+        if xp == NIL {
+            self.root = y;
+        } else if x == self.node(xp).right {
+            self.node_mut(xp).right = y;
+        } else {
+            self.node_mut(xp).left = y;
+        }
+        self.node_mut(y).right = x;
+        self.node_mut(x).parent = y;
+    }
+
+    fn insert_fixup(&mut self, mut z: usize) {
+        while self.is_red(self.node(z).parent) {
+            let mut p = self.node(z).parent;
+            let g = self.node(p).parent;
+            if p == self.node(g).left {
+                let u = self.node(g).right;
+                if self.is_red(u) {
+                    self.set_red(p, false);
+                    self.set_red(u, false);
+                    self.set_red(g, true);
+                    z = g;
+                } else {
+                    if z == self.node(p).right {
+                        z = p;
+                        self.rotate_left(z);
+                        p = self.node(z).parent;
+                    }
+                    self.set_red(p, false);
+                    self.set_red(g, true);
+                    self.rotate_right(g);
+                }
+            } else {
+                let u = self.node(g).left;
+                if self.is_red(u) {
+                    self.set_red(p, false);
+                    self.set_red(u, false);
+                    self.set_red(g, true);
+                    z = g;
+                } else {
+                    if z == self.node(p).left {
+                        z = p;
+                        self.rotate_right(z);
+                        p = self.node(z).parent;
+                    }
+                    self.set_red(p, false);
+                    self.set_red(g, true);
+                    self.rotate_left(g);
+                }
+            }
+        }
+        let root = self.root;
+        self.set_red(root, false);
+    }
+
+    /// Replaces the subtree rooted at `u` with the subtree rooted at `v`.
+    fn transplant(&mut self, u: usize, v: usize) {
+        let up = self.node(u).parent;
+        if up == NIL {
+            self.root = v;
+        } else if u == self.node(up).left {
+            self.node_mut(up).left = v;
+        } else {
+            self.node_mut(up).right = v;
+        }
+        self.set_parent(v, up);
+    }
+
+    fn delete_node(&mut self, z: usize) -> (K, V) {
+        let (zl, zr) = (self.node(z).left, self.node(z).right);
+        let x;
+        let x_parent;
+        let removed_red;
+        if zl == NIL {
+            x = zr;
+            x_parent = self.node(z).parent;
+            removed_red = self.node(z).red;
+            self.transplant(z, zr);
+        } else if zr == NIL {
+            x = zl;
+            x_parent = self.node(z).parent;
+            removed_red = self.node(z).red;
+            self.transplant(z, zl);
+        } else {
+            let y = self.minimum(zr);
+            removed_red = self.node(y).red;
+            x = self.node(y).right;
+            if self.node(y).parent == z {
+                x_parent = y;
+            } else {
+                x_parent = self.node(y).parent;
+                self.transplant(y, x);
+                self.node_mut(y).right = zr;
+                self.node_mut(zr).parent = y;
+            }
+            self.transplant(z, y);
+            self.node_mut(y).left = zl;
+            self.node_mut(zl).parent = y;
+            let z_red = self.node(z).red;
+            self.node_mut(y).red = z_red;
+        }
+        if !removed_red {
+            self.delete_fixup(x, x_parent);
+        }
+
+        let node = self.nodes[z].take().unwrap();
+        self.free.push(z);
+        self.len -= 1;
+        (node.key, node.value)
+    }
+
+    fn delete_fixup(&mut self, mut x: usize, mut parent: usize) {
+        while x != self.root && !self.is_red(x) {
+            if x == self.node(parent).left {
+                let mut w = self.node(parent).right;
+                if self.is_red(w) {
+                    self.set_red(w, false);
+                    self.set_red(parent, true);
+                    self.rotate_left(parent);
+                    w = self.node(parent).right;
+                }
+                if !self.is_red(self.node(w).left) && !self.is_red(self.node(w).right) {
+                    self.set_red(w, true);
+                    x = parent;
+                    parent = self.node(x).parent;
+                } else {
+                    if !self.is_red(self.node(w).right) {
+                        let wl = self.node(w).left;
+                        self.set_red(wl, false);
+                        self.set_red(w, true);
+                        self.rotate_right(w);
+                        w = self.node(parent).right;
+                    }
+                    let parent_red = self.node(parent).red;
+                    self.set_red(w, parent_red);
+                    self.set_red(parent, false);
+                    let wr = self.node(w).right;
+                    self.set_red(wr, false);
+                    self.rotate_left(parent);
+                    x = self.root;
+                }
+            } else {
+                let mut w = self.node(parent).left;
+                if self.is_red(w) {
+                    self.set_red(w, false);
+                    self.set_red(parent, true);
+                    self.rotate_right(parent);
+                    w = self.node(parent).left;
+                }
+                if !self.is_red(self.node(w).right) && !self.is_red(self.node(w).left) {
+                    self.set_red(w, true);
+                    x = parent;
+                    parent = self.node(x).parent;
+                } else {
+                    if !self.is_red(self.node(w).left) {
+                        let wr = self.node(w).right;
+                        self.set_red(wr, false);
+                        self.set_red(w, true);
+                        self.rotate_left(w);
+                        w = self.node(parent).left;
+                    }
+                    let parent_red = self.node(parent).red;
+                    self.set_red(w, parent_red);
+                    self.set_red(parent, false);
+                    let wl = self.node(w).left;
+                    self.set_red(wl, false);
+                    self.rotate_right(parent);
+                    x = self.root;
+                }
+            }
+        }
+        self.set_red(x, false);
+    }
+}
+
+impl<K: Ord, V> Default for RBTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over the key-value pairs of a [`RBTree`], in order by key
+pub struct PairsIterator<'a, K: Ord, V> {
+    tree: &'a RBTree<K, V>,
+    next: usize,
+}
+
+impl<'a, K: Ord, V> Iterator for PairsIterator<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == NIL {
+            return None;
+        }
+        let cur = self.next;
+        self.next = self.tree.successor(cur);
+        self.tree.entry_at(cur)
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for RBTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.pairs()).finish()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for RBTree<K, V> {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for elem in iter {
+            self.insert(elem.0, elem.1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+
+    /// Checks the red-black and search tree properties, returns the black height.
+    fn check_subtree<K: Ord, V>(tree: &RBTree<K, V>, i: usize, parent: usize) -> usize {
+        if i == NIL {
+            return 1;
+        }
+        let n = tree.node(i);
+        assert_eq!(n.parent, parent);
+        if n.red {
+            assert!(
+                !tree.is_red(n.left) && !tree.is_red(n.right),
+                "red node with a red child"
+            );
+        }
+        if n.left != NIL {
+            assert!(tree.node(n.left).key < n.key);
+        }
+        if n.right != NIL {
+            assert!(tree.node(n.right).key > n.key);
+        }
+        let left_height = check_subtree(tree, n.left, i);
+        let right_height = check_subtree(tree, n.right, i);
+        assert_eq!(left_height, right_height, "unbalanced black height");
+        left_height + !n.red as usize
+    }
+
+    fn check<K: Ord, V>(tree: &RBTree<K, V>) {
+        assert!(!tree.is_red(tree.root));
+        check_subtree(tree, tree.root, NIL);
+        assert_eq!(tree.pairs().count(), tree.len());
+        assert!(tree.keys().zip(tree.keys().skip(1)).all(|(a, b)| a < b));
+    }
+
+    /// A permutation of `0..n` from a linear congruential generator.
+    fn shuffled(n: usize, seed: usize) -> Vec<usize> {
+        let mut keys: Vec<usize> = (0..n).collect();
+        let mut state = seed;
+        for i in (1..n).rev() {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            keys.swap(i, (state >> 33) % (i + 1));
+        }
+        keys
+    }
+
+    #[test]
+    fn insert_rebalance() {
+        for keys in [
+            (0..256).collect(),
+            (0..256).rev().collect(),
+            shuffled(256, 1),
+        ] {
+            let mut tree = RBTree::new();
+            for (n, &k) in keys.iter().enumerate() {
+                assert_eq!(tree.insert(k, k * 2), None);
+                assert_eq!(tree.len(), n + 1);
+                check(&tree);
+            }
+            assert!(keys.iter().all(|k| tree.get(k) == Some(&(k * 2))));
+            assert!(!tree.contains_key(&256));
+        }
+    }
+
+    #[test]
+    fn insert_existing_key() {
+        let mut tree = RBTree::new();
+        tree.extend([(1, "a"), (2, "b"), (3, "c")]);
+        assert_eq!(tree.insert(2, "B"), Some("b"));
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get(&2), Some(&"B"));
+        check(&tree);
+    }
+
+    #[test]
+    fn remove_rebalance() {
+        let mut tree = RBTree::new();
+        let mut map = BTreeMap::new();
+        for k in shuffled(512, 2) {
+            tree.insert(k, k);
+            map.insert(k, k);
+        }
+        for (n, k) in shuffled(512, 3).into_iter().enumerate() {
+            if n % 4 == 0 {
+                // also missing keys
+                assert_eq!(tree.remove(&(k + 512)), None);
+            }
+            assert_eq!(tree.remove_entry(&k), map.remove_entry(&k));
+            assert_eq!(tree.remove(&k), None);
+            check(&tree);
+            assert!(tree.pairs().eq(map.iter()));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.first_entry(), None);
+    }
+
+    #[test]
+    fn in_order_iteration() {
+        let mut tree = RBTree::new();
+        for k in shuffled(100, 4) {
+            tree.insert(k, 100 - k);
+        }
+        assert!(tree.keys().copied().eq(0..100));
+        assert!(tree.values().copied().eq((1..=100).rev()));
+        assert!(tree.pairs().map(|(&k, &v)| k + v).all(|sum| sum == 100));
+        assert_eq!(tree.last_entry(), Some((&99, &1)));
+    }
+
+    #[test]
+    fn min_lookup() {
+        let mut tree = RBTree::new();
+        let mut map = BTreeMap::new();
+        // duplicate-free `(vruntime, seq)` keys, as the CFS queue uses
+        for (seq, k) in shuffled(300, 5).into_iter().enumerate() {
+            tree.insert((k / 3, seq), k);
+            map.insert((k / 3, seq), k);
+            assert_eq!(tree.first_entry(), map.iter().next());
+        }
+        while let Some(first) = tree.pop_first() {
+            assert_eq!(Some(first), map.pop_first());
+            assert_eq!(tree.first_entry(), map.iter().next());
+            check(&tree);
+        }
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn reuse_freed_slots() {
+        let mut tree = RBTree::new();
+        tree.extend((0..64).map(|k| (k, ())));
+        for k in 0..32 {
+            tree.remove(&k);
+        }
+        tree.extend((64..96).map(|k| (k, ())));
+        assert_eq!(tree.nodes.len(), 64);
+        check(&tree);
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.pairs().next(), None);
+    }
+}
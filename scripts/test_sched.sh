@@ -0,0 +1,51 @@
+#!/bin/bash
+# Boots the kernel with every scheduler policy, runs `usertests` as the init
+# program and checks that every test exited with code 0.
+#
+# Usage: scripts/test_sched.sh [policy...]    (run from the repository root or `kernel/`)
+
+ARCH=${ARCH:-x86_64}
+TIMEOUT=${TIMEOUT:-300}
+POLICIES=${@:-cfs rr fifo}
+EXPECTED=("forktree passed!" "yield passed!" "usertests passed!")
+
+cd "$(dirname "$0")/../kernel" || exit 1
+mkdir -p target/test-sched
+
+failed=0
+for sched in $POLICIES; do
+    log=target/test-sched/$ARCH-$sched.log
+    echo "==> Testing scheduler '$sched' on $ARCH (log: kernel/$log)"
+    if ! make user build ARCH="$ARCH" SCHED="$sched" INIT=usertests > "$log" 2>&1; then
+        echo "    FAILED: build error"
+        failed=1
+        continue
+    fi
+    timeout "$TIMEOUT" make justrun ARCH="$ARCH" GRAPHIC=off < /dev/null >> "$log" 2>&1
+    for pattern in "${EXPECTED[@]}"; do
+        if grep -q "$pattern" "$log"; then
+            echo "    ok: $pattern"
+        else
+            echo "    FAILED: '$pattern' not found"
+            failed=1
+        fi
+    done
+    # test names are printed with their trailing NUL
+    results=$(tr -d '\000' < "$log" | grep -o "Usertests: Test '[^']*' in Process [0-9]* exited with code -\?[0-9]*")
+    failures=$(echo "$results" | grep "code -\?[1-9]")
+    if [ -z "$results" ]; then
+        echo "    FAILED: no test ran"
+        failed=1
+    elif [ -n "$failures" ]; then
+        echo "$failures" | sed "s/^Usertests: Test '\([^']*\)'.* code \(.*\)$/    FAILED: '\1' exited with code \2/"
+        failed=1
+    else
+        echo "    ok: $(echo "$results" | grep -c .) tests exited with code 0"
+    fi
+done
+
+if [ $failed -ne 0 ]; then
+    echo "Scheduler tests failed!"
+    exit 1
+fi
+echo "All scheduler tests passed!"
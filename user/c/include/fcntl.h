@@ -0,0 +1,16 @@
+#ifndef __FCNTL_H__
+#define __FCNTL_H__
+
+#include <stdint.h>
+
+#define O_RDONLY 0
+#define O_WRONLY 1
+#define O_RDWR     2
+#define O_EXCL     0x80
+#define O_CREAT    0x200
+#define O_TRUNC    0x400
+#define O_NONBLOCK 0x800
+
+int open(const char *path, int flags, ...);
+
+#endif // __FCNTL_H__
@@ -0,0 +1,24 @@
+#ifndef __MQUEUE_H__
+#define __MQUEUE_H__
+
+#include <stdint.h>
+
+typedef int mqd_t;
+
+struct mq_attr {
+    long mq_flags;
+    long mq_maxmsg;
+    long mq_msgsize;
+    long mq_curmsgs;
+    long __reserved[4];
+};
+
+/* With `O_CREAT`, `mode_t mode` and `struct mq_attr *attr` follow `oflag`. */
+mqd_t mq_open(const char *name, int oflag, ...);
+int mq_close(mqd_t mqd);
+int mq_unlink(const char *name);
+int mq_send(mqd_t mqd, const char *msg, size_t len, unsigned prio);
+ssize_t mq_receive(mqd_t mqd, char *msg, size_t len, unsigned *prio);
+int mq_getattr(mqd_t mqd, struct mq_attr *attr);
+
+#endif // __MQUEUE_H__
@@ -3,6 +3,31 @@
 
 typedef unsigned long pthread_t;
 
+typedef struct {
+    int __state;
+} pthread_mutex_t;
+
+typedef struct {
+    int __seq;
+} pthread_cond_t;
+
+#define PTHREAD_MUTEX_INITIALIZER {0}
+#define PTHREAD_COND_INITIALIZER  {0}
+
 int pthread_create(pthread_t *res, const void *attrp, void *(*entry)(void *), void *arg);
+int pthread_join(pthread_t thread, void **retval);
+_Noreturn void pthread_exit(void *retval);
+
+int pthread_mutex_init(pthread_mutex_t *mutex, const void *attrp);
+int pthread_mutex_destroy(pthread_mutex_t *mutex);
+int pthread_mutex_lock(pthread_mutex_t *mutex);
+int pthread_mutex_trylock(pthread_mutex_t *mutex);
+int pthread_mutex_unlock(pthread_mutex_t *mutex);
+
+int pthread_cond_init(pthread_cond_t *cond, const void *attrp);
+int pthread_cond_destroy(pthread_cond_t *cond);
+int pthread_cond_wait(pthread_cond_t *cond, pthread_mutex_t *mutex);
+int pthread_cond_signal(pthread_cond_t *cond);
+int pthread_cond_broadcast(pthread_cond_t *cond);
 
 #endif // __PTHREAD_H__
@@ -35,7 +35,11 @@ typedef uint32_t uintptr_t;
 typedef uintptr_t size_t;
 typedef intptr_t ssize_t;
 
+#define SIZE_MAX ((size_t)-1)
+
 typedef int pid_t;
+typedef long off_t;
+typedef unsigned mode_t;
 
 #define NULL ((void *)0)
 
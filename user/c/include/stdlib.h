@@ -1,10 +1,17 @@
 #ifndef __STDLIB_H__
 #define __STDLIB_H__
 
+#include <stdint.h>
+
 int rand(void);
 void srand(unsigned);
 
 _Noreturn void exit(int);
 _Noreturn void abort(void);
 
+void *malloc(size_t size);
+void *calloc(size_t nmemb, size_t size);
+void *realloc(void *ptr, size_t size);
+void free(void *ptr);
+
 #endif //__STDLIB_H__
@@ -8,6 +8,7 @@ int isdigit(int c);
 int atoi(const char *s);
 
 void *memset(void *dest, int c, size_t n);
+void *memcpy(void *restrict dest, const void *restrict src, size_t n);
 void *memchr(const void *src, int c, size_t n);
 
 size_t strlen(const char *s);
@@ -0,0 +1,24 @@
+#ifndef __SYS_RESOURCE_H__
+#define __SYS_RESOURCE_H__
+
+#include <stdint.h>
+#include <time.h>
+
+typedef unsigned id_t;
+
+#define PRIO_PROCESS 0
+
+#define RUSAGE_SELF     0
+#define RUSAGE_CHILDREN (-1)
+
+struct rusage {
+    struct timeval ru_utime;
+    struct timeval ru_stime;
+    long __reserved[14];
+};
+
+int getpriority(int which, id_t who);
+int setpriority(int which, id_t who, int prio);
+int getrusage(int who, struct rusage *usage);
+
+#endif // __SYS_RESOURCE_H__
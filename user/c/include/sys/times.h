@@ -0,0 +1,16 @@
+#ifndef __SYS_TIMES_H__
+#define __SYS_TIMES_H__
+
+typedef long clock_t;
+
+/* in clock ticks, 100 per second */
+struct tms {
+    clock_t tms_utime;
+    clock_t tms_stime;
+    clock_t tms_cutime;
+    clock_t tms_cstime;
+};
+
+clock_t times(struct tms *buf);
+
+#endif // __SYS_TIMES_H__
@@ -5,12 +5,22 @@
 
 ssize_t read(int, void *, size_t);
 ssize_t write(int, const void *, size_t);
+int close(int fd);
+int pipe(int fds[2]);
+int dup(int fd);
+int dup2(int old_fd, int new_fd);
+int ftruncate(int fd, off_t length);
+int unlink(const char *path);
+
+int brk(void *addr);
+void *sbrk(intptr_t increment);
 
 pid_t getpid(void);
+pid_t gettid(void);
 int sched_yield(void);
 
 pid_t fork(void);
-int execve(const char *path);
+int execve(const char *path, char *const argv[], char *const envp[]);
 pid_t wait(int *exitcode);
 pid_t waitpid(pid_t pid, int *exitcode, int options);
 
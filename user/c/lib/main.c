@@ -1,9 +1,13 @@
 #include <stdlib.h>
 
-extern int main();
+extern int main(int argc, char **argv, char **envp);
 
-int __start_main()
+// `p` points to `argc` on the initial user stack, followed by `argv` and `envp`.
+int __start_main(long *p)
 {
-    exit(main());
+    int argc = p[0];
+    char **argv = (char **)(p + 1);
+    char **envp = argv + argc + 1;
+    exit(main(argc, argv, envp));
     return 0;
 }
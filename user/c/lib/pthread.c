@@ -1,24 +1,221 @@
 #include <pthread.h>
 #include <stdint.h>
 #include <stdio.h>
+#include <sys/mman.h>
 
-#define __MAX_THREADS       16
+#include "syscall.h"
+
+#define __PAGE_SIZE         4096
 #define __THREAD_STACK_SIZE (4096 * 4)
 
-static char THREAD_STACKS[__MAX_THREADS][__THREAD_STACK_SIZE] __attribute__((__aligned__(16)));
-static int THREAD_COUNT = 0;
+#define CLONE_CHILD_CLEARTID 0x200000
+#define CLONE_CHILD_SETTID   0x1000000
+
+#define FUTEX_WAIT         0
+#define FUTEX_WAKE         1
+#define FUTEX_PRIVATE_FLAG 128
+
+struct thread {
+    // set by the kernel before the thread runs, and cleared with a futex wake-up when it exits
+    int ctid;
+    pthread_t tid;
+    void *(*entry)(void *);
+    void *arg;
+    void *retval;
+    // set by the first `pthread_join`, later ones fail
+    int joining;
+    // the stack mapping, whose lowest page is the guard page
+    char *stack;
+    struct thread *next;
+};
+
+// threads not joined yet
+static struct thread *THREADS = NULL;
+static pthread_mutex_t THREADS_LOCK = PTHREAD_MUTEX_INITIALIZER;
+
+extern int __clone(void *(*entry)(void *), void *arg, void *stack, int flags, int *ctid);
 
-extern int __clone(void *(*entry)(void *), void *stack, void *arg);
+static int __futex_wait(int *addr, int val)
+{
+    return syscall(SYS_futex, addr, FUTEX_WAIT | FUTEX_PRIVATE_FLAG, val, NULL);
+}
+
+static int __futex_wake(int *addr, int count)
+{
+    return syscall(SYS_futex, addr, FUTEX_WAKE | FUTEX_PRIVATE_FLAG, count);
+}
+
+static void *__thread_start(void *arg)
+{
+    struct thread *thread = arg;
+    thread->retval = thread->entry(thread->arg);
+    return thread->retval;
+}
 
 int pthread_create(pthread_t *restrict res, const void *restrict attrp, void *(*entry)(void *),
                    void *restrict arg)
 {
-    int thread_id = THREAD_COUNT++;
-    void *newsp = THREAD_STACKS[thread_id] + __THREAD_STACK_SIZE;
-    int tid = __clone(entry, arg, newsp);
+    size_t size = __PAGE_SIZE + __THREAD_STACK_SIZE;
+    char *stack =
+        mmap(NULL, size, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_STACK, -1, 0);
+    if (stack == MAP_FAILED) {
+        return -1;
+    }
+    // overflowing the stack faults in the guard page
+    if (mprotect(stack, __PAGE_SIZE, PROT_NONE) != 0) {
+        munmap(stack, size);
+        return -1;
+    }
+    // the thread is kept at the top of its stack
+    struct thread *thread = (struct thread *)(stack + size) - 1;
+    thread->entry = entry;
+    thread->arg = arg;
+    thread->joining = 0;
+    thread->stack = stack;
+    void *newsp = (void *)((uintptr_t)thread & ~(uintptr_t)15);
+    int flags = CLONE_CHILD_SETTID | CLONE_CHILD_CLEARTID;
+    // locked until the thread is added, so that `pthread_exit` finds it
+    pthread_mutex_lock(&THREADS_LOCK);
+    int tid = __clone(__thread_start, thread, newsp, flags, &thread->ctid);
     if (tid < 0) {
+        pthread_mutex_unlock(&THREADS_LOCK);
+        munmap(stack, size);
         return tid;
     }
+    thread->tid = tid;
+    thread->next = THREADS;
+    THREADS = thread;
+    pthread_mutex_unlock(&THREADS_LOCK);
     *res = tid;
     return 0;
 }
+
+_Noreturn void pthread_exit(void *retval)
+{
+    pthread_t tid = syscall(SYS_gettid);
+    pthread_mutex_lock(&THREADS_LOCK);
+    for (struct thread *t = THREADS; t; t = t->next) {
+        if (t->tid == tid) {
+            t->retval = retval;
+            break;
+        }
+    }
+    pthread_mutex_unlock(&THREADS_LOCK);
+    for (;;) syscall(SYS_exit, 0);
+}
+
+int pthread_join(pthread_t thread, void **retval)
+{
+    pthread_mutex_lock(&THREADS_LOCK);
+    struct thread *t = THREADS;
+    while (t && t->tid != thread) {
+        t = t->next;
+    }
+    // the thread is freed by the joiner, so only one may wait for it
+    if (!t || t->joining) {
+        pthread_mutex_unlock(&THREADS_LOCK);
+        return -1;
+    }
+    t->joining = 1;
+    pthread_mutex_unlock(&THREADS_LOCK);
+    int ctid;
+    while ((ctid = __atomic_load_n(&t->ctid, __ATOMIC_ACQUIRE)) != 0) {
+        __futex_wait(&t->ctid, ctid);
+    }
+    pthread_mutex_lock(&THREADS_LOCK);
+    struct thread **prev = &THREADS;
+    while (*prev != t) {
+        prev = &(*prev)->next;
+    }
+    *prev = t->next;
+    pthread_mutex_unlock(&THREADS_LOCK);
+    if (retval) {
+        *retval = t->retval;
+    }
+    // the thread is freed with its stack
+    munmap(t->stack, __PAGE_SIZE + __THREAD_STACK_SIZE);
+    return 0;
+}
+
+// mutex states: 0 unlocked, 1 locked, 2 locked and there may be waiters
+
+int pthread_mutex_init(pthread_mutex_t *mutex, const void *attrp)
+{
+    mutex->__state = 0;
+    return 0;
+}
+
+int pthread_mutex_destroy(pthread_mutex_t *mutex)
+{
+    return 0;
+}
+
+static void __mutex_lock_contended(pthread_mutex_t *mutex)
+{
+    while (__atomic_exchange_n(&mutex->__state, 2, __ATOMIC_ACQUIRE) != 0) {
+        __futex_wait(&mutex->__state, 2);
+    }
+}
+
+int pthread_mutex_lock(pthread_mutex_t *mutex)
+{
+    if (pthread_mutex_trylock(mutex) != 0) {
+        __mutex_lock_contended(mutex);
+    }
+    return 0;
+}
+
+int pthread_mutex_trylock(pthread_mutex_t *mutex)
+{
+    int unlocked = 0;
+    if (__atomic_compare_exchange_n(&mutex->__state, &unlocked, 1, 0, __ATOMIC_ACQUIRE,
+                                    __ATOMIC_RELAXED)) {
+        return 0;
+    }
+    return -1;
+}
+
+int pthread_mutex_unlock(pthread_mutex_t *mutex)
+{
+    if (__atomic_exchange_n(&mutex->__state, 0, __ATOMIC_RELEASE) == 2) {
+        __futex_wake(&mutex->__state, 1);
+    }
+    return 0;
+}
+
+// a condition variable holds the number of notifications, waiters block until it changes
+
+int pthread_cond_init(pthread_cond_t *cond, const void *attrp)
+{
+    cond->__seq = 0;
+    return 0;
+}
+
+int pthread_cond_destroy(pthread_cond_t *cond)
+{
+    return 0;
+}
+
+int pthread_cond_wait(pthread_cond_t *cond, pthread_mutex_t *mutex)
+{
+    int seq = __atomic_load_n(&cond->__seq, __ATOMIC_RELAXED);
+    pthread_mutex_unlock(mutex);
+    __futex_wait(&cond->__seq, seq);
+    // other notified waiters may be blocked on the mutex
+    __mutex_lock_contended(mutex);
+    return 0;
+}
+
+int pthread_cond_signal(pthread_cond_t *cond)
+{
+    __atomic_fetch_add(&cond->__seq, 1, __ATOMIC_RELAXED);
+    __futex_wake(&cond->__seq, 1);
+    return 0;
+}
+
+int pthread_cond_broadcast(pthread_cond_t *cond)
+{
+    __atomic_fetch_add(&cond->__seq, 1, __ATOMIC_RELAXED);
+    __futex_wake(&cond->__seq, __INT_MAX__);
+    return 0;
+}
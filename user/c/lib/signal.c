@@ -0,0 +1,76 @@
+#include <signal.h>
+
+#include "syscall.h"
+
+void __restore_rt(void);
+
+int kill(pid_t pid, int sig)
+{
+    return syscall(SYS_kill, pid, sig);
+}
+
+int sigaction(int sig, const struct sigaction *act, struct sigaction *oldact)
+{
+    struct sigaction ksa;
+    if (act) {
+        ksa = *act;
+        ksa.sa_flags |= SA_RESTORER;
+        ksa.sa_restorer = __restore_rt;
+        act = &ksa;
+    }
+    return syscall(SYS_rt_sigaction, sig, act, oldact);
+}
+
+sighandler_t signal(int sig, sighandler_t handler)
+{
+    struct sigaction sa = {.sa_handler = handler, .sa_flags = 0, .sa_mask = 0};
+    struct sigaction old;
+    if (sigaction(sig, &sa, &old) < 0)
+        return SIG_ERR;
+    return old.sa_handler;
+}
+
+int sigprocmask(int how, const sigset_t *set, sigset_t *oldset)
+{
+    return syscall(SYS_rt_sigprocmask, how, set, oldset);
+}
+
+static int valid_signal(int sig)
+{
+    return sig >= 1 && sig < NSIG;
+}
+
+int sigemptyset(sigset_t *set)
+{
+    *set = 0;
+    return 0;
+}
+
+int sigfillset(sigset_t *set)
+{
+    *set = ~(sigset_t)0;
+    return 0;
+}
+
+int sigaddset(sigset_t *set, int sig)
+{
+    if (!valid_signal(sig))
+        return -1;
+    *set |= 1ULL << (sig - 1);
+    return 0;
+}
+
+int sigdelset(sigset_t *set, int sig)
+{
+    if (!valid_signal(sig))
+        return -1;
+    *set &= ~(1ULL << (sig - 1));
+    return 0;
+}
+
+int sigismember(const sigset_t *set, int sig)
+{
+    if (!valid_signal(sig))
+        return -1;
+    return (*set >> (sig - 1)) & 1;
+}
@@ -1,5 +1,6 @@
 #include <stdint.h>
 #include <stdlib.h>
+#include <string.h>
 #include <unistd.h>
 
 static uint64_t seed;
@@ -19,3 +20,111 @@ _Noreturn void abort(void)
 {
     exit(127);
 }
+
+/*
+ * A first-fit allocator with a circular free list sorted by address. The heap
+ * grows by moving the program break when no free block is large enough.
+ */
+
+struct block {
+    struct block *next;
+    size_t size; /* in units of `struct block`, including this header */
+};
+
+/* grow the heap by at least 64K */
+#define MIN_GROW_UNITS (0x10000 / sizeof(struct block))
+
+static struct block base;
+static struct block *freep;
+
+static struct block *morecore(size_t units)
+{
+    if (units < MIN_GROW_UNITS)
+        units = MIN_GROW_UNITS;
+    void *p = sbrk(units * sizeof(struct block));
+    if (p == (void *)-1)
+        return NULL;
+    struct block *b = p;
+    b->size = units;
+    free(b + 1);
+    return freep;
+}
+
+void *malloc(size_t size)
+{
+    if (size == 0 || size > SIZE_MAX / 2)
+        return NULL;
+    size_t units = (size + sizeof(struct block) - 1) / sizeof(struct block) + 1;
+    if (!freep) {
+        base.next = freep = &base;
+        base.size = 0;
+    }
+    struct block *prev = freep;
+    for (struct block *p = prev->next;; prev = p, p = p->next) {
+        if (p->size >= units) {
+            if (p->size == units) {
+                prev->next = p->next;
+            } else {
+                p->size -= units;
+                p += p->size;
+                p->size = units;
+            }
+            freep = prev;
+            return p + 1;
+        }
+        if (p == freep && !(p = morecore(units)))
+            return NULL;
+    }
+}
+
+void free(void *ptr)
+{
+    if (!ptr)
+        return;
+    struct block *b = (struct block *)ptr - 1, *p;
+    for (p = freep; !(b > p && b < p->next); p = p->next)
+        if (p >= p->next && (b > p || b < p->next))
+            break; /* at either end of the heap */
+    if (b + b->size == p->next) {
+        b->size += p->next->size;
+        b->next = p->next->next;
+    } else {
+        b->next = p->next;
+    }
+    if (p + p->size == b) {
+        p->size += b->size;
+        p->next = b->next;
+    } else {
+        p->next = b;
+    }
+    freep = p;
+}
+
+void *calloc(size_t nmemb, size_t size)
+{
+    if (size && nmemb > SIZE_MAX / size)
+        return NULL;
+    void *p = malloc(nmemb * size);
+    if (p)
+        memset(p, 0, nmemb * size);
+    return p;
+}
+
+void *realloc(void *ptr, size_t size)
+{
+    if (!ptr)
+        return malloc(size);
+    if (size == 0) {
+        free(ptr);
+        return NULL;
+    }
+    size_t old_size = (((struct block *)ptr - 1)->size - 1) * sizeof(struct block);
+    if (size <= old_size)
+        return ptr;
+    void *p = malloc(size);
+    if (p) {
+        memcpy(p, ptr, old_size);
+        free(ptr);
+    }
+    return p;
+}
@@ -89,6 +89,14 @@ void *memset(void *dest, int c, size_t n)
     return dest;
 }
 
+void *memcpy(void *restrict dest, const void *restrict src, size_t n)
+{
+    unsigned char *d = dest;
+    const unsigned char *s = src;
+    for (; n; n--) *d++ = *s++;
+    return dest;
+}
+
 int strcmp(const char *l, const char *r)
 {
     for (; *l == *r && *l; l++, r++);
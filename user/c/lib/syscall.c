@@ -1,4 +1,11 @@
+#include <fcntl.h>
+#include <mqueue.h>
+#include <sched.h>
+#include <stdarg.h>
 #include <stdint.h>
+#include <string.h>
+#include <sys/mman.h>
+#include <sys/resource.h>
 #include <unistd.h>
 
 #include "syscall.h"
@@ -13,11 +20,158 @@ ssize_t write(int fd, const void *buf, size_t count)
     return syscall(SYS_write, fd, buf, count);
 }
 
+int open(const char *path, int flags, ...)
+{
+    return syscall(SYS_open, path, flags);
+}
+
+int close(int fd)
+{
+    return syscall(SYS_close, fd);
+}
+
+int pipe(int fds[2])
+{
+    return syscall(SYS_pipe, fds);
+}
+
+int dup(int fd)
+{
+    return syscall(SYS_dup, fd);
+}
+
+int dup2(int old_fd, int new_fd)
+{
+    return syscall(SYS_dup2, old_fd, new_fd);
+}
+
+int ftruncate(int fd, off_t length)
+{
+    return syscall(SYS_ftruncate, fd, length);
+}
+
+int unlink(const char *path)
+{
+    return syscall(SYS_unlink, path);
+}
+
+void *mmap(void *addr, size_t len, int prot, int flags, int fd, off_t offset)
+{
+    return (void *)syscall(SYS_mmap, addr, len, prot, flags, fd, offset);
+}
+
+int munmap(void *addr, size_t len)
+{
+    return syscall(SYS_munmap, addr, len);
+}
+
+int mprotect(void *addr, size_t len, int prot)
+{
+    return syscall(SYS_mprotect, addr, len, prot);
+}
+
+int msync(void *addr, size_t len, int flags)
+{
+    return syscall(SYS_msync, addr, len, flags);
+}
+
+#define SHM_DIR      "/dev/shm/"
+#define SHM_PATH_MAX 256
+
+/* Shared memory objects are named files in `SHM_DIR`. */
+static int shm_path(char *path, const char *name)
+{
+    size_t dir_len = strlen(SHM_DIR), name_len = strlen(name);
+    if (dir_len + name_len >= SHM_PATH_MAX)
+        return -1;
+    memcpy(path, SHM_DIR, dir_len);
+    memcpy(path + dir_len, name, name_len + 1);
+    return 0;
+}
+
+int shm_open(const char *name, int oflag, mode_t mode)
+{
+    char path[SHM_PATH_MAX];
+    if (shm_path(path, name) < 0)
+        return -1;
+    return open(path, oflag, mode);
+}
+
+int shm_unlink(const char *name)
+{
+    char path[SHM_PATH_MAX];
+    if (shm_path(path, name) < 0)
+        return -1;
+    return unlink(path);
+}
+
+int brk(void *addr)
+{
+    return syscall(SYS_brk, addr) == (long)addr ? 0 : -1;
+}
+
+void *sbrk(intptr_t increment)
+{
+    long old = syscall(SYS_brk, 0);
+    if (increment && brk((void *)(old + increment)) < 0)
+        return (void *)-1;
+    return (void *)old;
+}
+
+/* The kernel takes names without the leading `/`. */
+static const char *mq_name(const char *name)
+{
+    return name[0] == '/' ? name + 1 : name;
+}
+
+mqd_t mq_open(const char *name, int oflag, ...)
+{
+    struct mq_attr *attr = NULL;
+    if (oflag & O_CREAT) {
+        va_list ap;
+        va_start(ap, oflag);
+        va_arg(ap, mode_t);
+        attr = va_arg(ap, struct mq_attr *);
+        va_end(ap);
+    }
+    return syscall(SYS_mq_open, mq_name(name), oflag, 0, attr);
+}
+
+int mq_close(mqd_t mqd)
+{
+    return close(mqd);
+}
+
+int mq_unlink(const char *name)
+{
+    return syscall(SYS_mq_unlink, mq_name(name));
+}
+
+int mq_send(mqd_t mqd, const char *msg, size_t len, unsigned prio)
+{
+    return syscall(SYS_mq_timedsend, mqd, msg, len, prio, NULL);
+}
+
+ssize_t mq_receive(mqd_t mqd, char *msg, size_t len, unsigned *prio)
+{
+    return syscall(SYS_mq_timedreceive, mqd, msg, len, prio, NULL);
+}
+
+int mq_getattr(mqd_t mqd, struct mq_attr *attr)
+{
+    return syscall(SYS_mq_getsetattr, mqd, NULL, attr);
+}
+
 pid_t getpid(void)
 {
     return syscall(SYS_getpid);
 }
 
+pid_t gettid(void)
+{
+    return syscall(SYS_gettid);
+}
+
 int sched_yield(void)
 {
     return syscall(SYS_yield);
@@ -25,7 +179,7 @@ int sched_yield(void)
 
 _Noreturn void exit(int code)
 {
-    for (;;) syscall(SYS_exit, code);
+    for (;;) syscall(SYS_exit_group, code);
 }
 
 pid_t fork(void)
@@ -33,9 +187,9 @@ pid_t fork(void)
     return syscall(SYS_fork);
 }
 
-int execve(const char *path)
+int execve(const char *path, char *const argv[], char *const envp[])
 {
-    return syscall(SYS_exec, path);
+    return syscall(SYS_exec, path, argv, envp);
 }
 
 pid_t waitpid(pid_t pid, int *exit_code, int options)
@@ -47,3 +201,51 @@ pid_t wait(int *exit_code)
 {
     return waitpid(-1, exit_code, 0);
 }
+
+int getpriority(int which, id_t who)
+{
+    int ret = syscall(SYS_getpriority, which, who);
+    return ret < 0 ? ret : 20 - ret;
+}
+
+int setpriority(int which, id_t who, int prio)
+{
+    return syscall(SYS_setpriority, which, who, prio);
+}
+
+int getrusage(int who, struct rusage *usage)
+{
+    return syscall(SYS_getrusage, who, usage);
+}
+
+int sched_setscheduler(pid_t pid, int policy, const struct sched_param *param)
+{
+    return syscall(SYS_sched_setscheduler, pid, policy, param);
+}
+
+int sched_getscheduler(pid_t pid)
+{
+    return syscall(SYS_sched_getscheduler, pid);
+}
+
+int sched_setparam(pid_t pid, const struct sched_param *param)
+{
+    return syscall(SYS_sched_setparam, pid, param);
+}
+
+int sched_getparam(pid_t pid, struct sched_param *param)
+{
+    return syscall(SYS_sched_getparam, pid, param);
+}
+
+int sched_setattr(pid_t pid, const struct sched_attr *attr, unsigned int flags)
+{
+    return syscall(SYS_sched_setattr, pid, attr, flags);
+}
+
+int sched_getattr(pid_t pid, struct sched_attr *attr, unsigned int size, unsigned int flags)
+{
+    if (flags != 0)
+        return -1;
+    return syscall(SYS_sched_getattr, pid, attr, size);
+}
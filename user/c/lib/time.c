@@ -1,3 +1,4 @@
+#include <sys/times.h>
 #include <time.h>
 
 #include "syscall.h"
@@ -43,3 +44,8 @@ int gettimeofday(struct timeval *restrict tv, void *restrict tz)
     tv->tv_usec = (int)ts.tv_nsec / 1000;
     return 0;
 }
+
+clock_t times(struct tms *buf)
+{
+    return syscall(SYS_times, buf);
+}
@@ -16,6 +16,7 @@ musl-gcc cyclictest.c -lpthread -DUSE_MUSL
 
 #include <assert.h>
 #include <pthread.h>
+#include <sched.h>
 #include <stdint.h>
 #include <stdio.h>
 #include <stdlib.h>
@@ -32,14 +33,15 @@ musl-gcc cyclictest.c -lpthread -DUSE_MUSL
 #define MAX_CPUS            12
 #define DEFAULT_INTERVAL    1000 // in usecs
 #define DEFAULT_DISTANCE    500
-// #define DEFAULT_PRIORITY 0
-// #define DEFAULT_POLICY SCHED_OTHER // SCHED_FIFO
+#define DEFAULT_PRIORITY    80
+#define DEFAULT_POLICY      SCHED_FIFO
 #define USEC_PER_SEC        1000000
 #define NSEC_PER_SEC        1000000000
 #define DEFAULT_CLOCK       CLOCK_MONOTONIC
 #define DEFAULT_TIMER_MODE  TIMER_ABSTIME
 #define MAX_CYCLES          30000
 #define PRINT_FREQ          500 // 500ms
+#define BACKGROUND_LOAD     "matrix"
 
 struct thread_param {
     int id;
@@ -60,7 +62,7 @@ struct thread_stat {
 };
 
 static int interval = DEFAULT_INTERVAL;
-// static int priority = DEFAULT_PRIORITY;
+static int priority = DEFAULT_PRIORITY;
 static struct thread_param thrpar[NUM_THREADS];
 static struct thread_stat thrstat[NUM_THREADS];
 static int shutdown = 0;
@@ -99,8 +101,11 @@ static void *timerthread(void* param)
     struct thread_param* par = param;
     struct thread_stat* stat = &thrstat[par->id];
     struct timespec now, next, interval;
+    struct sched_param schedp = {.sched_priority = par->prio};
 
     stat->tid = getpid();
+    err = sched_setscheduler(0, par->policy, &schedp);
+    assert(!err && "sched_setscheduler() failed");
     interval.tv_sec = par->interval / USEC_PER_SEC;
     interval.tv_nsec = (par->interval % USEC_PER_SEC) * 1000;
 
@@ -170,13 +175,22 @@ int main()
     // err = write(fd, &latency_target_value, 4);
     // assert(err == 4);
 
+    // keep the CPU busy with normal tasks while measuring
+    pid_t load = fork();
+    if (load == 0) {
+        char* argv[] = {BACKGROUND_LOAD, NULL};
+        char* envp[] = {NULL};
+        execve(BACKGROUND_LOAD, argv, envp);
+        exit(1);
+    }
+
     for (int i = 0; i < NUM_THREADS; i++) {
         struct thread_param* par = &thrpar[i];
         struct thread_stat* stat = &thrstat[i];
         par->id = i;
         par->cpu = i % MAX_CPUS;
-        // par->prio = priority;
-        // par->policy = DEFAULT_POLICY;
+        par->prio = priority;
+        par->policy = DEFAULT_POLICY;
         par->interval = interval;
         interval += DEFAULT_DISTANCE;
 
@@ -210,4 +224,7 @@ int main()
     // for (int i = 0; i < NUM_THREADS; i++) {
     //  pthread_join(thrpar[i].thread, NULL);
     // }
+
+    if (load > 0)
+        waitpid(load, NULL, 0);
 }
@@ -0,0 +1,44 @@
+#include <assert.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#define N 64
+
+int main()
+{
+    char *blocks[N];
+    void *start = sbrk(0);
+
+    /* far more than the old 32K heap */
+    for (int i = 0; i < N; i++) {
+        blocks[i] = malloc(4096 * (i % 8 + 1));
+        assert(blocks[i]);
+        memset(blocks[i], i, 4096 * (i % 8 + 1));
+    }
+    assert((char *)sbrk(0) - (char *)start >= 4096 * 4 * N);
+    for (int i = 0; i < N; i++) {
+        for (int j = 0; j < 4096 * (i % 8 + 1); j++) assert(blocks[i][j] == (char)i);
+    }
+
+    /* freed blocks are reused */
+    for (int i = 0; i < N; i += 2) free(blocks[i]);
+    void *brk = sbrk(0);
+    for (int i = 0; i < N; i += 2) assert((blocks[i] = malloc(4096)));
+    assert(sbrk(0) == brk);
+
+    int *zeros = calloc(1024, sizeof(int));
+    assert(zeros);
+    for (int i = 0; i < 1024; i++) assert(zeros[i] == 0);
+
+    blocks[1] = realloc(blocks[1], 4096 * 16);
+    assert(blocks[1]);
+    for (int j = 0; j < 4096 * 2; j++) assert(blocks[1][j] == 1);
+
+    for (int i = 0; i < N; i++) free(blocks[i]);
+    free(zeros);
+    puts("malloc_test passed!");
+    return 0;
+}
@@ -1,6 +1,6 @@
 use core::arch::asm;
 
-use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT};
+use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT, SYSCALL_RT_SIGRETURN};
 
 pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     let ret;
@@ -16,21 +16,64 @@ pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+pub fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let ret;
+    unsafe {
+        asm!(
+            "svc #0",
+            inlateout("x0") args[0] => ret,
+            in("x1") args[1],
+            in("x2") args[2],
+            in("x3") args[3],
+            in("x4") args[4],
+            in("x5") args[5],
+            in("x8") id,
+        );
+    }
+    ret
+}
+
+/// The entry of user programs, passes the initial stack pointer to `start_main()`.
+#[naked]
+#[no_mangle]
+#[link_section = ".text.entry"]
+pub unsafe extern "C" fn _start() -> ! {
+    asm!("
+            mov x29, #0
+            mov x30, #0
+            mov x0, sp
+            and sp, x0, #-16
+            b {start_main}",
+        start_main = sym crate::start_main,
+        options(noreturn),
+    )
+}
+
 #[naked]
 #[allow(improper_ctypes_definitions)]
-pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize) -> isize {
-    // sys_clone(entry, arg, newsp)
-    //             x0,   x1,    x2
-    // syscall(SYSCALL_CLONE, newsp)
-    //                   x8,     x0
+pub extern "C" fn sys_clone(
+    _entry: fn(usize) -> i32,
+    _arg: usize,
+    _newsp: usize,
+    _flags: usize,
+    _ctid: *mut u32,
+    _tls: usize,
+) -> isize {
+    // sys_clone(entry, arg, newsp, flags, ctid, tls)
+    //             x0,   x1,    x2,    x3,   x4,  x5
+    // syscall(SYSCALL_CLONE, newsp, flags, ctid, tls)
+    //                   x8,     x0,    x1,   x2,  x3
     unsafe {
         asm!("
             // align stack and save entry,arg to the new stack
             and x2, x2, #-16
             stp x0, x1, [x2, #-16]!
 
-            // syscall(SYSCALL_CLONE, newsp)
+            // syscall(SYSCALL_CLONE, newsp, flags, ctid, tls)
             mov x0, x2
+            mov x1, x3
+            mov x2, x4
+            mov x3, x5
             mov x8, {sys_clone}
             svc #0
 
@@ -50,3 +93,21 @@ pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize
         )
     }
 }
+
+/// Signal handlers return here.
+#[naked]
+pub extern "C" fn sys_rt_sigreturn() -> ! {
+    unsafe {
+        asm!("
+            mov x8, {sys_rt_sigreturn}
+            svc #0",
+            sys_rt_sigreturn = const SYSCALL_RT_SIGRETURN,
+            options(noreturn),
+        )
+    }
+}
+
+/// Sets the thread pointer of the current thread, which is `TPIDR_EL0`.
+pub fn set_thread_pointer(tp: usize) {
+    unsafe { asm!("msr tpidr_el0, {}", in(reg) tp) };
+}
@@ -1,6 +1,6 @@
 use core::arch::asm;
 
-use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT};
+use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT, SYSCALL_RT_SIGRETURN};
 
 pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     let ret;
@@ -16,13 +16,51 @@ pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+pub fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let ret;
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") args[0] => ret,
+            in("a1") args[1],
+            in("a2") args[2],
+            in("a3") args[3],
+            in("a4") args[4],
+            in("a5") args[5],
+            in("a7") id,
+        );
+    }
+    ret
+}
+
+/// The entry of user programs, passes the initial stack pointer to `start_main()`.
+#[naked]
+#[no_mangle]
+#[link_section = ".text.entry"]
+pub unsafe extern "C" fn _start() -> ! {
+    asm!("
+            mv a0, sp
+            andi sp, sp, -16
+            tail {start_main}",
+        start_main = sym crate::start_main,
+        options(noreturn),
+    )
+}
+
 #[naked]
 #[allow(improper_ctypes_definitions)]
-pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize) -> isize {
-    // sys_clone(entry, arg, newsp)
-    //             a0,   a1,    a2
-    // syscall(SYSCALL_CLONE, newsp)
-    //                   a7,     x0
+pub extern "C" fn sys_clone(
+    _entry: fn(usize) -> i32,
+    _arg: usize,
+    _newsp: usize,
+    _flags: usize,
+    _ctid: *mut u32,
+    _tls: usize,
+) -> isize {
+    // sys_clone(entry, arg, newsp, flags, ctid, tls)
+    //             a0,   a1,    a2,    a3,   a4,  a5
+    // syscall(SYSCALL_CLONE, newsp, flags, ctid, tls)
+    //                   a7,     a0,    a1,   a2,  a3
     unsafe {
         asm!("
             // align stack and save entry,arg to the new stack
@@ -31,8 +69,11 @@ pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize
             sd      a0, 0(a2)
             sd      a1, 8(a2)
 
-            // syscall(SYSCALL_CLONE, newsp)
+            // syscall(SYSCALL_CLONE, newsp, flags, ctid, tls)
             mv      a0, a2
+            mv      a1, a3
+            mv      a2, a4
+            mv      a3, a5
             li      a7, {sys_clone}
             ecall
 
@@ -53,3 +94,21 @@ pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize
         )
     }
 }
+
+/// Signal handlers return here.
+#[naked]
+pub extern "C" fn sys_rt_sigreturn() -> ! {
+    unsafe {
+        asm!("
+            li      a7, {sys_rt_sigreturn}
+            ecall",
+            sys_rt_sigreturn = const SYSCALL_RT_SIGRETURN,
+            options(noreturn),
+        )
+    }
+}
+
+/// Sets the thread pointer of the current thread, which is `tp`.
+pub fn set_thread_pointer(tp: usize) {
+    unsafe { asm!("mv tp, {}", in(reg) tp) };
+}
@@ -1,6 +1,10 @@
 use core::arch::asm;
 
-use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT};
+use crate::syscall::{SYSCALL_CLONE, SYSCALL_EXIT, SYSCALL_RT_SIGRETURN};
+
+// only on x86_64, other architectures set the thread pointer register directly
+const SYSCALL_ARCH_PRCTL: usize = 158;
+const ARCH_SET_FS: usize = 0x1002;
 
 pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     let ret;
@@ -18,23 +22,67 @@ pub fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+pub fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let ret;
+    unsafe {
+        asm!(
+            "syscall",
+            inlateout("rax") id => ret,
+            in("rdi") args[0],
+            in("rsi") args[1],
+            in("rdx") args[2],
+            in("r10") args[3],
+            in("r8") args[4],
+            in("r9") args[5],
+            out("rcx") _,
+            out("r11") _,
+        );
+    }
+    ret
+}
+
+/// The entry of user programs, passes the initial stack pointer to `start_main()`.
+#[naked]
+#[no_mangle]
+#[link_section = ".text.entry"]
+pub unsafe extern "C" fn _start() -> ! {
+    asm!("
+            xor rbp, rbp
+            mov rdi, rsp
+            and rsp, -16
+            call {start_main}",
+        start_main = sym crate::start_main,
+        options(noreturn),
+    )
+}
+
 #[naked]
 #[allow(improper_ctypes_definitions)]
-pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize) -> isize {
-    // sys_clone(entry, arg, newsp)
-    //             rdi, rsi,   rdx
-    // syscall(SYSCALL_CLONE, newsp)
-    //                   rax,   rdi
+pub extern "C" fn sys_clone(
+    _entry: fn(usize) -> i32,
+    _arg: usize,
+    _newsp: usize,
+    _flags: usize,
+    _ctid: *mut u32,
+    _tls: usize,
+) -> isize {
+    // sys_clone(entry, arg, newsp, flags, ctid, tls)
+    //             rdi, rsi,   rdx,   rcx,   r8,  r9
+    // syscall(SYSCALL_CLONE, newsp, flags, ctid, tls)
+    //                   rax,   rdi,   rsi,  rdx, r10
     unsafe {
         asm!("
-            // push arg (rsi) to stack, set func (rdi) to r9
+            // push arg (rsi) to stack, move tls (r9) to r10, set func (rdi) to r9
             and rdx, -16
             sub rdx, 8
             mov [rdx], rsi
+            mov r10, r9
             mov r9, rdi
 
-            // syscall(SYSCALL_CLONE, newsp)
+            // syscall(SYSCALL_CLONE, newsp, flags, ctid, tls)
             mov rdi, rdx
+            mov rsi, rcx
+            mov rdx, r8
             mov rax, {sys_clone}
             syscall
 
@@ -57,3 +105,22 @@ pub extern "C" fn sys_clone(_entry: fn(usize) -> i32, _arg: usize, _newsp: usize
         )
     }
 }
+
+/// Signal handlers return here. It uses `int 0x80` but not `syscall`, as `sysret`
+/// clobbers `rcx` and `r11` restored from the signal frame.
+#[naked]
+pub extern "C" fn sys_rt_sigreturn() -> ! {
+    unsafe {
+        asm!("
+            mov rax, {sys_rt_sigreturn}
+            int 0x80",
+            sys_rt_sigreturn = const SYSCALL_RT_SIGRETURN,
+            options(noreturn),
+        )
+    }
+}
+
+/// Sets the thread pointer of the current thread, which is the FS base.
+pub fn set_thread_pointer(tp: usize) {
+    assert_eq!(syscall(SYSCALL_ARCH_PRCTL, [ARCH_SET_FS, tp, 0]), 0);
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::ptr::null;
+
+use user_lib::{execve, fork, getenv, waitpid};
+
+const ARGS: &[&str] = &["argv_test\0", "hello\0", "world\0"];
+const ENVS: &[&str] = &["FOO=bar\0", "EMPTY=\0"];
+
+#[no_mangle]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc > 1 {
+        // run by the parent below
+        assert_eq!(argc, ARGS.len());
+        for (arg, expected) in argv.iter().zip(ARGS) {
+            assert_eq!(*arg, expected.trim_end_matches('\0'));
+        }
+        assert_eq!(getenv("FOO"), Some("bar"));
+        assert_eq!(getenv("EMPTY"), Some(""));
+        assert_eq!(getenv("FO"), None);
+        return 0;
+    }
+
+    assert_eq!(argc, 1);
+    assert_eq!(argv[0], "argv_test");
+    assert_eq!(getenv("FOO"), None);
+
+    let pid = fork();
+    if pid == 0 {
+        let argv = [ARGS[0].as_ptr(), ARGS[1].as_ptr(), ARGS[2].as_ptr(), null()];
+        let envp = [ENVS[0].as_ptr(), ENVS[1].as_ptr(), null()];
+        execve(ARGS[0], &argv, &envp);
+        unreachable!();
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, 0);
+    println!("argv_test passed!");
+    0
+}
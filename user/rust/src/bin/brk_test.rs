@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{brk, exit, fork, sbrk, waitpid, SIGSEGV};
+
+const PAGE_SIZE: usize = 4096;
+
+fn faults(ptr: *mut u8) -> bool {
+    let pid = fork();
+    if pid == 0 {
+        unsafe { ptr.write_volatile(1) };
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code == -(SIGSEGV as i32)
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let start = sbrk(0);
+    assert!(start > 0 && start as usize % PAGE_SIZE == 0);
+
+    // grow
+    let size = PAGE_SIZE * 4;
+    assert_eq!(sbrk(size as isize), start);
+    assert_eq!(sbrk(0), start + size as isize);
+    let heap = unsafe { core::slice::from_raw_parts_mut(start as *mut u8, size) };
+    assert!(heap.iter().all(|&b| b == 0));
+    heap.fill(1);
+    assert!(heap.iter().all(|&b| b == 1));
+
+    // shrink, the pages are released
+    assert_eq!(sbrk(-(size as isize)), start + size as isize);
+    assert_eq!(sbrk(0), start);
+    assert!(faults(start as *mut u8));
+
+    // not below the initial program break
+    assert_eq!(brk(PAGE_SIZE), -1);
+    assert_eq!(sbrk(0), start);
+
+    // the allocator grows the heap
+    let mut v = Vec::new();
+    for i in 0..(1 << 16) {
+        v.push(i);
+    }
+    assert!(v.iter().enumerate().all(|(i, &x)| i == x));
+    assert!(sbrk(0) > start);
+
+    println!("brk_test passed!");
+    0
+}
@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, sleep, times, waitpid, Tms};
+
+const LEN: usize = 4096 * 4;
+
+static mut DATA: [u8; LEN] = [0; LEN];
+static mut TMS: Tms = Tms {
+    tms_utime: 0,
+    tms_stime: 0,
+    tms_cutime: 0,
+    tms_cstime: 0,
+};
+
+fn fill(value: u8) {
+    unsafe { DATA.iter_mut().for_each(|b| *b = value) };
+}
+
+fn check(value: u8) -> bool {
+    unsafe { DATA.iter().all(|&b| b == value) }
+}
+
+fn wait_exit_code(pid: isize) -> i32 {
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    fill(1);
+
+    // the child writes its copy, the parent does not see it
+    let pid = fork();
+    if pid == 0 {
+        if !check(1) {
+            exit(-1);
+        }
+        fill(2);
+        exit(if check(2) { 0 } else { -2 });
+    }
+    assert_eq!(wait_exit_code(pid), 0);
+    assert!(check(1));
+
+    // the parent writes after fork, the child does not see it
+    let pid = fork();
+    if pid == 0 {
+        sleep(1);
+        exit(if check(1) { 0 } else { -1 });
+    }
+    fill(3);
+    assert_eq!(wait_exit_code(pid), 0);
+    assert!(check(3));
+
+    // the kernel writes to a shared page on behalf of the child
+    unsafe { TMS.tms_cutime = 100 };
+    let pid = fork();
+    if pid == 0 {
+        times(unsafe { &mut TMS });
+        let cutime = unsafe { TMS.tms_cutime };
+        exit(if cutime == 0 { 0 } else { -1 });
+    }
+    assert_eq!(wait_exit_code(pid), 0);
+    assert_eq!(unsafe { TMS.tms_cutime }, 100);
+
+    println!("cow_test passed!");
+    0
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time_us, getrusage, sleep, times, waitpid, RUsage, Tms, CLK_TCK,
+    RUSAGE_CHILDREN, RUSAGE_SELF,
+};
+
+const BUSY_US: isize = 200_000;
+
+fn busy_wait_us(us: isize) {
+    let start = get_time_us();
+    while get_time_us() - start < us {}
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let mut usage = RUsage::default();
+    assert_eq!(getrusage(RUSAGE_SELF, &mut usage), 0);
+    let start_cpu = usage.ru_utime.as_micros() + usage.ru_stime.as_micros();
+
+    // sleeping does not consume CPU time
+    sleep(1);
+    busy_wait_us(BUSY_US);
+    assert_eq!(getrusage(RUSAGE_SELF, &mut usage), 0);
+    let used = usage.ru_utime.as_micros() + usage.ru_stime.as_micros() - start_cpu;
+    println!(
+        "self: utime {} us, stime {} us",
+        usage.ru_utime.as_micros(),
+        usage.ru_stime.as_micros()
+    );
+    assert!(used >= BUSY_US as usize / 2 && used < 1_000_000);
+
+    let pid = fork();
+    if pid == 0 {
+        busy_wait_us(BUSY_US);
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(getrusage(RUSAGE_CHILDREN, &mut usage), 0);
+    let children = usage.ru_utime.as_micros() + usage.ru_stime.as_micros();
+    println!("children: {} us", children);
+    assert!(children >= BUSY_US as usize / 2);
+
+    let mut tms = Tms::default();
+    let ticks = times(&mut tms);
+    assert!(ticks > 0);
+    assert!(tms.tms_cutime + tms.tms_cstime >= BUSY_US as usize * CLK_TCK / 2_000_000);
+    assert_eq!(getrusage(2, &mut usage), -1);
+    println!("cputime passed!");
+    0
+}
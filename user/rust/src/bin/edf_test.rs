@@ -0,0 +1,113 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    clock_gettime, clock_nanosleep, exit, fork, get_time_us, sched_getattr, sched_getscheduler,
+    sched_setattr, sched_setscheduler, waitpid, SchedAttr, SchedParam, TimeSpec, CLOCK_MONOTONIC,
+    SCHED_DEADLINE, SCHED_OTHER, TIMER_ABSTIME,
+};
+
+const NUM_LOAD: usize = 3;
+const NUM_PERIODS: usize = 100;
+
+const RUNTIME_NS: u64 = 5_000_000;
+const PERIOD_NS: u64 = 20_000_000;
+const WORK_US: isize = 1_000;
+
+fn now_ns() -> u64 {
+    let mut ts = TimeSpec::default();
+    clock_gettime(CLOCK_MONOTONIC, &mut ts);
+    ts.sec as u64 * 1_000_000_000 + ts.nsec as u64
+}
+
+fn busy_wait_us(us: isize) {
+    let start = get_time_us();
+    while get_time_us() - start < us {}
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    // normal tasks that never sleep
+    let end_time = get_time_us() + (NUM_PERIODS as isize + 20) * (PERIOD_NS / 1000) as isize;
+    let mut loads = [0; NUM_LOAD];
+    for pid in loads.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            while get_time_us() < end_time {}
+            exit(0);
+        }
+    }
+
+    // more than 95% of the CPU can not be reserved
+    assert_eq!(
+        sched_setattr(
+            0,
+            &SchedAttr::deadline(PERIOD_NS * 99 / 100, PERIOD_NS, PERIOD_NS),
+            0
+        ),
+        -1
+    );
+    // runtime > deadline
+    assert_eq!(
+        sched_setattr(0, &SchedAttr::deadline(PERIOD_NS, RUNTIME_NS, PERIOD_NS), 0),
+        -1
+    );
+    assert_eq!(
+        sched_setattr(0, &SchedAttr::deadline(RUNTIME_NS, PERIOD_NS, 0), 0),
+        0
+    );
+    assert_eq!(sched_getscheduler(0), SCHED_DEADLINE as isize);
+    let mut attr = SchedAttr::default();
+    assert_eq!(sched_getattr(0, &mut attr), 0);
+    assert_eq!(attr.sched_policy, SCHED_DEADLINE as u32);
+    assert_eq!(attr.sched_runtime, RUNTIME_NS);
+    assert_eq!(attr.sched_deadline, PERIOD_NS);
+    assert_eq!(attr.sched_period, PERIOD_NS);
+
+    let pid = fork();
+    if pid == 0 {
+        // children do not inherit the reservation
+        assert_eq!(sched_getscheduler(0), SCHED_OTHER as isize);
+        // 25% is reserved by the parent
+        let ret = sched_setattr(0, &SchedAttr::deadline(PERIOD_NS * 4 / 5, PERIOD_NS, 0), 0);
+        exit(ret as i32);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, -1);
+
+    let mut misses = 0;
+    let mut max_finish = 0;
+    let mut period_start = now_ns();
+    for _ in 0..NUM_PERIODS {
+        busy_wait_us(WORK_US);
+        let finish = now_ns() - period_start;
+        max_finish = max_finish.max(finish);
+        if finish > PERIOD_NS {
+            misses += 1;
+        }
+        period_start += PERIOD_NS;
+        let next = TimeSpec {
+            sec: (period_start / 1_000_000_000) as usize,
+            nsec: (period_start % 1_000_000_000) as usize,
+        };
+        clock_nanosleep(CLOCK_MONOTONIC, TIMER_ABSTIME, &next);
+    }
+    println!(
+        "edf_test: {} deadline misses in {} periods, max finish time {} us",
+        misses,
+        NUM_PERIODS,
+        max_finish / 1000
+    );
+
+    sched_setscheduler(0, SCHED_OTHER, &SchedParam::new(0));
+    for pid in loads {
+        assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    }
+    assert_eq!(misses, 0);
+    println!("edf_test passed!");
+    0
+}
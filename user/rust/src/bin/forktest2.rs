@@ -9,7 +9,7 @@ use user_lib::{exit, fork, get_time_us, getpid, usleep, wait};
 static NUM: usize = 30;
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     for _ in 0..NUM {
         let pid = fork();
         if pid == 0 {
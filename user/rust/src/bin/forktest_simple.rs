@@ -7,7 +7,7 @@ extern crate user_lib;
 use user_lib::{fork, getpid};
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("parent start, pid = {}!", getpid());
     let pid = fork();
     if pid == 0 {
@@ -30,7 +30,7 @@ fn fork_tree(cur: &str) {
 }
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     fork_tree("");
     sleep(3);
     println!("forktree passed!");
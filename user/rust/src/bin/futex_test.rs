@@ -0,0 +1,137 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use user_lib::sync::{futex_requeue, futex_wait, futex_wake, Condvar, Mutex};
+use user_lib::{get_time_us, sched_yield, thread_join, thread_spawn, usleep, TimeSpec};
+
+const NUM_INCREMENTS: usize = 1000;
+const NUM_ITEMS: usize = 200;
+const QUEUE_SIZE: usize = 4;
+
+static COUNTER: Mutex<usize> = Mutex::new(0);
+
+struct Queue {
+    items: [usize; QUEUE_SIZE],
+    head: usize,
+    len: usize,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    items: [0; QUEUE_SIZE],
+    head: 0,
+    len: 0,
+});
+static NOT_EMPTY: Condvar = Condvar::new();
+static NOT_FULL: Condvar = Condvar::new();
+
+static FUTEX: AtomicU32 = AtomicU32::new(0);
+static TARGET: AtomicU32 = AtomicU32::new(0);
+
+fn increment(arg: usize) -> i32 {
+    for i in 0..NUM_INCREMENTS {
+        let mut counter = COUNTER.lock();
+        let value = *counter;
+        if i % 100 == 0 {
+            // let other threads block on the mutex
+            sched_yield();
+        }
+        *counter = value + 1;
+    }
+    arg as i32
+}
+
+fn produce(_arg: usize) -> i32 {
+    for i in 1..=NUM_ITEMS {
+        let mut queue = NOT_FULL.wait_while(QUEUE.lock(), |q| q.len == QUEUE_SIZE);
+        let tail = (queue.head + queue.len) % QUEUE_SIZE;
+        queue.items[tail] = i;
+        queue.len += 1;
+        NOT_EMPTY.notify_one();
+    }
+    0
+}
+
+/// Returns the sum of the consumed items, until a 0 is received.
+fn consume(_arg: usize) -> i32 {
+    let mut sum = 0;
+    loop {
+        let mut queue = NOT_EMPTY.wait_while(QUEUE.lock(), |q| q.len == 0);
+        let item = queue.items[queue.head];
+        queue.head = (queue.head + 1) % QUEUE_SIZE;
+        queue.len -= 1;
+        NOT_FULL.notify_one();
+        if item == 0 {
+            return sum as i32;
+        }
+        sum += item;
+    }
+}
+
+fn wait_futex(_arg: usize) -> i32 {
+    while FUTEX.load(Ordering::Acquire) == 0 {
+        futex_wait(&FUTEX, 0, None);
+    }
+    0
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    // waiting fails if the value does not match
+    assert!(!futex_wait(&FUTEX, 1, None));
+    assert_eq!(futex_wake(&FUTEX, 1), 0);
+
+    // waiting times out
+    let timeout = TimeSpec {
+        sec: 0,
+        nsec: 50_000_000,
+    };
+    let start = get_time_us();
+    assert!(!futex_wait(&FUTEX, 0, Some(&timeout)));
+    assert!(get_time_us() - start >= 50_000);
+
+    // the mutex keeps the counter consistent
+    let workers = [1, 2, 3, 4].map(|arg| (arg, thread_spawn(increment, arg)));
+    for (arg, tid) in workers {
+        assert!(tid > 0);
+        assert_eq!(thread_join(tid), Some(arg as i32));
+    }
+    assert_eq!(*COUNTER.lock(), workers.len() * NUM_INCREMENTS);
+    assert!(COUNTER.try_lock().is_some());
+    let guard = COUNTER.lock();
+    assert!(COUNTER.try_lock().is_none());
+    drop(guard);
+
+    // producers and consumers on a bounded queue
+    let producers = [thread_spawn(produce, 0), thread_spawn(produce, 0)];
+    let consumers = [thread_spawn(consume, 0), thread_spawn(consume, 0)];
+    for tid in producers {
+        assert_eq!(thread_join(tid), Some(0));
+    }
+    for _ in consumers {
+        let mut queue = NOT_FULL.wait_while(QUEUE.lock(), |q| q.len == QUEUE_SIZE);
+        let tail = (queue.head + queue.len) % QUEUE_SIZE;
+        queue.items[tail] = 0;
+        queue.len += 1;
+        NOT_EMPTY.notify_one();
+    }
+    let sum: i32 = consumers.iter().map(|&tid| thread_join(tid).unwrap()).sum();
+    assert_eq!(sum as usize, NUM_ITEMS * (NUM_ITEMS + 1));
+
+    // waiters are moved to another futex, and woken up there
+    let waiters = [0; 3].map(|_| thread_spawn(wait_futex, 0));
+    usleep(50_000);
+    FUTEX.store(1, Ordering::Release);
+    assert_eq!(futex_requeue(&FUTEX, 0, &TARGET, u32::MAX), 0);
+    assert_eq!(futex_wake(&FUTEX, u32::MAX), 0);
+    futex_wake(&TARGET, u32::MAX);
+    for tid in waiters {
+        assert_eq!(thread_join(tid), Some(0));
+    }
+    assert_eq!(thread_join(0), None);
+    println!("futex_test passed!");
+    0
+}
@@ -5,7 +5,7 @@
 extern crate user_lib;
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("Hello world from user mode program!");
     0
 }
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, times, waitpid, Tms};
+
+const PAGE_SIZE: usize = 4096;
+const PAGES: usize = 256;
+
+/// Far larger than what is touched, only touched pages get frames.
+static mut DATA: [u8; PAGE_SIZE * PAGES] = [0; PAGE_SIZE * PAGES];
+
+fn page(i: usize) -> &'static mut [u8] {
+    unsafe { &mut DATA[i * PAGE_SIZE..(i + 1) * PAGE_SIZE] }
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    // untouched pages read as zero
+    for i in (0..PAGES).step_by(64) {
+        assert!(page(i).iter().all(|&b| b == 0));
+    }
+
+    // written on first touch
+    for i in (1..PAGES).step_by(16) {
+        page(i).fill(i as u8);
+    }
+    for i in (1..PAGES).step_by(16) {
+        assert!(page(i).iter().all(|&b| b == i as u8));
+    }
+
+    // the kernel writes to an untouched page, no children have exited yet
+    let tms = unsafe { &mut *(page(PAGES - 1).as_mut_ptr() as *mut Tms) };
+    times(tms);
+    assert_eq!(tms.tms_cutime, 0);
+
+    // populated and unpopulated pages are both inherited by a child
+    let pid = fork();
+    if pid == 0 {
+        let ok = page(17).iter().all(|&b| b == 17) && page(18).iter().all(|&b| b == 0);
+        page(18).fill(1);
+        exit(if ok { 0 } else { -1 });
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, 0);
+    assert!(page(18).iter().all(|&b| b == 0));
+
+    println!("lazy_test passed!");
+    0
+}
@@ -46,7 +46,7 @@ fn work(times: isize) {
 }
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     for _ in 0..NUM {
         let pid = fork();
         if pid == 0 {
@@ -0,0 +1,105 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use user_lib::{close, exit, fork, mmap, msync, munmap, open, read, waitpid, write};
+use user_lib::{MmapFlags, MmapProt, MsyncFlags, OpenFlags};
+
+const PAGE_SIZE: usize = 4096;
+const FILE_SIZE: usize = PAGE_SIZE * 2 + 100;
+const MAP_SIZE: usize = PAGE_SIZE * 3;
+const PATH: &str = "mmap_file\0";
+
+fn pattern(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+/// Reads the whole file from the start.
+fn read_file() -> Vec<u8> {
+    let fd = open(PATH, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut buf = vec![0; MAP_SIZE];
+    let len = read(fd as usize, &mut buf);
+    assert!(len >= 0);
+    buf.truncate(len as usize);
+    close(fd as usize);
+    buf
+}
+
+fn map_file(fd: isize, flags: MmapFlags) -> &'static mut [u8] {
+    let prot = MmapProt::READ | MmapProt::WRITE;
+    let addr = mmap(0, MAP_SIZE, prot, flags, fd, 0);
+    assert!(addr > 0);
+    unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, MAP_SIZE) }
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let fd = open(PATH, OpenFlags::CREATE | OpenFlags::RDWR);
+    if fd < 0 {
+        println!("mmap_file_test skipped: no file system");
+        return 0;
+    }
+    let data: Vec<u8> = (0..FILE_SIZE).map(pattern).collect();
+    assert_eq!(write(fd as usize, &data), FILE_SIZE as isize);
+
+    // a read-only file can not be mapped shared and writable
+    let rd_fd = open(PATH, OpenFlags::RDONLY);
+    let prot = MmapProt::READ | MmapProt::WRITE;
+    assert_eq!(mmap(0, PAGE_SIZE, prot, MmapFlags::SHARED, rd_fd, 0), -1);
+    close(rd_fd as usize);
+
+    // the file content, zeros beyond the end of the file
+    let shared = map_file(fd, MmapFlags::SHARED);
+    assert_eq!(&shared[..FILE_SIZE], &data[..]);
+    assert!(shared[FILE_SIZE..].iter().all(|&b| b == 0));
+
+    // writes to the mapping are seen by `read`, and `write` is seen by the mapping
+    shared[0] = 0xaa;
+    shared[PAGE_SIZE + 1] = 0xbb;
+    let content = read_file();
+    assert_eq!((content[0], content[PAGE_SIZE + 1]), (0xaa, 0xbb));
+    let wr_fd = open(PATH, OpenFlags::RDWR);
+    assert_eq!(write(wr_fd as usize, &[1, 2]), 2);
+    close(wr_fd as usize);
+    assert_eq!(&shared[..3], &[1, 2, pattern(2)]);
+
+    // a child writes to the shared mapping
+    let pid = fork();
+    if pid == 0 {
+        shared[2] = 3;
+        exit(0);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(shared[2], 3);
+
+    // writes to a private mapping are not written back
+    let private = map_file(fd, MmapFlags::PRIVATE);
+    assert_eq!(&private[..3], &[1, 2, 3]);
+    private[PAGE_SIZE] = 0xcc;
+    assert_eq!(shared[PAGE_SIZE], pattern(PAGE_SIZE));
+    assert_eq!(munmap(private.as_ptr() as usize, MAP_SIZE), 0);
+
+    // written back to the file, which is not extended
+    shared[FILE_SIZE] = 0xdd;
+    let addr = shared.as_ptr() as usize;
+    assert_eq!(msync(addr, MAP_SIZE, MsyncFlags::SYNC), 0);
+    assert_eq!(munmap(addr, MAP_SIZE), 0);
+    close(fd as usize);
+    let content = read_file();
+    assert_eq!(content.len(), FILE_SIZE);
+    assert_eq!(&content[..3], &[1, 2, 3]);
+    assert_eq!(content[PAGE_SIZE], pattern(PAGE_SIZE));
+    assert_eq!(content[PAGE_SIZE + 1], 0xbb);
+
+    println!("mmap_file_test passed!");
+    0
+}
@@ -0,0 +1,125 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec;
+use user_lib::{exit, fork, mmap, mprotect, munmap, waitpid, MmapFlags, MmapProt, SIGSEGV};
+
+const PAGE_SIZE: usize = 4096;
+
+fn map(addr: usize, len: usize, prot: MmapProt, flags: MmapFlags) -> *mut u8 {
+    let ret = mmap(addr, len, prot, flags | MmapFlags::ANONYMOUS, -1, 0);
+    assert!(ret > 0);
+    ret as *mut u8
+}
+
+fn rw() -> MmapProt {
+    MmapProt::READ | MmapProt::WRITE
+}
+
+/// Runs `f` in a child, returns its exit code.
+fn in_child(f: impl FnOnce() -> i32) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exit(f());
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code
+}
+
+fn faults(f: impl FnOnce()) -> bool {
+    in_child(|| {
+        f();
+        0
+    }) == -(SIGSEGV as i32)
+}
+
+unsafe fn read(ptr: *mut u8) -> u8 {
+    ptr.read_volatile()
+}
+
+unsafe fn write(ptr: *mut u8, value: u8) {
+    ptr.write_volatile(value)
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let flags = MmapFlags::PRIVATE | MmapFlags::ANONYMOUS;
+    assert_eq!(mmap(0, 0, rw(), flags, -1, 0), -1);
+    assert_eq!(mmap(0, PAGE_SIZE, rw(), MmapFlags::ANONYMOUS, -1, 0), -1);
+
+    // zero filled, unmapped by `munmap`
+    let p = map(0, PAGE_SIZE * 4, rw(), MmapFlags::PRIVATE);
+    unsafe {
+        assert_eq!(read(p.add(PAGE_SIZE * 3)), 0);
+        write(p.add(PAGE_SIZE), 1);
+        assert_eq!(read(p.add(PAGE_SIZE)), 1);
+    }
+    assert_eq!(munmap(p as usize, PAGE_SIZE * 4), 0);
+    assert!(faults(|| unsafe { write(p, 1) }));
+
+    // a hole in the middle of a mapping
+    let p = map(0, PAGE_SIZE * 3, rw(), MmapFlags::PRIVATE);
+    unsafe { write(p, 1) };
+    assert_eq!(munmap(p as usize + PAGE_SIZE, PAGE_SIZE), 0);
+    assert!(faults(|| unsafe { write(p.add(PAGE_SIZE), 1) }));
+    unsafe {
+        assert_eq!(read(p), 1);
+        write(p.add(PAGE_SIZE * 2), 2);
+    }
+    // `mprotect` fails over the hole
+    assert_eq!(mprotect(p as usize, PAGE_SIZE * 3, MmapProt::READ), -1);
+    assert_eq!(munmap(p as usize, PAGE_SIZE * 3), 0);
+
+    // read-only and inaccessible pages
+    let p = map(0, PAGE_SIZE * 2, rw(), MmapFlags::PRIVATE);
+    unsafe { write(p, 1) };
+    assert_eq!(mprotect(p as usize, PAGE_SIZE, MmapProt::READ), 0);
+    assert!(faults(|| unsafe { write(p, 2) }));
+    assert_eq!(unsafe { read(p) }, 1);
+    unsafe { write(p.add(PAGE_SIZE), 1) };
+    assert_eq!(mprotect(p as usize, PAGE_SIZE * 2, MmapProt::empty()), 0);
+    assert!(faults(|| unsafe {
+        read(p.add(PAGE_SIZE));
+    }));
+    assert_eq!(mprotect(p as usize, PAGE_SIZE * 2, rw()), 0);
+    unsafe {
+        assert_eq!(read(p.add(PAGE_SIZE)), 1);
+        write(p, 3);
+    }
+
+    // `MmapFlags::FIXED` replaces the old mapping
+    let flags = MmapFlags::PRIVATE | MmapFlags::FIXED;
+    let q = map(p as usize, PAGE_SIZE, rw(), flags);
+    assert_eq!(q, p);
+    unsafe {
+        assert_eq!(read(p), 0);
+        assert_eq!(read(p.add(PAGE_SIZE)), 1);
+    }
+    assert_eq!(munmap(p as usize, PAGE_SIZE * 2), 0);
+
+    // shared and private mappings after `fork`
+    let shared = map(0, PAGE_SIZE, rw(), MmapFlags::SHARED);
+    let private = map(0, PAGE_SIZE, rw(), MmapFlags::PRIVATE);
+    let code = in_child(|| unsafe {
+        write(shared, 1);
+        write(private, 1);
+        0
+    });
+    assert_eq!(code, 0);
+    unsafe {
+        assert_eq!(read(shared), 1);
+        assert_eq!(read(private), 0);
+    }
+
+    // the heap grows beyond its initial size
+    let v = vec![1u8; 1 << 20];
+    assert!(v.iter().all(|&b| b == 1));
+
+    println!("mmap_test passed!");
+    0
+}
@@ -0,0 +1,131 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, mq_getattr, mq_open, mq_receive, mq_send, mq_unlink, waitpid};
+use user_lib::{MqAttr, OpenFlags};
+
+const NAME: &str = "/mq_test";
+const MSG_SIZE: usize = 16;
+const PRODUCERS: usize = 3;
+const CONSUMERS: usize = 2;
+const MSGS_PER_PRODUCER: usize = 20;
+
+/// Data messages are received before the stop messages sent after them.
+const DATA_PRIO: u32 = 1;
+const STOP_PRIO: u32 = 0;
+const STOP: u8 = 0xff;
+
+fn open(flags: OpenFlags) -> usize {
+    let mqd = mq_open(NAME, flags, None);
+    assert!(mqd >= 0);
+    mqd as usize
+}
+
+fn producer(id: u8) -> i32 {
+    let mqd = open(OpenFlags::WRONLY);
+    for seq in 0..MSGS_PER_PRODUCER as u8 {
+        if mq_send(mqd, &[id, seq], DATA_PRIO) != 0 {
+            return -1;
+        }
+    }
+    0
+}
+
+/// Returns the number of messages received, which are in order for each producer.
+fn consumer() -> i32 {
+    let mqd = open(OpenFlags::RDONLY);
+    let mut next_seq = [0u8; PRODUCERS];
+    let mut buf = [0u8; MSG_SIZE];
+    let mut count = 0;
+    loop {
+        let mut prio = 0;
+        if mq_receive(mqd, &mut buf, Some(&mut prio)) != 2 {
+            return -1;
+        }
+        let (id, seq) = (buf[0], buf[1]);
+        if id == STOP {
+            return count;
+        }
+        if prio != DATA_PRIO || seq < next_seq[id as usize] {
+            return -1;
+        }
+        next_seq[id as usize] = seq + 1;
+        count += 1;
+    }
+}
+
+fn wait_exit_code(pid: isize) -> i32 {
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let attr = MqAttr::new(4, MSG_SIZE);
+    let flags = OpenFlags::CREATE | OpenFlags::EXCL | OpenFlags::RDWR | OpenFlags::NONBLOCK;
+    let mqd = mq_open(NAME, flags, Some(&attr));
+    assert!(mqd >= 0);
+    let mqd = mqd as usize;
+    assert_eq!(mq_open(NAME, flags, Some(&attr)), -1);
+
+    // received by priorities, in FIFO order for the same priority
+    let mut buf = [0u8; MSG_SIZE];
+    let mut prio = 0;
+    assert_eq!(mq_receive(mqd, &mut buf, None), -1);
+    assert_eq!(mq_send(mqd, b"a", 1), 0);
+    assert_eq!(mq_send(mqd, b"b", 5), 0);
+    assert_eq!(mq_send(mqd, b"c", 1), 0);
+    assert_eq!(mq_send(mqd, b"", 0), 0);
+    assert_eq!(mq_send(mqd, b"full", 9), -1);
+    assert_eq!(mq_send(mqd, &[0; MSG_SIZE + 1], 1), -1);
+    let mut attr = MqAttr::default();
+    assert_eq!(mq_getattr(mqd, &mut attr), 0);
+    assert_eq!(
+        (attr.mq_maxmsg, attr.mq_msgsize, attr.mq_curmsgs),
+        (4, 16, 4)
+    );
+    assert_eq!(attr.mq_flags, OpenFlags::NONBLOCK.bits() as isize);
+    let expected: [(&[u8], u32); 4] = [(b"b", 5), (b"a", 1), (b"c", 1), (b"", 0)];
+    for (msg, msg_prio) in expected {
+        let len = mq_receive(mqd, &mut buf, Some(&mut prio));
+        assert_eq!(&buf[..len as usize], msg);
+        assert_eq!(prio, msg_prio);
+    }
+    assert_eq!(mq_receive(mqd, &mut buf[..MSG_SIZE - 1], None), -1);
+    close(mqd);
+
+    // blocking producers and consumers share the queue
+    let mut consumers = [0; CONSUMERS];
+    for pid in consumers.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            exit(consumer());
+        }
+    }
+    let mut producers = [0; PRODUCERS];
+    for (id, pid) in producers.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            exit(producer(id as u8));
+        }
+    }
+    for pid in producers {
+        assert_eq!(wait_exit_code(pid), 0);
+    }
+    let mqd = open(OpenFlags::WRONLY);
+    for _ in 0..CONSUMERS {
+        assert_eq!(mq_send(mqd, &[STOP, 0], STOP_PRIO), 0);
+    }
+    let received: i32 = consumers.iter().map(|&pid| wait_exit_code(pid)).sum();
+    assert_eq!(received as usize, PRODUCERS * MSGS_PER_PRODUCER);
+    close(mqd);
+
+    assert_eq!(mq_unlink(NAME), 0);
+    assert_eq!(mq_open(NAME, OpenFlags::RDONLY, None), -1);
+    println!("mq_test passed!");
+    0
+}
@@ -0,0 +1,104 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec::Vec;
+use user_lib::{close, dup, dup2, exit, fork, pipe, read, signal, waitpid, write};
+use user_lib::{SIGPIPE, SIG_IGN};
+
+const STDOUT: usize = 1;
+/// Larger than the capacity of a pipe, so that the writer blocks.
+const DATA_LEN: usize = 4096 * 3 + 100;
+
+fn wait_exit_code(pid: isize) -> i32 {
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code
+}
+
+/// Reads until the end of the pipe, returns the total size.
+fn read_all(fd: usize, check: impl Fn(usize, u8) -> bool) -> usize {
+    let mut buf = [0u8; 1000];
+    let mut total = 0;
+    loop {
+        let len = read(fd, &mut buf);
+        assert!(len >= 0);
+        if len == 0 {
+            return total;
+        }
+        for &b in &buf[..len as usize] {
+            assert!(check(total, b));
+            total += 1;
+        }
+    }
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let mut fds = [0; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    let [read_end, write_end] = fds;
+    assert_eq!(write(write_end, b"hello"), 5);
+    let mut buf = [0u8; 16];
+    assert_eq!(read(read_end, &mut buf), 5);
+    assert_eq!(&buf[..5], b"hello");
+    // the ends can not be used the other way
+    assert_eq!(read(write_end, &mut buf), -1);
+    assert_eq!(write(read_end, b"x"), -1);
+
+    // the reader gets EOF after the writer exits
+    let pid = fork();
+    if pid == 0 {
+        close(read_end);
+        let data: Vec<u8> = (0..DATA_LEN).map(|i| i as u8).collect();
+        exit((write(write_end, &data) != DATA_LEN as isize) as i32);
+    }
+    close(write_end);
+    assert_eq!(read_all(read_end, |i, b| b == i as u8), DATA_LEN);
+    assert_eq!(wait_exit_code(pid), 0);
+    close(read_end);
+
+    // the standard output of a child is redirected to a duplicate of the write end
+    assert_eq!(pipe(&mut fds), 0);
+    let [read_end, write_end] = fds;
+    let dup_end = dup(write_end);
+    assert!(dup_end >= 0);
+    close(write_end);
+    let pid = fork();
+    if pid == 0 {
+        close(read_end);
+        assert_eq!(dup2(dup_end as usize, STDOUT), STDOUT as isize);
+        close(dup_end as usize);
+        print!("written to pipe");
+        exit(0);
+    }
+    close(dup_end as usize);
+    let expected = b"written to pipe";
+    let len = read_all(read_end, |i, b| expected.get(i) == Some(&b));
+    assert_eq!(len, expected.len());
+    assert_eq!(wait_exit_code(pid), 0);
+    close(read_end);
+
+    // writing without readers raises SIGPIPE
+    assert_eq!(pipe(&mut fds), 0);
+    let [read_end, write_end] = fds;
+    close(read_end);
+    let pid = fork();
+    if pid == 0 {
+        write(write_end, b"x");
+        exit(0);
+    }
+    assert_eq!(wait_exit_code(pid), -(SIGPIPE as i32));
+    signal(SIGPIPE, SIG_IGN);
+    assert_eq!(write(write_end, b"x"), -1);
+    close(write_end);
+
+    // invalid descriptors
+    assert_eq!(dup(read_end), -1);
+    assert_eq!(dup2(read_end, 10), -1);
+    println!("pipe_test passed!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, getpriority, setpriority, waitpid, PRIO_PROCESS};
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    assert_eq!(getpriority(PRIO_PROCESS, 0), Some(0));
+    assert_eq!(setpriority(PRIO_PROCESS, 0, 5), 0);
+    assert_eq!(getpriority(PRIO_PROCESS, 0), Some(5));
+
+    let pid = fork();
+    if pid == 0 {
+        // inherited from the parent
+        assert_eq!(getpriority(PRIO_PROCESS, 0), Some(5));
+        // out of range values are clamped
+        setpriority(PRIO_PROCESS, 0, 100);
+        assert_eq!(getpriority(PRIO_PROCESS, 0), Some(19));
+        setpriority(PRIO_PROCESS, 0, -100);
+        assert_eq!(getpriority(PRIO_PROCESS, 0), Some(-20));
+        return 0;
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, 0);
+    assert!(getpriority(PRIO_PROCESS, pid as usize).is_none());
+    assert_eq!(getpriority(PRIO_PROCESS, 0), Some(5));
+    println!("priority passed!");
+    0
+}
@@ -0,0 +1,115 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use user_lib::{close, exit, fork, ftruncate, mmap, munmap, read, sched_yield, waitpid};
+use user_lib::{shm_open, shm_unlink, MmapFlags, MmapProt, OpenFlags};
+
+const PAGE_SIZE: usize = 4096;
+const NAME: &str = "shm_test";
+const DATA_LEN: usize = PAGE_SIZE * 2;
+
+/// The object shared by the producer and the consumer, the data follows the header page.
+struct Buffer {
+    state: &'static AtomicUsize,
+    data: &'static mut [u8],
+}
+
+const EMPTY: usize = 0;
+const FULL: usize = 1;
+const DONE: usize = 2;
+
+fn map_buffer(flags: OpenFlags) -> Buffer {
+    let fd = shm_open(NAME, flags);
+    assert!(fd >= 0);
+    let prot = MmapProt::READ | MmapProt::WRITE;
+    let addr = mmap(0, PAGE_SIZE + DATA_LEN, prot, MmapFlags::SHARED, fd, 0);
+    assert!(addr > 0);
+    close(fd as usize);
+    unsafe {
+        Buffer {
+            state: &*(addr as *const AtomicUsize),
+            data: core::slice::from_raw_parts_mut((addr as usize + PAGE_SIZE) as *mut u8, DATA_LEN),
+        }
+    }
+}
+
+fn wait_state(buf: &Buffer, state: usize) {
+    while buf.state.load(Ordering::Acquire) != state {
+        sched_yield();
+    }
+}
+
+fn consumer() -> i32 {
+    let mut buf = map_buffer(OpenFlags::RDWR);
+    wait_state(&buf, FULL);
+    let ok = buf.data.iter().enumerate().all(|(i, &b)| b == i as u8);
+    buf.data.fill(0xff);
+    buf.state.store(DONE, Ordering::Release);
+    if ok {
+        0
+    } else {
+        -1
+    }
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let fd = shm_open(NAME, OpenFlags::CREATE | OpenFlags::RDWR);
+    assert!(fd >= 0);
+    assert_eq!(ftruncate(fd as usize, PAGE_SIZE), 0);
+
+    // only the header is accessible until the object grows
+    let pid = fork();
+    if pid == 0 {
+        let prot = MmapProt::READ | MmapProt::WRITE;
+        let addr = mmap(0, PAGE_SIZE * 2, prot, MmapFlags::SHARED, fd, 0);
+        unsafe { ((addr as usize + PAGE_SIZE) as *mut u8).write_volatile(1) };
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_ne!(exit_code, 0);
+    assert_eq!(ftruncate(fd as usize, PAGE_SIZE + DATA_LEN), 0);
+
+    // shared memory objects can not be mapped private
+    let prot = MmapProt::READ | MmapProt::WRITE;
+    assert_eq!(mmap(0, PAGE_SIZE, prot, MmapFlags::PRIVATE, fd, 0), -1);
+
+    // the consumer opens and maps the object by name
+    let pid = fork();
+    if pid == 0 {
+        exit(consumer());
+    }
+    let mut buf = map_buffer(OpenFlags::RDWR);
+    for (i, b) in buf.data.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    buf.state.store(FULL, Ordering::Release);
+    wait_state(&buf, DONE);
+    assert!(buf.data.iter().all(|&b| b == 0xff));
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, 0);
+
+    // the content is also read through the file
+    let mut header = [0u8; 8];
+    assert_eq!(read(fd as usize, &mut header), 8);
+    assert_eq!(usize::from_ne_bytes(header), DONE);
+    close(fd as usize);
+
+    // the name is removed, the mapping stays valid
+    assert_eq!(shm_unlink(NAME), 0);
+    assert_eq!(shm_open(NAME, OpenFlags::RDWR), -1);
+    assert_eq!(shm_unlink(NAME), -1);
+    buf.data[0] = 1;
+    assert_eq!(buf.data[0], 1);
+    let addr = buf.state as *const AtomicUsize as usize;
+    assert_eq!(munmap(addr, PAGE_SIZE + DATA_LEN), 0);
+
+    println!("shm_test passed!");
+    0
+}
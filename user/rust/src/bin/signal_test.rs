@@ -0,0 +1,111 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use user_lib::{
+    exit, fork, get_time_us, getpid, kill, sigaction, sigmask, signal, sigprocmask, sleep, usleep,
+    waitpid, SigAction, SigSet, SIGKILL, SIGSEGV, SIGTERM, SIGUSR1, SIGUSR2, SIG_BLOCK, SIG_IGN,
+    SIG_UNBLOCK,
+};
+
+static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn handler(sig: usize) {
+    RECEIVED.fetch_add(sig, Ordering::SeqCst);
+}
+
+extern "C" fn segv_handler(sig: usize) {
+    exit(sig as i32 + 100);
+}
+
+fn received() -> usize {
+    RECEIVED.swap(0, Ordering::SeqCst)
+}
+
+fn wait_exit_code(pid: isize) -> i32 {
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let pid = getpid();
+
+    // delivered on returning from `kill`
+    assert_eq!(sigaction(SIGUSR1, Some(&SigAction::new(handler)), None), 0);
+    assert_eq!(kill(pid, SIGUSR1), 0);
+    assert_eq!(received(), SIGUSR1);
+
+    // delivered when unblocked
+    let set: SigSet = sigmask(SIGUSR1);
+    assert_eq!(sigprocmask(SIG_BLOCK, Some(&set), None), 0);
+    assert_eq!(kill(pid, SIGUSR1), 0);
+    assert_eq!(received(), 0);
+    let mut old: SigSet = 0;
+    assert_eq!(sigprocmask(SIG_UNBLOCK, Some(&set), Some(&mut old)), 0);
+    assert_eq!(old, set);
+    assert_eq!(received(), SIGUSR1);
+
+    // ignored
+    assert_eq!(signal(SIGUSR2, SIG_IGN), 0);
+    assert_eq!(kill(pid, SIGUSR2), 0);
+    assert_eq!(received(), 0);
+
+    // SIGKILL can not be caught
+    assert_eq!(signal(SIGKILL, SIG_IGN), -1);
+
+    // terminated by the default action
+    let child = fork();
+    if child == 0 {
+        loop {
+            sleep(1);
+        }
+    }
+    usleep(10_000);
+    assert_eq!(kill(child, SIGTERM), 0);
+    assert_eq!(wait_exit_code(child), -(SIGTERM as i32));
+
+    // a sleep is interrupted, and the handler runs on the child stack
+    let child = fork();
+    if child == 0 {
+        let start = get_time_us();
+        let ret = sleep(5);
+        let elapsed = get_time_us() - start;
+        exit(
+            if ret != 0 && elapsed < 1_000_000 && received() == SIGUSR1 {
+                0
+            } else {
+                -1
+            },
+        );
+    }
+    usleep(10_000);
+    assert_eq!(kill(child, SIGUSR1), 0);
+    assert_eq!(wait_exit_code(child), 0);
+
+    // faults turn into SIGSEGV
+    for catch in [false, true] {
+        let child = fork();
+        if child == 0 {
+            if catch {
+                sigaction(SIGSEGV, Some(&SigAction::new(segv_handler)), None);
+            }
+            unsafe { core::ptr::null_mut::<usize>().write_volatile(0) };
+            exit(0);
+        }
+        let expected = if catch {
+            SIGSEGV as i32 + 100
+        } else {
+            -(SIGSEGV as i32)
+        };
+        assert_eq!(wait_exit_code(child), expected);
+    }
+
+    println!("signal_test passed!");
+    0
+}
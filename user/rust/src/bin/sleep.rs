@@ -16,7 +16,7 @@ fn sleepy() {
 }
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     let current_time = get_time_us();
     let pid = fork();
     let mut exit_code = 0;
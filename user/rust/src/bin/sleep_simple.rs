@@ -7,7 +7,7 @@ extern crate user_lib;
 use user_lib::{get_time_us, sleep};
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("into sleep test!");
     let start = get_time_us();
     println!("current time_usec = {}", start);
@@ -11,7 +11,7 @@ fn f(d: usize) {
 }
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("It should trigger segmentation fault!");
     f(0);
     0
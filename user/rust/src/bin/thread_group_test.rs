@@ -0,0 +1,121 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{close, exit, fork, getpid, gettid, kill, pipe, read, sigaction, sleep, usleep};
+use user_lib::{thread_exit, thread_join, thread_spawn, waitpid, write, SigAction, SIGUSR1};
+
+static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+static WRITE_END: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn handler(sig: usize) {
+    RECEIVED.fetch_add(sig, Ordering::SeqCst);
+}
+
+fn wait_exit_code(pid: isize) -> i32 {
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    exit_code
+}
+
+fn check_ids(pid: usize) -> i32 {
+    assert_eq!(getpid(), pid as isize);
+    assert_ne!(gettid(), getpid());
+    gettid() as i32
+}
+
+/// Returns the read end of a new pipe, with some data written.
+fn open_pipe(_arg: usize) -> i32 {
+    let mut fds = [0; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    assert_eq!(write(fds[1], b"from thread"), 11);
+    WRITE_END.store(fds[1], Ordering::SeqCst);
+    fds[0] as i32
+}
+
+fn set_handler(_arg: usize) -> i32 {
+    sigaction(SIGUSR1, Some(&SigAction::new(handler)), None) as i32
+}
+
+fn fork_child(_arg: usize) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exit(5);
+    }
+    pid as i32
+}
+
+fn sleep_forever(_arg: usize) -> i32 {
+    loop {
+        sleep(1);
+    }
+}
+
+fn exit_process(exit_code: usize) -> i32 {
+    usleep(10_000);
+    exit(exit_code as i32);
+}
+
+/// Writes to the pipe after the leader exits.
+fn write_later(fd: usize) -> i32 {
+    usleep(50_000);
+    assert_eq!(write(fd, b"done"), 4);
+    0
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let pid = getpid();
+    assert_eq!(gettid(), pid);
+    let tid = thread_spawn(check_ids, pid as usize);
+    assert_eq!(thread_join(tid), Some(tid as i32));
+    // threads are not children
+    assert_eq!(waitpid(tid, None, 0), -1);
+
+    // the file descriptor table is shared
+    let read_end = thread_join(thread_spawn(open_pipe, 0)).unwrap() as usize;
+    let mut buf = [0u8; 16];
+    assert_eq!(read(read_end, &mut buf), 11);
+    assert_eq!(&buf[..11], b"from thread");
+    close(read_end);
+    close(WRITE_END.load(Ordering::SeqCst));
+
+    // signal handlers are shared
+    assert_eq!(thread_join(thread_spawn(set_handler, 0)), Some(0));
+    assert_eq!(kill(pid, SIGUSR1), 0);
+    assert_eq!(RECEIVED.load(Ordering::SeqCst), SIGUSR1);
+
+    // children forked by a thread belong to the process
+    let child = thread_join(thread_spawn(fork_child, 0)).unwrap() as isize;
+    assert!(child > 0);
+    assert_eq!(wait_exit_code(child), 5);
+
+    // exiting the process from a thread kills the other threads
+    let child = fork();
+    if child == 0 {
+        thread_spawn(sleep_forever, 0);
+        thread_spawn(exit_process, 42);
+        sleep_forever(0);
+    }
+    assert_eq!(wait_exit_code(child), 42);
+
+    // the process exits after its last thread, with the exit code of the leader
+    let mut fds = [0; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    let child = fork();
+    if child == 0 {
+        close(fds[0]);
+        thread_spawn(write_later, fds[1]);
+        thread_exit(3);
+    }
+    close(fds[1]);
+    assert_eq!(wait_exit_code(child), 3);
+    assert_eq!(read(fds[0], &mut buf), 4);
+    assert_eq!(&buf[..4], b"done");
+    close(fds[0]);
+    println!("thread_group_test passed!");
+    0
+}
@@ -5,7 +5,7 @@
 extern crate user_lib;
 
 use core::sync::atomic::{AtomicUsize, Ordering};
-use user_lib::{getpid, thread_spawn, waitpid};
+use user_lib::{getpid, gettid, thread_join, thread_spawn};
 
 static GLOBAL_VAR: AtomicUsize = AtomicUsize::new(0);
 
@@ -23,27 +23,27 @@ fn get_sp() -> usize {
 }
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     let test_user_thread = |arg| {
         for _ in 0..100 {
             let value = GLOBAL_VAR.fetch_add(100, Ordering::AcqRel);
             println!(
-                "test user thread: pid = {:?}, arg = {:#x}, sp = {:#x?}, global_var = {}",
+                "test user thread: pid = {:?}, tid = {:?}, arg = {:#x}, sp = {:#x?}, global_var = {}",
                 getpid(),
+                gettid(),
                 arg,
                 get_sp(),
                 value
             );
         }
-        -getpid() as _
+        -gettid() as _
     };
 
     let t0 = thread_spawn(test_user_thread, 0xdead);
     let t1 = thread_spawn(test_user_thread, 0xbeef);
-    let mut exit_code = 0;
-    waitpid(t0, Some(&mut exit_code), 0);
+    let exit_code = thread_join(t0).unwrap();
     println!("thread {} exited with {}.", t0, exit_code);
-    waitpid(t1, Some(&mut exit_code), 0);
+    let exit_code = thread_join(t1).unwrap();
     println!("thread {} exited with {}.", t1, exit_code);
     println!("main thread exited.");
     0
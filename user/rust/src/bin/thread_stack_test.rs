@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::hint::black_box;
+use user_lib::{exit, fork, thread_join, thread_spawn, waitpid, SIGSEGV};
+
+const NUM_THREADS: usize = 24;
+const NUM_ROUNDS: usize = 4;
+
+/// Uses most of the stack, returns `arg`.
+fn fill_stack(arg: usize) -> i32 {
+    let mut buf = [0u8; 12 * 1024];
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = (i + arg) as u8;
+    }
+    black_box(&mut buf);
+    assert!(buf.iter().enumerate().all(|(i, &b)| b == (i + arg) as u8));
+    arg as i32
+}
+
+#[allow(unconditional_recursion)]
+fn recurse(depth: usize) -> i32 {
+    let buf = black_box([depth as u8; 256]);
+    recurse(depth + 1) + buf[0] as i32
+}
+
+fn overflow(_arg: usize) -> i32 {
+    recurse(0)
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    // stacks are mapped on demand, and unmapped when the threads are joined
+    for _ in 0..NUM_ROUNDS {
+        let tids: [isize; NUM_THREADS] = core::array::from_fn(|i| thread_spawn(fill_stack, i));
+        for (i, &tid) in tids.iter().enumerate() {
+            assert!(tid > 0);
+            assert_eq!(thread_join(tid), Some(i as i32));
+        }
+    }
+
+    // a thread is joined only once
+    let tid = thread_spawn(fill_stack, 1);
+    assert_eq!(thread_join(tid), Some(1));
+    assert_eq!(thread_join(tid), None);
+
+    // overflowing the stack of a thread faults in its guard page
+    let pid = fork();
+    if pid == 0 {
+        thread_join(thread_spawn(overflow, 0));
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid, Some(&mut exit_code), 0), pid);
+    assert_eq!(exit_code, -(SIGSEGV as i32));
+    println!("thread_stack_test passed!");
+    0
+}
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+#![feature(thread_local)]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{sched_yield, thread_join, thread_spawn};
+
+const NUM_THREADS: usize = 4;
+const NUM_INCREMENTS: usize = 1000;
+
+#[repr(align(64))]
+struct Aligned(u8);
+
+#[thread_local]
+static mut COUNTER: usize = 100;
+#[thread_local]
+static mut ZEROS: [usize; NUM_THREADS] = [0; NUM_THREADS];
+#[thread_local]
+static ALIGNED: Aligned = Aligned(7);
+
+static ADDRS: [AtomicUsize; NUM_THREADS] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// Increments its own counter, returns the increase.
+fn count(arg: usize) -> i32 {
+    unsafe {
+        // initialized from .tdata and .tbss
+        assert_eq!(COUNTER, 100);
+        assert_eq!(ZEROS, [0; NUM_THREADS]);
+        assert_eq!(ALIGNED.0, 7);
+        assert_eq!(&ALIGNED as *const _ as usize % 64, 0);
+        ADDRS[arg - 1].store(&COUNTER as *const _ as usize, Ordering::SeqCst);
+        for i in 0..NUM_INCREMENTS {
+            COUNTER += arg;
+            if i % 100 == 0 {
+                sched_yield();
+            }
+        }
+        ZEROS[arg - 1] = arg;
+        assert_eq!(ZEROS.iter().sum::<usize>(), arg);
+        (COUNTER - 100) as i32
+    }
+}
+
+#[no_mangle]
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    // the main thread has its own block too
+    assert_eq!(count(NUM_THREADS), (NUM_THREADS * NUM_INCREMENTS) as i32);
+    let main_addr = ADDRS[NUM_THREADS - 1].load(Ordering::SeqCst);
+    for _ in 0..2 {
+        let tids = [1, 2, 3, 4].map(|arg| (arg, thread_spawn(count, arg)));
+        for (arg, tid) in tids {
+            assert!(tid > 0);
+            assert_eq!(thread_join(tid), Some((arg * NUM_INCREMENTS) as i32));
+        }
+        for (i, addr) in ADDRS.iter().enumerate() {
+            let addr = addr.load(Ordering::SeqCst);
+            assert!(ADDRS[i + 1..]
+                .iter()
+                .all(|a| a.load(Ordering::SeqCst) != addr));
+            assert_ne!(addr, main_addr);
+        }
+    }
+    unsafe {
+        // not changed by the other threads
+        assert_eq!(COUNTER, 100 + NUM_THREADS * NUM_INCREMENTS);
+        assert_eq!(ZEROS.iter().sum::<usize>(), NUM_THREADS);
+    }
+    println!("tls_test passed!");
+    0
+}
@@ -9,7 +9,7 @@ use alloc::{vec, vec::Vec};
 use user_lib::{connect, read, write};
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("udp test open!");
 
     let udp_fd = connect(10 << 24 | 0 << 16 | 2 << 8 | 2, 2001, 26099);
@@ -3,20 +3,136 @@
 
 #[macro_use]
 extern crate user_lib;
+extern crate alloc;
 
 const LF: u8 = b'\n';
 const CR: u8 = b'\r';
 const DL: u8 = b'\x7f';
 const BS: u8 = b'\x08';
 
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
 use user_lib::console::getchar;
-use user_lib::{exec, fork, waitpid};
+use user_lib::{close, dup2, exec, exit, fork, open, pipe, signal, waitpid, OpenFlags};
+use user_lib::{SIGINT, SIG_DFL, SIG_IGN};
 
 const MAX_CMD_LEN: usize = 256;
+const STDIN: usize = 0;
+const STDOUT: usize = 1;
+
+/// A command of a pipeline, with its arguments ending with `\0`.
+struct Command {
+    args: Vec<String>,
+    /// The file which the output is redirected to by `> file`.
+    output: Option<String>,
+}
+
+impl Command {
+    fn parse(cmd: &str) -> Option<Self> {
+        let mut args = Vec::new();
+        let mut output = None;
+        let mut tokens = cmd.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token == ">" {
+                output = Some(format!("{}\0", tokens.next()?));
+            } else {
+                args.push(format!("{}\0", token));
+            }
+        }
+        if args.is_empty() {
+            None
+        } else {
+            Some(Self { args, output })
+        }
+    }
+
+    /// Executes the command in the child process, with its standard input and output
+    /// replaced by `stdin` and `stdout` if any. Returns only if it fails.
+    fn exec(&self, stdin: Option<usize>, stdout: Option<usize>, pipe_fds: &[usize]) -> i32 {
+        signal(SIGINT, SIG_DFL);
+        if let Some(fd) = stdin {
+            dup2(fd, STDIN);
+        }
+        if let Some(fd) = stdout {
+            dup2(fd, STDOUT);
+        }
+        for &fd in pipe_fds {
+            close(fd);
+        }
+        if let Some(path) = &self.output {
+            let flags = OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC;
+            let fd = open(path, flags);
+            if fd < 0 {
+                println!("cannot open: {:?}", path.trim_end_matches('\0'));
+                return -4;
+            }
+            dup2(fd as usize, STDOUT);
+            close(fd as usize);
+        }
+        let mut argv: Vec<*const u8> = self.args.iter().map(|arg| arg.as_ptr()).collect();
+        argv.push(core::ptr::null());
+        exec(&self.args[0], &argv);
+        let name = self.args[0].trim_end_matches('\0');
+        println!("command not found: {:?}", name);
+        -4
+    }
+}
+
+/// Runs a pipeline of commands separated by `|`, and waits for all of them.
+fn run(line: &str) {
+    if line.trim().is_empty() {
+        return;
+    }
+    let cmds: Option<Vec<Command>> = line.split('|').map(Command::parse).collect();
+    let cmds = match cmds {
+        Some(cmds) => cmds,
+        None => {
+            println!("syntax error: {:?}", line);
+            return;
+        }
+    };
+    // the read end and the write end of the pipe after each command but the last
+    let mut pipes = Vec::new();
+    for _ in 1..cmds.len() {
+        let mut fds = [0; 2];
+        if pipe(&mut fds) < 0 {
+            println!("cannot create pipe");
+            for &fd in pipes.iter().flatten() {
+                close(fd);
+            }
+            return;
+        }
+        pipes.push(fds);
+    }
+    let pipe_fds: Vec<usize> = pipes.iter().flatten().copied().collect();
+    let mut pids = Vec::new();
+    for (i, cmd) in cmds.iter().enumerate() {
+        let pid = fork();
+        if pid == 0 {
+            let stdin = i.checked_sub(1).map(|i| pipes[i][0]);
+            let stdout = pipes.get(i).map(|fds| fds[1]);
+            exit(cmd.exec(stdin, stdout, &pipe_fds));
+        }
+        pids.push(pid);
+    }
+    // the readers get EOF when all writers exit
+    for &fd in &pipe_fds {
+        close(fd);
+    }
+    for pid in pids {
+        let mut exit_code = 0;
+        let exit_pid = waitpid(pid, Some(&mut exit_code), 0);
+        assert_eq!(pid, exit_pid);
+        println!("Shell: Process {} exited with code {}", pid, exit_code);
+    }
+}
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("Rust user shell");
+    // Ctrl-C only kills the foreground command
+    signal(SIGINT, SIG_IGN);
     let mut line = [0; MAX_CMD_LEN];
     let mut cursor = 0;
     print!(">> ");
@@ -25,25 +141,8 @@ pub fn main() -> i32 {
         match c {
             LF | CR => {
                 println!();
-                if cursor > 0 {
-                    line[cursor] = b'\0';
-                    let pid = fork();
-                    if pid == 0 {
-                        // child process
-                        let path = core::str::from_utf8(&line[..cursor]).unwrap();
-                        if exec(path) < 0 {
-                            println!("command not found: {:?}", path);
-                            return -4;
-                        }
-                        unreachable!();
-                    } else {
-                        let mut exit_code = 0;
-                        let exit_pid = waitpid(pid, Some(&mut exit_code), 0);
-                        assert_eq!(pid, exit_pid);
-                        println!("Shell: Process {} exited with code {}", pid, exit_code);
-                    }
-                    cursor = 0;
-                }
+                run(core::str::from_utf8(&line[..cursor]).unwrap());
+                cursor = 0;
                 print!(">> ");
             }
             BS | DL => {
@@ -10,25 +10,45 @@ static TESTS: &[&str] = &[
     "forktest\0",
     "forktest2\0",
     "forktest_simple\0",
+    "forktree\0",
     "hello_world\0",
     "matrix\0",
+    "priority\0",
     "sleep\0",
     "sleep_simple\0",
     "stack_overflow\0",
     "yield\0",
     "thread_simple\0",
     "cyclictest\0",
+    "cputime\0",
+    "edf_test\0",
+    "signal_test\0",
+    "argv_test\0",
+    "cow_test\0",
+    "lazy_test\0",
+    "mmap_test\0",
+    "brk_test\0",
+    "mmap_file_test\0",
+    "shm_test\0",
+    "mq_test\0",
+    "pipe_test\0",
+    "futex_test\0",
+    "thread_group_test\0",
+    "tls_test\0",
+    "thread_stack_test\0",
+    "malloc_test\0",
 ];
 
 use user_lib::{exec, fork, waitpid};
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
+    let mut failed = 0;
     for test in TESTS {
         println!("Usertests: Running '{}':", test);
         let pid = fork();
         if pid == 0 {
-            if exec(*test) == -1 {
+            if exec(test, &[test.as_ptr(), core::ptr::null()]) == -1 {
                 panic!("usertest '{}' not found!", test);
             } else {
                 panic!("unreachable!");
@@ -37,6 +57,9 @@ pub fn main() -> i32 {
             let mut exit_code = 0;
             let wait_pid = waitpid(pid, Some(&mut exit_code), 0);
             assert_eq!(pid, wait_pid);
+            if exit_code != 0 {
+                failed += 1;
+            }
             let color = if exit_code == 0 { 32 } else { 31 };
             println!(
                 "\x1b[{}mUsertests: Test '{}' in Process {} exited with code {}.\x1b[0m",
@@ -44,6 +67,10 @@ pub fn main() -> i32 {
             );
         }
     }
+    if failed > 0 {
+        println!("usertests failed: {} of {} tests!", failed, TESTS.len());
+        return -1;
+    }
     println!("usertests passed!");
     0
 }
@@ -6,7 +6,7 @@ extern crate user_lib;
 use user_lib::{getpid, sched_yield};
 
 #[no_mangle]
-pub fn main() -> i32 {
+pub fn main(_argc: usize, _argv: &[&str]) -> i32 {
     println!("Hello, I am process {}.", getpid());
     for i in 0..5 {
         sched_yield();
@@ -0,0 +1,59 @@
+use alloc::format;
+use core::ptr::{null, null_mut};
+
+use super::syscall::*;
+use super::OpenFlags;
+
+/// Same as `struct mq_attr` of the kernel.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MqAttr {
+    /// `OpenFlags::NONBLOCK` or 0
+    pub mq_flags: isize,
+    /// capacity of the queue
+    pub mq_maxmsg: isize,
+    /// maximum size of messages
+    pub mq_msgsize: isize,
+    /// number of messages in the queue
+    pub mq_curmsgs: isize,
+    reserved: [isize; 4],
+}
+
+impl MqAttr {
+    pub fn new(max_msgs: usize, msg_size: usize) -> Self {
+        Self {
+            mq_maxmsg: max_msgs as isize,
+            mq_msgsize: msg_size as isize,
+            ..Default::default()
+        }
+    }
+}
+
+/// Opens the message queue `name`, which starts with `/`. It is created with
+/// `OpenFlags::CREATE`, with the capacity and maximum message size in `attr` or the
+/// defaults of the kernel. Returns the message queue descriptor, closed by `close`.
+pub fn mq_open(name: &str, flags: OpenFlags, attr: Option<&MqAttr>) -> isize {
+    let name = format!("{}\0", name.strip_prefix('/').unwrap_or(name));
+    let attr = attr.map_or(null(), |a| a as *const _);
+    sys_mq_open(&name, flags.bits, attr)
+}
+
+pub fn mq_unlink(name: &str) -> isize {
+    let name = format!("{}\0", name.strip_prefix('/').unwrap_or(name));
+    sys_mq_unlink(&name)
+}
+
+/// Sends `msg` with priority `prio`, higher priority messages are received first.
+pub fn mq_send(mqd: usize, msg: &[u8], prio: u32) -> isize {
+    sys_mq_timedsend(mqd, msg, prio)
+}
+
+/// Receives a message into `buf`, which must be able to hold the largest message.
+/// Returns the message size.
+pub fn mq_receive(mqd: usize, buf: &mut [u8], prio: Option<&mut u32>) -> isize {
+    sys_mq_timedreceive(mqd, buf, prio.map_or(null_mut(), |p| p as *mut _))
+}
+
+pub fn mq_getattr(mqd: usize, attr: &mut MqAttr) -> isize {
+    sys_mq_getsetattr(mqd, null(), attr)
+}
@@ -9,26 +9,29 @@
 pub mod console;
 
 mod arch;
+mod ipc;
 mod lang_items;
+mod mm;
 mod net;
+mod sched;
+mod signal;
+pub mod sync;
 mod syscall;
+mod thread;
 mod time;
 extern crate alloc;
 extern crate bitflags;
 use alloc::vec::Vec;
 use bitflags::bitflags;
-use buddy_system_allocator::LockedHeap;
+pub use ipc::*;
+pub use mm::*;
 pub use net::*;
+pub use sched::*;
+pub use signal::*;
 use syscall::*;
+pub use thread::*;
 pub use time::*;
 
-const USER_HEAP_SIZE: usize = 32768;
-
-static mut HEAP_SPACE: [u8; USER_HEAP_SIZE] = [0; USER_HEAP_SIZE];
-
-#[global_allocator]
-static HEAP: LockedHeap = LockedHeap::empty();
-
 #[alloc_error_handler]
 pub fn handle_alloc_error(layout: core::alloc::Layout) -> ! {
     panic!("Heap allocation error, layout = {:?}", layout);
@@ -38,27 +41,70 @@ bitflags! {
         const RDONLY = 0;
         const WRONLY = 1 << 0;
         const RDWR = 1 << 1;
+        const EXCL = 1 << 7;
         const CREATE = 1 << 9;
         const TRUNC = 1 << 10;
+        const NONBLOCK = 1 << 11;
     }
 }
 
-#[no_mangle]
-#[link_section = ".text.entry"]
-pub extern "C" fn _start() -> ! {
+/// Environment variables as `KEY=VALUE` strings, set before `main()`.
+static mut ENVS: Vec<&'static str> = Vec::new();
+
+/// Reads a `\0` terminated string on the initial user stack.
+unsafe fn str_from_ptr(ptr: *const u8) -> &'static str {
+    let len = (0..).find(|&i| ptr.add(i).read() == b'\0').unwrap();
+    core::str::from_utf8(core::slice::from_raw_parts(ptr, len)).unwrap()
+}
+
+const AT_NULL: usize = 0;
+const AT_PHDR: usize = 3;
+const AT_PHNUM: usize = 5;
+
+/// Called by `_start` with the initial user stack pointer, where `argc` is followed by
+/// the null-terminated `argv` and `envp`, and the auxiliary vector.
+extern "C" fn start_main(sp: *const usize) -> ! {
+    let argc = unsafe { sp.read() };
+    let argv = unsafe { sp.add(1) as *const *const u8 };
+    let args: Vec<&'static str> = (0..argc)
+        .map(|i| unsafe { str_from_ptr(argv.add(i).read()) })
+        .collect();
     unsafe {
-        HEAP.lock()
-            .init(HEAP_SPACE.as_ptr() as usize, USER_HEAP_SIZE);
+        let mut envp = argv.add(argc + 1);
+        while !envp.read().is_null() {
+            ENVS.push(str_from_ptr(envp.read()));
+            envp = envp.add(1);
+        }
+        let mut auxv = envp.add(1) as *const [usize; 2];
+        let (mut phdr, mut phnum) = (0, 0);
+        loop {
+            match auxv.read() {
+                [AT_NULL, _] => break,
+                [AT_PHDR, value] => phdr = value,
+                [AT_PHNUM, value] => phnum = value,
+                _ => {}
+            }
+            auxv = auxv.add(1);
+        }
+        thread::init_tls(phdr, phnum);
     }
-    exit(main());
+    exit(main(argc, &args));
 }
 
 #[linkage = "weak"]
 #[no_mangle]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     panic!("Cannot find main!");
 }
 
+/// Returns the value of the environment variable `key`.
+pub fn getenv(key: &str) -> Option<&'static str> {
+    unsafe { ENVS.iter() }.find_map(|env| {
+        env.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix('='))
+    })
+}
+
 
 pub fn read(fd: usize, buf: &mut [u8]) -> isize {
     sys_read(fd, buf)
@@ -73,9 +119,31 @@ pub fn open(path: &str, flags: OpenFlags) -> isize {
 pub fn close(fd: usize) -> isize {
     sys_close(fd)
 }
+/// Creates a pipe, `fds` receives the file descriptors of its read end and write end.
+pub fn pipe(fds: &mut [usize; 2]) -> isize {
+    let mut pipe_fds = [0; 2];
+    let ret = sys_pipe(&mut pipe_fds);
+    if ret == 0 {
+        *fds = pipe_fds.map(|fd| fd as usize);
+    }
+    ret
+}
+pub fn dup(fd: usize) -> isize {
+    sys_dup(fd)
+}
+pub fn dup2(old_fd: usize, new_fd: usize) -> isize {
+    sys_dup2(old_fd, new_fd)
+}
+pub fn ftruncate(fd: usize, len: usize) -> isize {
+    sys_ftruncate(fd, len)
+}
+pub fn unlink(path: &str) -> isize {
+    sys_unlink(path)
+}
 
+/// Exits the process with all its threads.
 pub fn exit(exit_code: i32) -> ! {
-    sys_exit(exit_code)
+    sys_exit_group(exit_code)
 }
 
 pub fn sched_yield() -> isize {
@@ -86,12 +154,24 @@ pub fn getpid() -> isize {
     sys_getpid()
 }
 
+pub fn gettid() -> isize {
+    sys_gettid()
+}
+
 pub fn fork() -> isize {
     sys_fork()
 }
 
-pub fn exec(path: &str) -> isize {
-    sys_exec(path)
+/// Runs the program `path` with arguments `argv` and environment variables `envp`.
+/// All strings must end with `\0`, and both arrays must end with a null pointer.
+pub fn execve(path: &str, argv: &[*const u8], envp: &[*const u8]) -> isize {
+    assert_eq!(argv.last(), Some(&core::ptr::null()));
+    assert_eq!(envp.last(), Some(&core::ptr::null()));
+    sys_exec(path, argv.as_ptr(), envp.as_ptr())
+}
+
+pub fn exec(path: &str, argv: &[*const u8]) -> isize {
+    execve(path, argv, &[core::ptr::null()])
 }
 
 pub fn waitpid(pid: isize, exit_code: Option<&mut i32>, options: u32) -> isize {
@@ -103,15 +183,18 @@ pub fn wait(exit_code: Option<&mut i32>) -> isize {
     waitpid(-1, exit_code, 0)
 }
 
-pub fn thread_spawn(entry: fn(usize) -> i32, arg: usize) -> isize {
-    use core::sync::atomic::{AtomicUsize, Ordering};
-    const MAX_THREADS: usize = 16;
-    const THREAD_STACK_SIZE: usize = 4096 * 4; // 16K
-    static mut THREAD_STACKS: [[u8; THREAD_STACK_SIZE]; MAX_THREADS] =
-        [[0; THREAD_STACK_SIZE]; MAX_THREADS];
-    static THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+pub const PRIO_PROCESS: usize = 0;
+
+/// Returns the nice value of the process `who` (0 for the calling process).
+pub fn getpriority(which: usize, who: usize) -> Option<i32> {
+    let ret = sys_getpriority(which, who);
+    if ret < 0 {
+        None
+    } else {
+        Some(20 - ret as i32)
+    }
+}
 
-    let thread_id = THREAD_COUNT.fetch_add(1, Ordering::AcqRel);
-    let newsp = unsafe { THREAD_STACKS[thread_id].as_ptr_range().end as usize };
-    sys_clone(entry, arg, newsp)
+pub fn setpriority(which: usize, who: usize, nice: i32) -> isize {
+    sys_setpriority(which, who, nice)
 }
@@ -0,0 +1,124 @@
+use alloc::format;
+use core::alloc::{GlobalAlloc, Layout};
+use core::ptr::{null_mut, NonNull};
+
+use bitflags::bitflags;
+use buddy_system_allocator::LockedHeap;
+
+use super::syscall::*;
+use super::{open, unlink, OpenFlags};
+
+bitflags! {
+    pub struct MmapProt: usize {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXEC = 1 << 2;
+    }
+}
+
+bitflags! {
+    pub struct MmapFlags: usize {
+        const SHARED = 0x01;
+        const PRIVATE = 0x02;
+        const FIXED = 0x10;
+        const ANONYMOUS = 0x20;
+        /// A thread stack, whose inaccessible pages are guard pages.
+        const STACK = 0x20000;
+    }
+}
+
+/// Maps `len` bytes at `addr`, or at an address chosen by the kernel if `addr` is not
+/// free and `MmapFlags::FIXED` is not given. Returns the start address, or -1 on failure.
+pub fn mmap(
+    addr: usize,
+    len: usize,
+    prot: MmapProt,
+    flags: MmapFlags,
+    fd: isize,
+    offset: usize,
+) -> isize {
+    sys_mmap(addr, len, prot.bits, flags.bits, fd as usize, offset)
+}
+
+pub fn munmap(addr: usize, len: usize) -> isize {
+    sys_munmap(addr, len)
+}
+
+pub fn mprotect(addr: usize, len: usize, prot: MmapProt) -> isize {
+    sys_mprotect(addr, len, prot.bits)
+}
+
+bitflags! {
+    pub struct MsyncFlags: usize {
+        const ASYNC = 1;
+        const INVALIDATE = 2;
+        const SYNC = 4;
+    }
+}
+
+/// Writes the shared file mappings in `[addr, addr + len)` back to the files.
+pub fn msync(addr: usize, len: usize, flags: MsyncFlags) -> isize {
+    sys_msync(addr, len, flags.bits)
+}
+
+/// Opens the shared memory object `name`, which is created empty with
+/// `OpenFlags::CREATE` and sized with `ftruncate`.
+pub fn shm_open(name: &str, flags: OpenFlags) -> isize {
+    open(&format!("/dev/shm/{}\0", name), flags)
+}
+
+pub fn shm_unlink(name: &str) -> isize {
+    unlink(&format!("/dev/shm/{}\0", name))
+}
+
+/// Sets the program break to `addr`, returns 0 on success or -1 on failure.
+pub fn brk(addr: usize) -> isize {
+    if sys_brk(addr) == addr as isize {
+        0
+    } else {
+        -1
+    }
+}
+
+/// Moves the program break by `increment` bytes, returns the old program break or -1
+/// on failure.
+pub fn sbrk(increment: isize) -> isize {
+    let old = sys_brk(0);
+    if increment != 0 && brk((old + increment) as usize) != 0 {
+        return -1;
+    }
+    old
+}
+
+/// The heap grows by at least this size.
+const HEAP_CHUNK_SIZE: usize = 0x10000; // 64K
+
+static HEAP: LockedHeap = LockedHeap::empty();
+
+/// Allocates from `HEAP`, which grows by moving the program break when exhausted.
+struct HeapAllocator;
+
+#[global_allocator]
+static HEAP_ALLOCATOR: HeapAllocator = HeapAllocator;
+
+unsafe impl GlobalAlloc for HeapAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let mut heap = HEAP.lock();
+        if let Ok(ptr) = heap.alloc(layout) {
+            return ptr.as_ptr();
+        }
+        // twice the buddy block size ensures an aligned block in the new memory
+        let block_size = layout.size().max(layout.align()).next_power_of_two();
+        let size = (block_size * 2).max(HEAP_CHUNK_SIZE);
+        let start = sbrk(size as isize);
+        if start < 0 {
+            return null_mut();
+        }
+        heap.add_to_heap(start as usize, start as usize + size);
+        heap.alloc(layout).map_or(null_mut(), |ptr| ptr.as_ptr())
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        HEAP.lock().dealloc(NonNull::new_unchecked(ptr), layout)
+    }
+}
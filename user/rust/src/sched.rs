@@ -0,0 +1,72 @@
+use super::syscall::*;
+
+pub const SCHED_OTHER: usize = 0;
+pub const SCHED_FIFO: usize = 1;
+pub const SCHED_RR: usize = 2;
+pub const SCHED_DEADLINE: usize = 6;
+
+#[repr(C)]
+#[derive(Default)]
+pub struct SchedParam {
+    pub sched_priority: i32,
+}
+
+impl SchedParam {
+    pub const fn new(sched_priority: i32) -> Self {
+        Self { sched_priority }
+    }
+}
+
+/// Scheduling attributes of `sched_setattr()`, times are in nanoseconds.
+#[repr(C)]
+#[derive(Default)]
+pub struct SchedAttr {
+    pub size: u32,
+    pub sched_policy: u32,
+    pub sched_flags: u64,
+    pub sched_nice: i32,
+    pub sched_priority: u32,
+    pub sched_runtime: u64,
+    pub sched_deadline: u64,
+    pub sched_period: u64,
+}
+
+impl SchedAttr {
+    /// Attributes of a `SCHED_DEADLINE` task with `runtime` every `period`.
+    pub const fn deadline(runtime: u64, deadline: u64, period: u64) -> Self {
+        Self {
+            size: core::mem::size_of::<Self>() as u32,
+            sched_policy: SCHED_DEADLINE as u32,
+            sched_flags: 0,
+            sched_nice: 0,
+            sched_priority: 0,
+            sched_runtime: runtime,
+            sched_deadline: deadline,
+            sched_period: period,
+        }
+    }
+}
+
+pub fn sched_setscheduler(pid: usize, policy: usize, param: &SchedParam) -> isize {
+    sys_sched_setscheduler(pid, policy, param)
+}
+
+pub fn sched_getscheduler(pid: usize) -> isize {
+    sys_sched_getscheduler(pid)
+}
+
+pub fn sched_setparam(pid: usize, param: &SchedParam) -> isize {
+    sys_sched_setparam(pid, param)
+}
+
+pub fn sched_getparam(pid: usize, param: &mut SchedParam) -> isize {
+    sys_sched_getparam(pid, param)
+}
+
+pub fn sched_setattr(pid: usize, attr: &SchedAttr, flags: u32) -> isize {
+    sys_sched_setattr(pid, attr, flags)
+}
+
+pub fn sched_getattr(pid: usize, attr: &mut SchedAttr) -> isize {
+    sys_sched_getattr(pid, attr, core::mem::size_of::<SchedAttr>())
+}
@@ -0,0 +1,98 @@
+use super::syscall::*;
+
+pub const SIGHUP: usize = 1;
+pub const SIGINT: usize = 2;
+pub const SIGQUIT: usize = 3;
+pub const SIGILL: usize = 4;
+pub const SIGTRAP: usize = 5;
+pub const SIGABRT: usize = 6;
+pub const SIGBUS: usize = 7;
+pub const SIGFPE: usize = 8;
+pub const SIGKILL: usize = 9;
+pub const SIGUSR1: usize = 10;
+pub const SIGSEGV: usize = 11;
+pub const SIGUSR2: usize = 12;
+pub const SIGPIPE: usize = 13;
+pub const SIGALRM: usize = 14;
+pub const SIGTERM: usize = 15;
+pub const SIGCHLD: usize = 17;
+pub const SIGCONT: usize = 18;
+pub const SIGSTOP: usize = 19;
+
+pub const SIG_DFL: usize = 0;
+pub const SIG_IGN: usize = 1;
+
+pub const SA_RESTORER: usize = 0x0400_0000;
+pub const SA_NODEFER: usize = 0x4000_0000;
+pub const SA_RESETHAND: usize = 0x8000_0000;
+
+pub const SIG_BLOCK: usize = 0;
+pub const SIG_UNBLOCK: usize = 1;
+pub const SIG_SETMASK: usize = 2;
+
+/// A set of signals, bit `n - 1` stands for signal `n`.
+pub type SigSet = u64;
+
+pub const fn sigmask(sig: usize) -> SigSet {
+    1 << (sig - 1)
+}
+
+/// Same as `struct sigaction` of the kernel. `restorer` is always set by `sigaction()`.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SigAction {
+    pub handler: usize,
+    pub flags: usize,
+    pub restorer: usize,
+    pub mask: SigSet,
+}
+
+impl SigAction {
+    /// Calls `handler` with the signal number on delivery.
+    pub fn new(handler: extern "C" fn(usize)) -> Self {
+        Self::with_handler(handler as usize)
+    }
+
+    /// `SIG_DFL`, `SIG_IGN` or the address of a handler.
+    pub const fn with_handler(handler: usize) -> Self {
+        Self {
+            handler,
+            flags: 0,
+            restorer: 0,
+            mask: 0,
+        }
+    }
+}
+
+pub fn kill(pid: isize, sig: usize) -> isize {
+    sys_kill(pid, sig)
+}
+
+pub fn sigaction(sig: usize, act: Option<&SigAction>, oldact: Option<&mut SigAction>) -> isize {
+    // handlers return to `sys_rt_sigreturn`
+    let act = act.map(|act| SigAction {
+        flags: act.flags | SA_RESTORER,
+        restorer: sys_rt_sigreturn as usize,
+        ..*act
+    });
+    let act_ptr = act.as_ref().map_or(core::ptr::null(), |a| a as *const _);
+    let oldact_ptr = oldact.map_or(core::ptr::null_mut(), |a| a as *mut _);
+    sys_rt_sigaction(sig, act_ptr, oldact_ptr)
+}
+
+/// Sets the handler of `sig`, returns the old one or -1 on error.
+pub fn signal(sig: usize, handler: usize) -> isize {
+    let mut old = SigAction::default();
+    let ret = sigaction(sig, Some(&SigAction::with_handler(handler)), Some(&mut old));
+    if ret < 0 {
+        ret
+    } else {
+        old.handler as isize
+    }
+}
+
+pub fn sigprocmask(how: usize, set: Option<&SigSet>, oldset: Option<&mut SigSet>) -> isize {
+    let set_ptr = set.map_or(core::ptr::null(), |s| s as *const _);
+    let oldset_ptr = oldset.map_or(core::ptr::null_mut(), |s| s as *mut _);
+    sys_rt_sigprocmask(how, set_ptr, oldset_ptr)
+}
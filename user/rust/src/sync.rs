@@ -0,0 +1,168 @@
+//! Locks for threads, which only enter the kernel when they block or wake up waiters.
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::ptr::null;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::syscall::sys_futex;
+use super::TimeSpec;
+
+const FUTEX_WAIT: u32 = 0;
+const FUTEX_WAKE: u32 = 1;
+const FUTEX_REQUEUE: u32 = 3;
+const FUTEX_PRIVATE_FLAG: u32 = 128;
+
+/// Blocks while `futex` holds `val`, until woken up by `futex_wake()`, interrupted by a
+/// signal, or `timeout` elapses. Returns `true` only if woken up.
+pub fn futex_wait(futex: &AtomicU32, val: u32, timeout: Option<&TimeSpec>) -> bool {
+    let timeout = timeout.map_or(0, |t| t as *const _ as usize);
+    let op = FUTEX_WAIT | FUTEX_PRIVATE_FLAG;
+    sys_futex(futex, op, val, timeout, null(), 0) == 0
+}
+
+/// Wakes up at most `count` threads waiting on `futex`, returns the number of woken
+/// threads.
+pub fn futex_wake(futex: &AtomicU32, count: u32) -> isize {
+    sys_futex(futex, FUTEX_WAKE | FUTEX_PRIVATE_FLAG, count, 0, null(), 0)
+}
+
+/// Wakes up at most `count` threads waiting on `futex`, and moves at most `requeue` of
+/// the others to wait on `target`. Returns the number of woken threads.
+pub fn futex_requeue(futex: &AtomicU32, count: u32, target: &AtomicU32, requeue: u32) -> isize {
+    let op = FUTEX_REQUEUE | FUTEX_PRIVATE_FLAG;
+    sys_futex(futex, op, count, requeue as usize, target, 0)
+}
+
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+/// Locked, and there may be waiters.
+const CONTENDED: u32 = 2;
+
+/// A mutual exclusion lock, threads block on a futex while it is locked.
+pub struct Mutex<T: ?Sized> {
+    state: AtomicU32,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+/// Unlocks the mutex when dropped.
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<T> Mutex<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            state: AtomicU32::new(UNLOCKED),
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        if self
+            .state
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            self.lock_contended();
+        }
+        MutexGuard { mutex: self }
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.state
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| MutexGuard { mutex: self })
+    }
+
+    /// Locks as contended, as there may be other waiters.
+    fn lock_contended(&self) {
+        while self.state.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+            futex_wait(&self.state, CONTENDED, None);
+        }
+    }
+
+    fn unlock(&self) {
+        if self.state.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+            futex_wake(&self.state, 1);
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.unlock();
+    }
+}
+
+/// A condition variable, threads block on a futex holding the number of notifications.
+pub struct Condvar {
+    seq: AtomicU32,
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self {
+            seq: AtomicU32::new(0),
+        }
+    }
+
+    /// Unlocks the mutex of `guard` and blocks until notified, then locks it again. It
+    /// may also return spuriously.
+    pub fn wait<'a, T: ?Sized>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        let seq = self.seq.load(Ordering::Relaxed);
+        let mutex = guard.mutex;
+        drop(guard);
+        futex_wait(&self.seq, seq, None);
+        // other notified waiters may be blocked on the mutex
+        mutex.lock_contended();
+        MutexGuard { mutex }
+    }
+
+    /// Blocks while `condition` returns `true`.
+    pub fn wait_while<'a, T: ?Sized>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: impl FnMut(&mut T) -> bool,
+    ) -> MutexGuard<'a, T> {
+        while condition(&mut *guard) {
+            guard = self.wait(guard);
+        }
+        guard
+    }
+
+    pub fn notify_one(&self) {
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        futex_wake(&self.seq, 1);
+    }
+
+    pub fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        futex_wake(&self.seq, u32::MAX);
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
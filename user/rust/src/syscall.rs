@@ -1,13 +1,29 @@
-use super::time::{ClockId, TimeSpec};
-use crate::arch::syscall;
+use core::sync::atomic::AtomicU32;
 
-pub use crate::arch::sys_clone;
+use super::ipc::MqAttr;
+use super::sched::{SchedAttr, SchedParam};
+use super::signal::{SigAction, SigSet};
+use super::time::{ClockId, RUsage, TimeSpec, Tms};
+use crate::arch::{syscall, syscall6};
+
+pub use crate::arch::{sys_clone, sys_rt_sigreturn};
 
 pub const SYSCALL_READ: usize = 0;
 pub const SYSCALL_WRITE: usize = 1;
 const SYSCALL_OPEN: usize = 2;
 const SYSCALL_CLOSE: usize = 3;
+pub const SYSCALL_MMAP: usize = 9;
+pub const SYSCALL_MPROTECT: usize = 10;
+pub const SYSCALL_MUNMAP: usize = 11;
+pub const SYSCALL_BRK: usize = 12;
+pub const SYSCALL_RT_SIGACTION: usize = 13;
+pub const SYSCALL_RT_SIGPROCMASK: usize = 14;
+pub const SYSCALL_RT_SIGRETURN: usize = 15;
+pub const SYSCALL_PIPE: usize = 22;
 pub const SYSCALL_YIELD: usize = 24;
+pub const SYSCALL_MSYNC: usize = 26;
+pub const SYSCALL_DUP: usize = 32;
+pub const SYSCALL_DUP2: usize = 33;
 const SYSCALL_CONNECT: usize = 29;
 pub const SYSCALL_GETPID: usize = 39;
 pub const SYSCALL_CLONE: usize = 56;
@@ -15,8 +31,29 @@ pub const SYSCALL_FORK: usize = 57;
 pub const SYSCALL_EXEC: usize = 59;
 pub const SYSCALL_EXIT: usize = 60;
 pub const SYSCALL_WAITPID: usize = 61;
+pub const SYSCALL_KILL: usize = 62;
+pub const SYSCALL_FTRUNCATE: usize = 77;
+pub const SYSCALL_UNLINK: usize = 87;
+pub const SYSCALL_GETRUSAGE: usize = 98;
+pub const SYSCALL_TIMES: usize = 100;
+pub const SYSCALL_GETPRIORITY: usize = 140;
+pub const SYSCALL_SETPRIORITY: usize = 141;
+pub const SYSCALL_SCHED_SETPARAM: usize = 142;
+pub const SYSCALL_SCHED_GETPARAM: usize = 143;
+pub const SYSCALL_SCHED_SETSCHEDULER: usize = 144;
+pub const SYSCALL_SCHED_GETSCHEDULER: usize = 145;
+pub const SYSCALL_GETTID: usize = 186;
+pub const SYSCALL_FUTEX: usize = 202;
 pub const SYSCALL_CLOCK_GETTIME: usize = 228;
 pub const SYSCALL_CLOCK_NANOSLEEP: usize = 230;
+pub const SYSCALL_EXIT_GROUP: usize = 231;
+pub const SYSCALL_MQ_OPEN: usize = 240;
+pub const SYSCALL_MQ_UNLINK: usize = 241;
+pub const SYSCALL_MQ_TIMEDSEND: usize = 242;
+pub const SYSCALL_MQ_TIMEDRECEIVE: usize = 243;
+pub const SYSCALL_MQ_GETSETATTR: usize = 245;
+pub const SYSCALL_SCHED_SETATTR: usize = 314;
+pub const SYSCALL_SCHED_GETATTR: usize = 315;
 
 pub fn sys_read(fd: usize, buffer: &mut [u8]) -> isize {
     syscall(
@@ -37,6 +74,88 @@ pub fn sys_close(fd: usize) -> isize {
     syscall(SYSCALL_CLOSE, [fd, 0, 0])
 }
 
+pub fn sys_pipe(fds: &mut [i32; 2]) -> isize {
+    syscall(SYSCALL_PIPE, [fds.as_mut_ptr() as usize, 0, 0])
+}
+
+pub fn sys_dup(fd: usize) -> isize {
+    syscall(SYSCALL_DUP, [fd, 0, 0])
+}
+
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    syscall(SYSCALL_DUP2, [old_fd, new_fd, 0])
+}
+
+pub fn sys_ftruncate(fd: usize, len: usize) -> isize {
+    syscall(SYSCALL_FTRUNCATE, [fd, len, 0])
+}
+
+pub fn sys_unlink(path: &str) -> isize {
+    syscall(SYSCALL_UNLINK, [path.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_mmap(
+    addr: usize,
+    len: usize,
+    prot: usize,
+    flags: usize,
+    fd: usize,
+    offset: usize,
+) -> isize {
+    syscall6(SYSCALL_MMAP, [addr, len, prot, flags, fd, offset])
+}
+
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+    syscall(SYSCALL_MUNMAP, [addr, len, 0])
+}
+
+pub fn sys_mprotect(addr: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_MPROTECT, [addr, len, prot])
+}
+
+pub fn sys_msync(addr: usize, len: usize, flags: usize) -> isize {
+    syscall(SYSCALL_MSYNC, [addr, len, flags])
+}
+
+pub fn sys_brk(brk: usize) -> isize {
+    syscall(SYSCALL_BRK, [brk, 0, 0])
+}
+
+pub fn sys_mq_open(name: &str, flags: u32, attr: *const MqAttr) -> isize {
+    let name = name.as_ptr() as usize;
+    syscall6(
+        SYSCALL_MQ_OPEN,
+        [name, flags as usize, 0, attr as usize, 0, 0],
+    )
+}
+
+pub fn sys_mq_unlink(name: &str) -> isize {
+    syscall(SYSCALL_MQ_UNLINK, [name.as_ptr() as usize, 0, 0])
+}
+
+pub fn sys_mq_timedsend(mqd: usize, msg: &[u8], prio: u32) -> isize {
+    let msg_ptr = msg.as_ptr() as usize;
+    syscall6(
+        SYSCALL_MQ_TIMEDSEND,
+        [mqd, msg_ptr, msg.len(), prio as usize, 0, 0],
+    )
+}
+
+pub fn sys_mq_timedreceive(mqd: usize, buf: &mut [u8], prio: *mut u32) -> isize {
+    let buf_ptr = buf.as_mut_ptr() as usize;
+    syscall6(
+        SYSCALL_MQ_TIMEDRECEIVE,
+        [mqd, buf_ptr, buf.len(), prio as usize, 0, 0],
+    )
+}
+
+pub fn sys_mq_getsetattr(mqd: usize, new_attr: *const MqAttr, old_attr: *mut MqAttr) -> isize {
+    syscall(
+        SYSCALL_MQ_GETSETATTR,
+        [mqd, new_attr as usize, old_attr as usize],
+    )
+}
+
 pub fn sys_connect(dest: u32, sport: u16, dport: u16) -> isize {
     syscall(
         SYSCALL_CONNECT,
@@ -49,6 +168,11 @@ pub fn sys_exit(exit_code: i32) -> ! {
     panic!("sys_exit never returns!");
 }
 
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    syscall(SYSCALL_EXIT_GROUP, [exit_code as usize, 0, 0]);
+    panic!("sys_exit_group never returns!");
+}
+
 pub fn sys_yield() -> isize {
     syscall(SYSCALL_YIELD, [0, 0, 0])
 }
@@ -57,12 +181,19 @@ pub fn sys_getpid() -> isize {
     syscall(SYSCALL_GETPID, [0, 0, 0])
 }
 
+pub fn sys_gettid() -> isize {
+    syscall(SYSCALL_GETTID, [0, 0, 0])
+}
+
 pub fn sys_fork() -> isize {
     syscall(SYSCALL_FORK, [0, 0, 0])
 }
 
-pub fn sys_exec(path: &str) -> isize {
-    syscall(SYSCALL_EXEC, [path.as_ptr() as usize, 0, 0])
+pub fn sys_exec(path: &str, argv: *const *const u8, envp: *const *const u8) -> isize {
+    syscall(
+        SYSCALL_EXEC,
+        [path.as_ptr() as usize, argv as usize, envp as usize],
+    )
 }
 
 pub fn sys_waitpid(pid: isize, exit_code: *mut i32, options: u32) -> isize {
@@ -72,6 +203,88 @@ pub fn sys_waitpid(pid: isize, exit_code: *mut i32, options: u32) -> isize {
     )
 }
 
+pub fn sys_kill(pid: isize, sig: usize) -> isize {
+    syscall(SYSCALL_KILL, [pid as usize, sig, 0])
+}
+
+pub fn sys_rt_sigaction(sig: usize, act: *const SigAction, oldact: *mut SigAction) -> isize {
+    syscall(SYSCALL_RT_SIGACTION, [sig, act as usize, oldact as usize])
+}
+
+pub fn sys_rt_sigprocmask(how: usize, set: *const SigSet, oldset: *mut SigSet) -> isize {
+    syscall(SYSCALL_RT_SIGPROCMASK, [how, set as usize, oldset as usize])
+}
+
+pub fn sys_getpriority(which: usize, who: usize) -> isize {
+    syscall(SYSCALL_GETPRIORITY, [which, who, 0])
+}
+
+pub fn sys_setpriority(which: usize, who: usize, nice: i32) -> isize {
+    syscall(SYSCALL_SETPRIORITY, [which, who, nice as usize])
+}
+
+pub fn sys_sched_setparam(pid: usize, param: &SchedParam) -> isize {
+    syscall(SYSCALL_SCHED_SETPARAM, [pid, param as *const _ as usize, 0])
+}
+
+pub fn sys_sched_getparam(pid: usize, param: &mut SchedParam) -> isize {
+    syscall(SYSCALL_SCHED_GETPARAM, [pid, param as *mut _ as usize, 0])
+}
+
+pub fn sys_sched_setscheduler(pid: usize, policy: usize, param: &SchedParam) -> isize {
+    syscall(
+        SYSCALL_SCHED_SETSCHEDULER,
+        [pid, policy, param as *const _ as usize],
+    )
+}
+
+pub fn sys_sched_getscheduler(pid: usize) -> isize {
+    syscall(SYSCALL_SCHED_GETSCHEDULER, [pid, 0, 0])
+}
+
+pub fn sys_futex(
+    uaddr: &AtomicU32,
+    op: u32,
+    val: u32,
+    timeout: usize,
+    uaddr2: *const AtomicU32,
+    val3: u32,
+) -> isize {
+    syscall6(
+        SYSCALL_FUTEX,
+        [
+            uaddr as *const _ as usize,
+            op as usize,
+            val as usize,
+            timeout,
+            uaddr2 as usize,
+            val3 as usize,
+        ],
+    )
+}
+
+pub fn sys_sched_setattr(pid: usize, attr: &SchedAttr, flags: u32) -> isize {
+    syscall(
+        SYSCALL_SCHED_SETATTR,
+        [pid, attr as *const _ as usize, flags as usize],
+    )
+}
+
+pub fn sys_sched_getattr(pid: usize, attr: &mut SchedAttr, size: usize) -> isize {
+    syscall(SYSCALL_SCHED_GETATTR, [pid, attr as *mut _ as usize, size])
+}
+
+pub fn sys_getrusage(who: isize, usage: &mut RUsage) -> isize {
+    syscall(
+        SYSCALL_GETRUSAGE,
+        [who as usize, usage as *mut _ as usize, 0],
+    )
+}
+
+pub fn sys_times(tms: &mut Tms) -> isize {
+    syscall(SYSCALL_TIMES, [tms as *mut _ as usize, 0, 0])
+}
+
 pub fn sys_clock_gettime(clk: ClockId, req: &mut TimeSpec) -> isize {
     syscall(SYSCALL_CLOCK_GETTIME, [clk as _, req as *mut _ as usize, 0])
 }
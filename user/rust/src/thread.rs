@@ -0,0 +1,221 @@
+//! Threads sharing the address space of the process.
+
+use alloc::alloc::{alloc_zeroed, dealloc};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::alloc::Layout;
+use core::ptr::copy_nonoverlapping;
+use core::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+
+use super::arch::set_thread_pointer;
+use super::sync::{futex_wait, Mutex};
+use super::syscall::{sys_clone, sys_exit, sys_gettid};
+use super::{mmap, mprotect, munmap, MmapFlags, MmapProt};
+
+const PAGE_SIZE: usize = 4096;
+const THREAD_STACK_SIZE: usize = 4096 * 4; // 16K
+
+const CLONE_SETTLS: usize = 0x8_0000;
+const CLONE_CHILD_CLEARTID: usize = 0x20_0000;
+const CLONE_CHILD_SETTID: usize = 0x100_0000;
+
+struct Thread {
+    /// The thread id set by the kernel before the thread runs, and cleared with a futex
+    /// wake-up when it exits.
+    ctid: AtomicU32,
+    entry: fn(usize) -> i32,
+    arg: usize,
+    exit_code: AtomicI32,
+    /// The start of the stack mapping, whose lowest page is the guard page.
+    stack: usize,
+    /// The TLS block, or 0 if the program has no TLS.
+    tls_block: usize,
+}
+
+/// Threads not joined yet, by thread id.
+static THREADS: Mutex<BTreeMap<isize, Arc<Thread>>> = Mutex::new(BTreeMap::new());
+
+impl Drop for Thread {
+    fn drop(&mut self) {
+        munmap(self.stack, PAGE_SIZE + THREAD_STACK_SIZE);
+        if let Some(image) = unsafe { TLS_IMAGE.as_ref() }.filter(|_| self.tls_block != 0) {
+            image.free_block(self.tls_block as *mut u8);
+        }
+    }
+}
+
+/// Maps a thread stack with a guard page below it, so that overflowing the stack
+/// faults. Returns the start of the mapping.
+fn alloc_stack() -> Option<usize> {
+    let size = PAGE_SIZE + THREAD_STACK_SIZE;
+    let prot = MmapProt::READ | MmapProt::WRITE;
+    let flags = MmapFlags::PRIVATE | MmapFlags::ANONYMOUS | MmapFlags::STACK;
+    let stack = mmap(0, size, prot, flags, -1, 0);
+    if stack < 0 {
+        return None;
+    }
+    let stack = stack as usize;
+    if mprotect(stack, PAGE_SIZE, MmapProt::empty()) != 0 {
+        munmap(stack, size);
+        return None;
+    }
+    Some(stack)
+}
+
+const PT_TLS: u32 = 7;
+
+#[repr(C)]
+struct ProgramHeader {
+    p_type: u32,
+    p_flags: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_paddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+    p_align: u64,
+}
+
+/// The initial TLS data of each thread, from the `PT_TLS` segment of the program.
+struct TlsImage {
+    data: *const u8,
+    filesz: usize,
+    memsz: usize,
+    align: usize,
+}
+
+static mut TLS_IMAGE: Option<TlsImage> = None;
+
+/// Finds the `PT_TLS` segment in the program headers at `phdr` and sets up the TLS
+/// block of the main thread, called before `main()`.
+pub(crate) unsafe fn init_tls(phdr: usize, phnum: usize) {
+    if phdr == 0 {
+        return;
+    }
+    let phdrs = core::slice::from_raw_parts(phdr as *const ProgramHeader, phnum);
+    let tls_ph = phdrs.iter().find(|ph| ph.p_type == PT_TLS);
+    if let Some(ph) = tls_ph.filter(|ph| ph.p_memsz > 0) {
+        let image = TLS_IMAGE.insert(TlsImage {
+            data: ph.p_vaddr as *const u8,
+            filesz: ph.p_filesz as usize,
+            memsz: ph.p_memsz as usize,
+            align: (ph.p_align as usize).max(1),
+        });
+        // never freed, as the main thread is not joined
+        let (_, tp) = image.alloc_block();
+        set_thread_pointer(tp);
+    }
+}
+
+const fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) / align * align
+}
+
+impl TlsImage {
+    /// Returns the layout of a TLS block, the offset of the TLS data and the offset of
+    /// the thread pointer in it.
+    fn block_layout(&self) -> (Layout, usize, usize) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            // the data is below the thread pointer, where `%fs:0` points to itself
+            let tp = align_up(self.memsz, self.align);
+            let size = tp + core::mem::size_of::<usize>();
+            let layout = Layout::from_size_align(size, self.align.max(8)).unwrap();
+            (layout, 0, tp)
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            // the data follows a 16-byte TCB at the thread pointer
+            let data = align_up(16, self.align);
+            let layout = Layout::from_size_align(data + self.memsz, self.align.max(16)).unwrap();
+            (layout, data, 0)
+        }
+        #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+        {
+            // the data starts at the thread pointer
+            let layout = Layout::from_size_align(self.memsz, self.align).unwrap();
+            (layout, 0, 0)
+        }
+    }
+
+    /// Allocates a TLS block initialized from the image, returns the block and the
+    /// thread pointer.
+    fn alloc_block(&self) -> (*mut u8, usize) {
+        let (layout, data, tp) = self.block_layout();
+        unsafe {
+            let block = alloc_zeroed(layout);
+            assert!(!block.is_null());
+            copy_nonoverlapping(self.data, block.add(data), self.filesz);
+            let tp = block as usize + tp;
+            #[cfg(target_arch = "x86_64")]
+            (tp as *mut usize).write(tp);
+            (block, tp)
+        }
+    }
+
+    fn free_block(&self, block: *mut u8) {
+        unsafe { dealloc(block, self.block_layout().0) };
+    }
+}
+
+fn thread_start(thread: usize) -> i32 {
+    let thread = unsafe { &*(thread as *const Thread) };
+    let exit_code = (thread.entry)(thread.arg);
+    thread.exit_code.store(exit_code, Ordering::Release);
+    exit_code
+}
+
+/// Creates a thread running `entry(arg)`, returns the thread id.
+pub fn thread_spawn(entry: fn(usize) -> i32, arg: usize) -> isize {
+    let stack = match alloc_stack() {
+        Some(stack) => stack,
+        None => return -1,
+    };
+    let tls = unsafe { TLS_IMAGE.as_ref() }.map(|image| image.alloc_block());
+    let thread = Arc::new(Thread {
+        ctid: AtomicU32::new(0),
+        entry,
+        arg,
+        exit_code: AtomicI32::new(0),
+        stack,
+        tls_block: tls.map_or(0, |(block, _)| block as usize),
+    });
+    let newsp = stack + PAGE_SIZE + THREAD_STACK_SIZE;
+    let ctid = &thread.ctid as *const AtomicU32 as *mut u32;
+    let mut flags = CLONE_CHILD_SETTID | CLONE_CHILD_CLEARTID;
+    if tls.is_some() {
+        flags |= CLONE_SETTLS;
+    }
+    let tls = tls.map_or(0, |(_, tp)| tp);
+    let arg = Arc::as_ptr(&thread) as usize;
+    // locked until the thread is added, so that `thread_exit()` finds it
+    let mut threads = THREADS.lock();
+    let tid = sys_clone(thread_start, arg, newsp, flags, ctid, tls);
+    if tid > 0 {
+        threads.insert(tid, thread);
+    }
+    tid
+}
+
+/// Exits the current thread, the process exits after its last thread exits.
+pub fn thread_exit(exit_code: i32) -> ! {
+    if let Some(thread) = THREADS.lock().get(&sys_gettid()) {
+        thread.exit_code.store(exit_code, Ordering::Release);
+    }
+    sys_exit(exit_code)
+}
+
+/// Waits for the thread `tid` to exit and frees its stack, returns its exit code, or
+/// `None` if there is no such thread or it is already joined.
+pub fn thread_join(tid: isize) -> Option<i32> {
+    let thread = THREADS.lock().get(&tid)?.clone();
+    loop {
+        let ctid = thread.ctid.load(Ordering::Acquire);
+        if ctid == 0 {
+            break;
+        }
+        futex_wait(&thread.ctid, ctid, None);
+    }
+    THREADS.lock().remove(&tid);
+    Some(thread.exit_code.load(Ordering::Acquire))
+}
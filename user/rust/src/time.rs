@@ -9,6 +9,39 @@ pub struct TimeSpec {
     pub nsec: usize,
 }
 
+#[repr(C)]
+#[derive(Default)]
+pub struct TimeVal {
+    /// seconds
+    pub sec: usize,
+    /// micro seconds
+    pub usec: usize,
+}
+
+impl TimeVal {
+    pub const fn as_micros(&self) -> usize {
+        self.sec * 1_000_000 + self.usec
+    }
+}
+
+/// Times in clock ticks, there are `CLK_TCK` ticks per second.
+#[repr(C)]
+#[derive(Default)]
+pub struct Tms {
+    pub tms_utime: usize,
+    pub tms_stime: usize,
+    pub tms_cutime: usize,
+    pub tms_cstime: usize,
+}
+
+#[repr(C)]
+#[derive(Default)]
+pub struct RUsage {
+    pub ru_utime: TimeVal,
+    pub ru_stime: TimeVal,
+    __reserved: [isize; 14],
+}
+
 pub type ClockId = u32;
 
 pub const CLOCK_REALTIME: ClockId = 0;
@@ -16,6 +49,20 @@ pub const CLOCK_MONOTONIC: ClockId = 1;
 
 pub const TIMER_ABSTIME: u32 = 1;
 
+pub const CLK_TCK: usize = 100;
+
+pub const RUSAGE_SELF: isize = 0;
+pub const RUSAGE_CHILDREN: isize = -1;
+
+/// Returns the clock ticks elapsed since boot.
+pub fn times(tms: &mut Tms) -> isize {
+    sys_times(tms)
+}
+
+pub fn getrusage(who: isize, usage: &mut RUsage) -> isize {
+    sys_getrusage(who, usage)
+}
+
 pub fn clock_gettime(clk: ClockId, req: &mut TimeSpec) -> isize {
     sys_clock_gettime(clk, req)
 }